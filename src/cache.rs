@@ -0,0 +1,310 @@
+use crate::config;
+use crate::pattern::FilePattern;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Location of the scan cache, relative to the project root
+pub const CACHE_PATH: &str = ".scaff/cache.json";
+
+/// Caches written by another version of scaff are discarded, since its scanner may
+/// have extracted different items from the same file
+const SCANNER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Where the scan cache for files under `scanned_dir` is read from and written to,
+/// whether or not it exists: in the scanned directory's project root. Outside of one
+/// there's no cache, since creating `.scaff/` would make the scanned directory a
+/// project root itself. The current directory plays no part, so scanning another
+/// directory never writes a cache here.
+pub fn cache_path(scanned_dir: &Path) -> Option<PathBuf> {
+    let dir = fs::canonicalize(scanned_dir).unwrap_or_else(|_| scanned_dir.to_path_buf());
+    config::find_project_root(&dir).map(|root| root.join(CACHE_PATH))
+}
+
+/// Extracted file patterns from earlier scans, reused while a file's modification
+/// time and size are unchanged so it isn't parsed again
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    /// `SCANNER_VERSION` of the scaff that wrote the cache; absent in caches written
+    /// before it was recorded
+    #[serde(default)]
+    version: String,
+    /// Keyed by language and canonical file path, see `cache_key`
+    entries: HashMap<String, CacheEntry>,
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    modified_secs: u64,
+    modified_nanos: u32,
+    size: u64,
+    /// Whether `file.content_hash` was computed with whitespace normalized
+    normalize_whitespace: bool,
+    file: FilePattern,
+}
+
+// Modification time and size identifying one version of a file
+fn file_stamp(path: &Path) -> Option<(u64, u32, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_secs(), modified.subsec_nanos(), metadata.len()))
+}
+
+fn cache_key(path: &Path, language: &str) -> Option<String> {
+    let canonical = fs::canonicalize(path).ok()?;
+    Some(format!("{}:{}", language, canonical.display()))
+}
+
+impl ScanCache {
+    /// Load the cache at `path`, starting empty when it's absent, unreadable or was
+    /// written by another version of scaff
+    pub fn load(path: &Path) -> Self {
+        let mut cache = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring invalid scan cache {}: {}", path.display(), e);
+                ScanCache::default()
+            }),
+            Err(_) => ScanCache::default(),
+        };
+        if cache.version != SCANNER_VERSION {
+            if !cache.entries.is_empty() {
+                info!(
+                    "Discarding scan cache {} written by another version of scaff",
+                    path.display()
+                );
+            }
+            cache = ScanCache {
+                version: SCANNER_VERSION.to_string(),
+                ..ScanCache::default()
+            };
+        }
+        cache.path = path.to_path_buf();
+        cache
+    }
+
+    /// The cached pattern for `path`, unless the file changed since it was cached
+    pub fn get(
+        &self,
+        path: &Path,
+        language: &str,
+        normalize_whitespace: bool,
+    ) -> Option<FilePattern> {
+        let entry = self.entries.get(&cache_key(path, language)?)?;
+        let (modified_secs, modified_nanos, size) = file_stamp(path)?;
+        if entry.modified_secs != modified_secs
+            || entry.modified_nanos != modified_nanos
+            || entry.size != size
+            || entry.normalize_whitespace != normalize_whitespace
+        {
+            debug!("Scan cache entry for {} is stale", path.display());
+            return None;
+        }
+
+        // The same file may be reached through another relative path this time
+        Some(FilePattern {
+            path: path.to_string_lossy().to_string(),
+            ..entry.file.clone()
+        })
+    }
+
+    pub fn insert(
+        &mut self,
+        path: &Path,
+        language: &str,
+        normalize_whitespace: bool,
+        file: &FilePattern,
+    ) {
+        let (Some(key), Some((modified_secs, modified_nanos, size))) =
+            (cache_key(path, language), file_stamp(path))
+        else {
+            return;
+        };
+        self.entries.insert(
+            key,
+            CacheEntry {
+                modified_secs,
+                modified_nanos,
+                size,
+                normalize_whitespace,
+                file: file.clone(),
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Write the cache back if anything was added, dropping entries for deleted files
+    pub fn save(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        self.entries.retain(|key, _| {
+            key.split_once(':')
+                .is_some_and(|(_, path)| Path::new(path).exists())
+        });
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(self)?)?;
+        info!(
+            "Saved {} scan cache entries to {}",
+            self.entries.len(),
+            self.path.display()
+        );
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Delete the cache file, returning whether there was one
+    pub fn clear(path: &Path) -> std::io::Result<bool> {
+        match fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{self, ScanOptions};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cached_scan_matches_fresh_scan() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let src = temp_dir.path().join("src");
+        fs::create_dir_all(&src)?;
+        fs::write(src.join("lib.rs"), "pub struct Config;\npub fn run() {}")?;
+        let cache_file = temp_dir.path().join(".scaff/cache.json");
+
+        let scan = || {
+            let options = ScanOptions {
+                cache: Some(Rc::new(RefCell::new(ScanCache::load(&cache_file)))),
+                ..ScanOptions::default()
+            };
+            let files = scanner::scan_language_files_in_dir_with_options(
+                src.to_str().unwrap(),
+                "rust",
+                &options,
+            );
+            options.save_cache();
+            files
+        };
+
+        let first = scan();
+        assert!(cache_file.exists());
+        let second = scan();
+        assert_eq!(
+            serde_json::to_value(&first)?,
+            serde_json::to_value(&second)?
+        );
+
+        let cache = ScanCache::load(&cache_file);
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.get(&src.join("lib.rs"), "rust", false).is_some());
+        assert!(cache.get(&src.join("lib.rs"), "rust", true).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modified_file_busts_cache_entry() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("lib.rs");
+        fs::write(&file, "pub fn run() {}")?;
+
+        let mut cache = ScanCache::load(&temp_dir.path().join("cache.json"));
+        let pattern = FilePattern {
+            path: file.to_string_lossy().to_string(),
+            functions: vec!["run".to_string()],
+            ..Default::default()
+        };
+        cache.insert(&file, "rust", false, &pattern);
+        assert!(cache.get(&file, "rust", false).is_some());
+
+        // A different size invalidates the entry even if the mtime didn't move
+        fs::write(&file, "pub fn run() {}\npub fn stop() {}")?;
+        assert!(cache.get(&file, "rust", false).is_none());
+
+        let options = ScanOptions {
+            cache: Some(Rc::new(RefCell::new(cache))),
+            ..ScanOptions::default()
+        };
+        let files = scanner::scan_language_files_in_dir_with_options(
+            temp_dir.path().to_str().unwrap(),
+            "rust",
+            &options,
+        );
+        assert_eq!(files[0].functions, vec!["run", "stop"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_from_another_version_is_discarded() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("lib.rs");
+        fs::write(&file, "pub fn run() {}")?;
+        let cache_file = temp_dir.path().join("cache.json");
+
+        let mut cache = ScanCache::load(&cache_file);
+        cache.insert(&file, "rust", false, &FilePattern::default());
+        cache.save()?;
+        assert!(
+            ScanCache::load(&cache_file)
+                .get(&file, "rust", false)
+                .is_some()
+        );
+
+        let mut written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&cache_file)?)?;
+        written["version"] = "0.0.0-old".into();
+        fs::write(&cache_file, written.to_string())?;
+        assert!(
+            ScanCache::load(&cache_file)
+                .get(&file, "rust", false)
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_path_follows_scanned_directory() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let project = fs::canonicalize(temp_dir.path())?;
+        let nested = project.join("app/src");
+        fs::create_dir_all(&nested)?;
+        fs::create_dir(project.join(".git"))?;
+        assert_eq!(cache_path(&nested), None);
+
+        fs::create_dir_all(project.join("scaffs"))?;
+        assert_eq!(cache_path(&nested), Some(project.join(CACHE_PATH)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let cache_file = temp_dir.path().join("cache.json");
+        fs::write(&cache_file, "{}")?;
+
+        assert!(ScanCache::clear(&cache_file)?);
+        assert!(!cache_file.exists());
+        assert!(!ScanCache::clear(&cache_file)?);
+
+        Ok(())
+    }
+}
@@ -1,273 +1,1751 @@
-use crate::generator::CodeGenerator;
-use crate::pattern::{ScaffDirectory, create_pattern_from_scan, display_pattern_summary};
-use crate::scanner;
-use crate::validator::ArchitectureValidator;
-use clap::{Parser, Subcommand};
+use crate::cache::{self, ScanCache};
+use crate::config::{self, ScaffConfig};
+use crate::diff;
+use crate::doctor;
+use crate::error::ScaffError;
+use crate::generator::{self, CodeGenerator, TargetLanguage, TemplateSource};
+use crate::git;
+use crate::pattern::{
+    self, FilePattern, ListOptions, ListSort, OptionalItem, SCHEMA_VERSION, ScaffDirectory,
+    comparable_items, create_pattern_from_scan, scaff_file_stem, select_files,
+};
+use crate::postprocess;
+use crate::report;
+use crate::scanner::{self, ScanOptions};
+use crate::stats;
+use crate::style;
+use crate::symbols;
+use crate::validator::{
+    ArchitectureValidator, COMPARED_ITEM_TYPES, CountExpectation, DEFAULT_FUZZY_THRESHOLD,
+    MissingFileSeverity, ValidationResult, normalize_path,
+};
+#[cfg(feature = "watch")]
+use crate::watch;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dialoguer::MultiSelect;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 #[derive(Parser)]
 #[command(name = "scaff")]
 #[command(about = "Architecture in your pocket", long_about = None)]
-struct Cli {
+pub struct Cli {
+    /// Log more: -v for info, -vv for debug, -vvv for trace (RUST_LOG takes precedence)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Don't log anything, not even errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Print ASCII markers like [OK] and [FAIL] instead of emoji (also the default when
+    /// NO_COLOR is set or stdout isn't a terminal)
+    #[arg(long, global = true)]
+    no_emoji: bool,
+    /// Don't color output (also the default when NO_COLOR is set or stdout isn't a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// Read and write scaffs in this directory instead of SCAFF_DIR, the config's
+    /// scaff_dir or scaffs/
+    #[arg(long, value_name = "PATH", global = true)]
+    scaffs_dir: Option<PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
 
+/// File selection options shared by commands that scan a directory
+#[derive(Args)]
+struct ScanFilterArgs {
+    /// Don't skip .gitignore'd or .scaffignore'd paths or target/, node_modules/, .git/, dist/ and .scaff/
+    #[arg(long)]
+    no_ignore: bool,
+    /// Ignore whitespace-only differences when hashing file content
+    #[arg(long)]
+    normalize_whitespace: bool,
+    /// Skip files larger than this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = scanner::DEFAULT_MAX_FILE_SIZE)]
+    max_file_size: u64,
+    /// Only scan files matching this glob, relative to the scanned directory (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+    /// Skip files matching this glob, relative to the scanned directory (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// Descend at most N directory levels; 1 scans only the directory's own files
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    depth: Option<u32>,
+    /// Follow symlinked files and directories (links back into the tree are still skipped)
+    #[arg(long)]
+    follow_symlinks: bool,
+    /// Parse every file instead of reusing results cached in the scanned project's
+    /// .scaff/cache.json
+    #[arg(long)]
+    no_cache: bool,
+    /// Store file paths relative to this directory instead of the scanned one, e.g. '.'
+    /// to keep the --path prefix
+    #[arg(long, value_name = "DIR")]
+    base_path: Option<PathBuf>,
+}
+
+impl ScanFilterArgs {
+    // Options for scanning `path`, whose project the scan cache belongs to
+    fn scan_options(&self, path: &Path) -> Result<ScanOptions, globset::Error> {
+        Ok(ScanOptions {
+            respect_ignore: !self.no_ignore,
+            normalize_whitespace: self.normalize_whitespace,
+            max_file_size: self.max_file_size,
+            include: scanner::build_glob_set(&self.include)?,
+            exclude: scanner::build_glob_set(&self.exclude)?,
+            max_depth: self.depth.map(|depth| depth as usize),
+            follow_symlinks: self.follow_symlinks,
+            cache: cache::cache_path(path)
+                .filter(|_| !self.no_cache)
+                .map(|path| Rc::new(RefCell::new(ScanCache::load(&path)))),
+            base_path: self.base_path.clone(),
+            restrict_to: None,
+        })
+    }
+}
+
+/// How a command prints its results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// Pretty-printed JSON
+    Json,
+}
+
+/// How `scaff show` prints a scaff
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ShowFormat {
+    /// Human-readable text
+    Text,
+    /// The raw pattern as pretty-printed JSON
+    Json,
+    /// A Mermaid class diagram of the files, grouped by directory
+    Mermaid,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Scan the codebase for patterns
     Scan {
+        /// Directory to scan
+        #[arg(short, long, default_value = ".")]
+        path: String,
         /// Language to scan for (js, rust, or all)
         #[arg(short, long, default_value = "all")]
         language: String,
+        /// Scan for the language with the most files, or all on a tie
+        #[arg(long, conflicts_with = "language")]
+        auto: bool,
+        /// Only scan files changed since this git ref (e.g. main); outside a git
+        /// repository every file is scanned
+        #[arg(
+            long,
+            visible_alias = "since",
+            value_name = "GIT_REF",
+            conflicts_with = "follow_imports"
+        )]
+        changed_since: Option<String>,
+        /// Entry file to start from when following imports
+        #[arg(long, value_name = "FILE")]
+        entry: Option<String>,
+        /// Only scan files reachable from --entry through mod/use/import declarations
+        #[arg(long, requires = "entry")]
+        follow_imports: bool,
+        #[command(flatten)]
+        filters: ScanFilterArgs,
+        /// Scan just this file, with the language taken from its extension unless --language is
+        /// given; fails if --include, --exclude or --max-file-size would skip it
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["auto", "changed_since", "follow_imports", "stream_json", "only_files"])]
+        file: Option<String>,
+        /// Scan source read from stdin as --language
+        #[arg(long, conflicts_with_all = ["file", "auto", "changed_since", "follow_imports", "stream_json", "only_files"])]
+        stdin: bool,
+        /// Stream results to stdout as a JSON array without buffering the whole scan
+        #[arg(long, conflicts_with = "follow_imports")]
+        stream_json: bool,
+        /// Only list the files the scan would cover, without parsing them
+        #[arg(long, conflicts_with_all = ["follow_imports", "stream_json"])]
+        only_files: bool,
+        /// Truncate item names longer than this in the output
+        #[arg(long, default_value_t = scanner::DEFAULT_MAX_ITEM_NAME_LENGTH)]
+        max_item_name_length: usize,
+        /// Output format; json prints the scanned files (per language with --language all)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with_all = ["stream_json", "only_files"])]
+        format: OutputFormat,
     },
     /// Save a detected pattern as a scaff
     Save {
         name: String,
-        /// Language to scan for (js, rust, or all)
+        /// Directory to scan, e.g. a reference project elsewhere; file paths are saved
+        /// relative to it
+        #[arg(short, long, visible_alias = "from-dir", default_value = ".")]
+        path: String,
+        #[command(flatten)]
+        filters: ScanFilterArgs,
+        /// Language to scan for (js, rust, or all to save every language found)
         #[arg(short, long, default_value = "all")]
         language: String,
+        /// Save the language with the most files, or all languages on a tie
+        #[arg(long, conflicts_with = "language")]
+        auto: bool,
+        /// Refuse to save if any file has syntax errors
+        #[arg(long)]
+        strict_parse: bool,
+        /// Pick which scanned files to include, then which of their items are optional,
+        /// from checklists
+        #[arg(short, long)]
+        interactive: bool,
+        /// Describe the scaff instead of using the generated file/item summary
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Label the scaff for 'scaff list --tag' (repeatable)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+    },
+    /// Re-scan a saved scaff's files, keeping its description, tags and creation date
+    Update {
+        name: String,
+        /// Directory to scan
+        #[arg(short, long, default_value = ".")]
+        path: String,
+        /// Language to scan for instead of the scaff's own, e.g. rust
+        #[arg(short, long)]
+        language: Option<String>,
     },
     /// List available scaffs
-    List {},
+    List {
+        /// Break item counts down by category (classes, functions, structs, ...)
+        #[arg(long, alias = "pattern-stats")]
+        detailed: bool,
+        /// Only list scaffs with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only list scaffs of this language, e.g. rust
+        #[arg(short, long)]
+        language: Option<String>,
+        #[arg(long, value_enum, default_value = "name")]
+        sort: ListSort,
+    },
+    /// Show the files and items a scaff contains
+    Show {
+        /// Scaff to show; may be omitted when only one scaff is saved
+        scaff: Option<String>,
+        /// Print the raw pattern as JSON, same as --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        #[arg(long, value_enum, default_value_t = ShowFormat::Text)]
+        format: ShowFormat,
+    },
+    /// Rewrite scaffs saved with an older format in the current one
+    Migrate {
+        /// Scaff to migrate
+        #[arg(required_unless_present = "all")]
+        scaff: Option<String>,
+        /// Migrate every saved scaff
+        #[arg(long, conflicts_with = "scaff")]
+        all: bool,
+    },
+    /// Show the structural differences between two scaffs
+    Diff {
+        /// Base scaff, shown with '-'
+        a: String,
+        /// Scaff to compare against it, shown with '+'
+        b: String,
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Summarize all saved scaffs: languages, sizes and age
+    Stats {
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Write a scaff to a single shareable file
+    Export {
+        scaff: String,
+        /// File to write; a .scaff extension gzip-compresses it [default: <scaff>.json]
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Install a scaff from a file written by 'scaff export'
+    Import {
+        file: String,
+        /// Replace an existing scaff with the same name
+        #[arg(long)]
+        force: bool,
+    },
     /// Generate code from a scaff
     Generate {
         scaff: String,
         /// Output directory for generated code
         #[arg(short, long, default_value = "generated")]
         output: String,
+        /// Template to render files with instead of rust_file/js_file
+        #[arg(long)]
+        template: Option<String>,
+        /// Fail if the template isn't registered instead of using the built-in default
+        #[arg(long, alias = "no-default-template")]
+        require_template: bool,
+        /// Write files with extension FROM as TO instead, e.g. rs=txt (repeatable)
+        #[arg(long, value_name = "FROM=TO", value_parser = parse_extension_mapping)]
+        output_ext: Vec<(String, String)>,
+        /// List the files that would be written without touching disk
+        #[arg(long)]
+        dry_run: bool,
+        /// With --dry-run, also print each file's rendered content
+        #[arg(long, requires = "dry_run")]
+        print: bool,
+        /// Print only the paths that would be written, one per line. Existing files are
+        /// left out unless --force is given, and an existing manifest always is.
+        #[arg(long, conflicts_with_all = ["dry_run", "diff"])]
+        list_files: bool,
+        /// Overwrite files that already exist instead of skipping them. An existing
+        /// project manifest is always kept.
+        #[arg(long)]
+        force: bool,
+        /// Print a unified diff for existing files that would change instead of writing them
+        #[arg(long, conflicts_with = "force")]
+        diff: bool,
+        /// Only generate scaff files whose path matches this glob, e.g. 'src/services/**' (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        only: Vec<String>,
+        /// Generate into this language instead of the scaff's own, mapping structs to classes
+        #[arg(long, value_enum)]
+        target_language: Option<TargetLanguage>,
+        /// Set {{vars.KEY}} for templates, overriding the scaff's variables (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+        vars: Vec<(String, String)>,
+        /// Run rustfmt on generated .rs files and prettier on .js/.ts files, skipping
+        /// formatters that aren't on PATH
+        #[arg(long, overrides_with = "no_format_output")]
+        format_output: bool,
+        /// Leave generated files as the templates render them (the default)
+        #[arg(long)]
+        no_format_output: bool,
+    },
+    /// Inspect scaff's configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
     },
+    /// Manage the scan cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// List and inspect generation templates
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    /// Show version, grammar and directory information for bug reports
+    Info {},
+    /// Check the config, scaffs, default scaff and templates for setup problems, exiting 1 if any
+    Doctor {},
+    /// Print the JSON Schema of scaff files, for checking hand-edited scaffs in editors or CI
+    Schema {},
     /// Validate codebase against a scaff
-    Validate { scaff: String },
+    Validate {
+        /// Scaffs to validate against, e.g. 'scaff validate service shared-lib'
+        #[arg(value_name = "SCAFF", required_unless_present = "all")]
+        scaffs: Vec<String>,
+        /// Validate against every saved scaff; with several scaffs, any failure exits non-zero
+        #[arg(long, conflicts_with = "scaffs")]
+        all: bool,
+        /// Directory to validate
+        #[arg(short, long, default_value = ".")]
+        path: String,
+        /// Only compare files of the scaff's exact language (e.g. no .js for a TypeScript scaff)
+        #[arg(long)]
+        strict_language_match: bool,
+        /// Report file paths relative to the base directory (no leading ./)
+        #[arg(long)]
+        base_dir_relative: bool,
+        /// Whether files missing from the codebase fail validation
+        #[arg(long, value_enum, default_value = "error")]
+        treat_missing_file_as: MissingFileSeverity,
+        /// Require at least n items of a type, optionally in one file: [file:]type=n (repeatable)
+        #[arg(long, value_name = "[FILE:]TYPE=N")]
+        expect_count: Vec<CountExpectation>,
+        /// Compare per-file item counts per category instead of item names
+        #[arg(long)]
+        count_match: bool,
+        /// Fail on extra files and extra items too, not just missing ones
+        #[arg(long)]
+        strict: bool,
+        /// Don't compare items of this type, e.g. function (repeatable)
+        #[arg(long = "ignore-type", value_name = "TYPE", value_parser = COMPARED_ITEM_TYPES)]
+        ignore_types: Vec<String>,
+        /// Report a missing item with a similarly named extra item as renamed
+        #[arg(long)]
+        fuzzy: bool,
+        /// Name similarity (0.0-1.0, Jaro-Winkler) --fuzzy needs to call an item renamed
+        #[arg(long, value_name = "SIMILARITY", requires = "fuzzy", value_parser = parse_similarity)]
+        fuzzy_threshold: Option<f64>,
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+        /// Generate the scaff's missing files, then validate again; existing files are never overwritten
+        #[arg(long, conflicts_with = "format")]
+        fix: bool,
+        /// Parse every file instead of reusing results cached in the scanned project's
+        /// .scaff/cache.json
+        #[arg(long)]
+        no_cache: bool,
+        /// Only validate files changed since this git ref (e.g. main), plus scaff files
+        /// that don't exist yet; outside a git repository every file is validated
+        #[arg(long, visible_alias = "since", value_name = "GIT_REF")]
+        changed_since: Option<String>,
+        /// Keep running and re-validate whenever the directory or the scaffs change
+        #[cfg(feature = "watch")]
+        #[arg(long, conflicts_with = "all")]
+        watch: bool,
+    },
 }
 
-pub fn run() {
-    let cli = Cli::parse();
-    match cli.command {
-        Commands::Scan { language } => {
-            println!("🔍 Scanning the codebase for patterns...");
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Delete the cached scan results so the next scan parses every file
+    Clear,
+}
 
-            match language.as_str() {
-                "js" | "javascript" => {
-                    let files = scanner::scan_language_files_in_dir(".", "javascript");
-                    scanner::display_scan_results(&files, "JavaScript");
+#[derive(Subcommand)]
+enum TemplateAction {
+    /// List templates in templates/ and the built-in defaults
+    List,
+    /// Print a template's source, from templates/ if it's there and built in otherwise
+    Show { name: String },
+}
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language javascript"
-                        );
-                    }
-                }
-                "ts" | "typescript" => {
-                    let files = scanner::scan_language_files_in_dir(".", "typescript");
-                    scanner::display_scan_results(&files, "TypeScript");
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print where the project root, config and scaffs directory resolve to
+    Path,
+}
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language typescript"
-                        );
-                    }
-                }
-                "python" | "py" => {
-                    let files = scanner::scan_language_files_in_dir(".", "python");
-                    scanner::display_scan_results(&files, "Python");
+impl Cli {
+    /// Maximum log level asked for with --verbose/--quiet; errors only by default
+    pub fn log_level(&self) -> log::LevelFilter {
+        if self.quiet {
+            return log::LevelFilter::Off;
+        }
+        match self.verbose {
+            0 => log::LevelFilter::Error,
+            1 => log::LevelFilter::Info,
+            2 => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Trace,
+        }
+    }
+}
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language python"
-                        );
-                    }
-                }
-                "java" => {
-                    let files = scanner::scan_language_files_in_dir(".", "java");
-                    scanner::display_scan_results(&files, "Java");
+pub fn run(cli: Cli) {
+    symbols::set_ascii(style::prefers_plain(cli.no_emoji));
+    style::set_color(!style::prefers_plain(cli.no_color));
+    let scaff_dir = cli
+        .scaffs_dir
+        .map_or_else(ScaffDirectory::new, ScaffDirectory::at);
+    match cli.command {
+        Commands::Scan {
+            path,
+            language,
+            auto,
+            changed_since,
+            entry,
+            follow_imports,
+            filters,
+            file,
+            stdin,
+            stream_json,
+            only_files,
+            max_item_name_length,
+            format,
+        } => {
+            if !Path::new(&path).is_dir() {
+                eprintln!(
+                    "{} {}",
+                    symbols::FAIL,
+                    ScaffError::MissingDirectory(PathBuf::from(&path))
+                );
+                std::process::exit(1);
+            }
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language java"
-                        );
+            let mut scan_options = match filters.scan_options(Path::new(&path)) {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("{} Invalid glob: {}", symbols::FAIL, e);
+                    std::process::exit(1);
+                }
+            };
+            if file.is_some() || stdin {
+                scan_single_source(
+                    file,
+                    &path,
+                    &scan_options,
+                    &language,
+                    format,
+                    max_item_name_length,
+                );
+                return;
+            }
+            if let Some(git_ref) = &changed_since {
+                match changed_files(&path, git_ref) {
+                    Ok(files) => {
+                        if let Some(files) = &files
+                            && !stream_json
+                            && !only_files
+                            && format == OutputFormat::Text
+                        {
+                            println!(
+                                "{} Limiting scan to {} files changed since {}",
+                                symbols::NOTE,
+                                files.len(),
+                                git_ref
+                            );
+                        }
+                        scan_options.restrict_to = files;
+                    }
+                    Err(e) => {
+                        eprintln!("{} Failed to list changed files: {}", symbols::FAIL, e);
+                        std::process::exit(1);
                     }
                 }
-                "go" => {
-                    let files = scanner::scan_language_files_in_dir(".", "go");
-                    scanner::display_scan_results(&files, "Go");
+            }
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language go"
-                        );
+            let language = if auto {
+                let detected = scanner::detect_primary_language(&path, &scan_options);
+                // Machine-readable output must stay parseable
+                if !stream_json && !only_files && format == OutputFormat::Text {
+                    match detected {
+                        Some(language) => println!(
+                            "{} Detected primary language: {}",
+                            symbols::LANGUAGE,
+                            language
+                        ),
+                        None => println!(
+                            "{} No single primary language, scanning all languages",
+                            symbols::INFO
+                        ),
                     }
                 }
-                "rust" => {
-                    let files = scanner::scan_rust_files_in_dir(".");
-                    scanner::display_scan_results(&files, "Rust");
+                detected.unwrap_or("all").to_string()
+            } else {
+                language
+            };
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language rust"
-                        );
-                    }
+            if stream_json || only_files {
+                let languages = match language.as_str() {
+                    "all" => scanner::get_supported_languages(),
+                    other => vec![canonical_language(other)],
+                };
+                let supported = scanner::get_supported_languages();
+                if let Some(unsupported) = languages.iter().find(|l| !supported.contains(l)) {
+                    eprintln!("{} Unsupported language: {}", symbols::FAIL, unsupported);
+                    std::process::exit(1);
                 }
-                "json" => {
-                    let files = scanner::scan_language_files_in_dir(".", "json");
-                    scanner::display_scan_results(&files, "JSON");
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language json"
-                        );
+                if only_files {
+                    for lang in languages {
+                        for file in scanner::matching_files_in_dir(&path, lang, &scan_options) {
+                            println!("{}", file.display());
+                        }
                     }
+                    return;
                 }
-                "html" => {
-                    let files = scanner::scan_language_files_in_dir(".", "html");
-                    scanner::display_scan_results(&files, "HTML");
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language html"
-                        );
-                    }
+                let mut stdout = std::io::stdout().lock();
+                if let Err(e) =
+                    scanner::write_scan_json(&path, &languages, &scan_options, &mut stdout)
+                {
+                    eprintln!("{} Failed to write scan results: {}", symbols::FAIL, e);
+                    std::process::exit(1);
                 }
-                "css" => {
-                    let files = scanner::scan_language_files_in_dir(".", "css");
-                    scanner::display_scan_results(&files, "CSS");
+                scan_options.save_cache();
+                return;
+            }
 
-                    if !files.is_empty() {
-                        println!(
-                            "\n💡 To save this pattern, run: scaff save <pattern-name> --language css"
-                        );
+            let json = format == OutputFormat::Json;
+            if json
+                && language != "all"
+                && !scanner::get_supported_languages().contains(&canonical_language(&language))
+            {
+                eprintln!("{} Unsupported language: {}", symbols::FAIL, language);
+                std::process::exit(1);
+            }
+            if !json {
+                println!("{} Scanning the codebase for patterns...", symbols::SEARCH);
+            }
+
+            let file_set = if follow_imports {
+                let entry = entry.unwrap_or_default();
+                let files = scanner::reachable_files(Path::new(&entry));
+                if files.is_empty() {
+                    println!("{} Entry file not found: {}", symbols::FAIL, entry);
+                    return;
+                }
+                if !json {
+                    println!(
+                        "{} Limiting scan to {} files reachable from {}",
+                        symbols::NOTE,
+                        files.len(),
+                        entry
+                    );
+                }
+                Some(files)
+            } else {
+                None
+            };
+            let scan = |lang: &str| {
+                let files = match &file_set {
+                    Some(files) => scanner::scan_language_files(files, lang, &scan_options),
+                    None => {
+                        scanner::scan_language_files_in_dir_with_options(&path, lang, &scan_options)
                     }
+                };
+                scan_options.save_cache();
+                files
+            };
+
+            if json {
+                let content = if language == "all" {
+                    let results = match &file_set {
+                        Some(files) => scanner::scan_all_languages_in_files(files, &scan_options),
+                        None => scanner::scan_all_languages_in_dir(&path, &scan_options),
+                    };
+                    scan_options.save_cache();
+                    serde_json::to_string_pretty(&results)
+                } else {
+                    serde_json::to_string_pretty(&scan(canonical_language(&language)))
+                };
+                match content {
+                    Ok(content) => println!("{}", content),
+                    Err(e) => println!("{} Failed to serialize scan results: {}", symbols::FAIL, e),
                 }
+                return;
+            }
+
+            match language.as_str() {
                 "all" => {
-                    let results = scanner::scan_all_languages_in_dir(".");
+                    let results = match &file_set {
+                        Some(files) => scanner::scan_all_languages_in_files(files, &scan_options),
+                        None => scanner::scan_all_languages_in_dir(&path, &scan_options),
+                    };
+                    scan_options.save_cache();
 
                     if results.is_empty() {
                         println!("No supported files found.");
-                        println!(
-                            "Supported languages: rust, javascript, typescript, python, java, go, json, html, css"
-                        );
+                        let supported = scanner::get_supported_languages();
+                        println!("Supported languages: {}", supported.join(", "));
                         return;
                     }
 
-                    scanner::display_all_scan_results(&results);
+                    report::display_all_scan_results(&results, max_item_name_length);
 
-                    println!("\n💡 To save a specific language pattern:");
-                    let supported_langs = scanner::get_supported_languages();
+                    println!("\n{} To save a specific language pattern:", symbols::TIP);
                     for (lang_display, _) in &results {
-                        // Convert display name back to language identifier
-                        let lang_name = supported_langs
-                            .iter()
-                            .find(|&lang| scanner::get_language_display_name(lang) == *lang_display)
-                            .unwrap_or(&"unknown");
-                        println!("   scaff save <pattern-name> --language {}", lang_name);
+                        if let Some(lang_name) = scanner::language_from_display(lang_display) {
+                            println!("   scaff save <pattern-name> --language {}", lang_name);
+                        }
                     }
                 }
-                _ => {
-                    println!("❌ Unsupported language: {}", language);
-                    let supported = scanner::get_supported_languages();
-                    println!("Supported languages: {}, all", supported.join(", "));
-                    return;
-                }
+                other => match scanner::language_config(other) {
+                    Some(config) => {
+                        let files = scan(config.name);
+                        report::display_scan_results(
+                            &files,
+                            config.display_name,
+                            max_item_name_length,
+                        );
+
+                        if !files.is_empty() {
+                            println!(
+                                "\n{} To save this pattern, run: scaff save <pattern-name> --language {}",
+                                symbols::TIP,
+                                config.name
+                            );
+                        }
+                    }
+                    None => {
+                        println!("{} Unsupported language: {}", symbols::FAIL, language);
+                        let supported = scanner::get_supported_languages();
+                        println!("Supported languages: {}, all", supported.join(", "));
+                    }
+                },
             }
         }
-        Commands::Save { name, language } => {
-            println!("💾 Saving pattern as scaff: {}", name);
+        Commands::Save {
+            name,
+            path,
+            filters,
+            language,
+            auto,
+            strict_parse,
+            interactive,
+            description,
+            tags,
+        } => {
+            println!("{} Saving pattern as scaff: {}", symbols::SAVE, name);
+            if !Path::new(&path).is_dir() {
+                eprintln!(
+                    "{} {}",
+                    symbols::FAIL,
+                    ScaffError::MissingDirectory(PathBuf::from(&path))
+                );
+                std::process::exit(1);
+            }
 
-            let (files, lang_type) = match language.as_str() {
-                "javascript" => (
-                    scanner::scan_language_files_in_dir(".", "javascript"),
-                    "JavaScript",
-                ),
-                "typescript" => (
-                    scanner::scan_language_files_in_dir(".", "typescript"),
-                    "TypeScript",
+            let scan_options = match filters.scan_options(Path::new(&path)) {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("{} Invalid glob: {}", symbols::FAIL, e);
+                    std::process::exit(1);
+                }
+            };
+
+            let language = if auto {
+                match scanner::detect_primary_language(&path, &scan_options) {
+                    Some(detected) => {
+                        println!(
+                            "{} Detected primary language: {}",
+                            symbols::LANGUAGE,
+                            detected
+                        );
+                        detected.to_string()
+                    }
+                    None => {
+                        println!(
+                            "{} No single primary language, saving all languages",
+                            symbols::INFO
+                        );
+                        "all".to_string()
+                    }
+                }
+            } else {
+                language
+            };
+
+            let (files, lang_type) = match scanner::language_config(&language) {
+                Some(config) => (
+                    scanner::scan_language_files_in_dir_with_options(
+                        &path,
+                        config.name,
+                        &scan_options,
+                    ),
+                    config.display_name.to_string(),
                 ),
-                "python" => (scanner::scan_language_files_in_dir(".", "python"), "Python"),
-                "java" => (scanner::scan_language_files_in_dir(".", "java"), "Java"),
-                "go" => (scanner::scan_language_files_in_dir(".", "go"), "Go"),
-                "rust" => (scanner::scan_rust_files_in_dir("."), "Rust"),
-                "json" => (scanner::scan_language_files_in_dir(".", "json"), "JSON"),
-                "html" => (scanner::scan_language_files_in_dir(".", "html"), "HTML"),
-                "css" => (scanner::scan_language_files_in_dir(".", "css"), "CSS"),
-                _ => {
-                    println!("❌ Unsupported language: {}", language);
+                None if language == "all" => {
+                    let results = scanner::scan_all_languages_in_dir(&path, &scan_options);
+                    let lang_type = results
+                        .iter()
+                        .map(|(display_name, _)| display_name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(scanner::LANGUAGE_SEPARATOR);
+                    let files = results.into_iter().flat_map(|(_, files)| files).collect();
+                    (files, lang_type)
+                }
+                None => {
+                    println!("{} Unsupported language: {}", symbols::FAIL, language);
                     let supported = scanner::get_supported_languages();
                     println!("Supported languages: {}", supported.join(", "));
                     return;
                 }
             };
 
+            scan_options.save_cache();
+
             if files.is_empty() {
-                println!("❌ No files found to save as pattern");
+                println!("{} No files found to save as pattern", symbols::FAIL);
                 return;
             }
 
-            let pattern = create_pattern_from_scan(files, name, lang_type.to_string());
-            display_pattern_summary(&pattern);
+            let files = if !interactive {
+                files
+            } else if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+                println!(
+                    "{} Not running in a terminal, saving all {} scanned files",
+                    symbols::WARN,
+                    files.len()
+                );
+                files
+            } else {
+                let mut files = match prompt_file_selection(&files) {
+                    Ok(selections) => select_files(files, &selections),
+                    Err(e) => {
+                        println!("{} File selection failed: {}", symbols::FAIL, e);
+                        return;
+                    }
+                };
+                if let Err(e) = prompt_optional_items(&mut files) {
+                    println!("{} Item selection failed: {}", symbols::FAIL, e);
+                    return;
+                }
+                files
+            };
+            if files.is_empty() {
+                println!("{} No files selected, nothing to save", symbols::FAIL);
+                return;
+            }
+
+            let failed = scanner::files_with_parse_errors(&files);
+            if !failed.is_empty() {
+                let icon = if strict_parse {
+                    symbols::FAIL
+                } else {
+                    symbols::WARN
+                };
+                println!("{} Files with parse errors ({}):", icon, failed.len());
+                for file in &failed {
+                    println!("  - {}", file.path);
+                }
+                if strict_parse {
+                    println!(
+                        "{} Fix the syntax errors or save without --strict-parse",
+                        symbols::TIP
+                    );
+                    return;
+                }
+            }
+
+            let mut pattern = create_pattern_from_scan(files, name, lang_type);
+            pattern.include = filters.include.clone();
+            pattern.exclude = filters.exclude.clone();
+            if let Some(description) = description {
+                pattern.description = description;
+            }
+            pattern.tags = tags;
+            report::display_pattern_summary(&pattern);
 
-            let scaff_dir = ScaffDirectory::new();
             match scaff_dir.save_pattern(&pattern) {
                 Ok(_) => {
-                    println!("✅ Successfully saved pattern '{}'", pattern.name);
                     println!(
-                        "💡 To generate code from this pattern, run: scaff generate {} --output <directory>",
+                        "{} Successfully saved pattern '{}'",
+                        symbols::OK,
+                        pattern.name
+                    );
+                    println!(
+                        "{} To generate code from this pattern, run: scaff generate {} --output <directory>",
+                        symbols::TIP,
                         pattern.name
                     );
                 }
-                Err(e) => println!("❌ Failed to save pattern: {}", e),
+                Err(e) => println!("{} Failed to save pattern: {}", symbols::FAIL, e),
             }
         }
-        Commands::List {} => match ScaffDirectory::list_patterns() {
-            Ok(_) => {}
-            Err(e) => println!("❌ Failed to list patterns: {}", e),
+        Commands::Update {
+            name,
+            path,
+            language,
+        } => {
+            if !update_scaff(&scaff_dir, &name, &path, language.as_deref()) {
+                std::process::exit(1);
+            }
+        }
+        Commands::List {
+            detailed,
+            tag,
+            language,
+            sort,
+        } => {
+            let filtered = tag.is_some() || language.is_some();
+            let options = ListOptions {
+                sort,
+                tag,
+                language,
+            };
+            match scaff_dir.list_patterns(&options) {
+                Ok(patterns) if patterns.is_empty() && filtered => {
+                    println!("No scaffs match the given filters.");
+                }
+                Ok(patterns) if patterns.is_empty() => {
+                    println!("No scaffs found. Use 'scaff save <name>' to save patterns.");
+                }
+                Ok(patterns) => report::display_pattern_list(&patterns, detailed),
+                Err(e) => println!("{} Failed to list patterns: {}", symbols::FAIL, e),
+            }
+        }
+        Commands::Show {
+            scaff,
+            json,
+            format,
+        } => {
+            let format = if json { ShowFormat::Json } else { format };
+            let pattern = scaff_dir
+                .resolve_scaff_name(scaff)
+                .and_then(|name| scaff_dir.find_pattern(&name));
+            match pattern {
+                Ok(pattern) => match format {
+                    ShowFormat::Text => report::display_pattern_summary(&pattern),
+                    ShowFormat::Json => match serde_json::to_string_pretty(&pattern) {
+                        Ok(content) => println!("{}", content),
+                        Err(e) => {
+                            println!("{} Failed to serialize scaff: {}", symbols::FAIL, e);
+                            std::process::exit(1);
+                        }
+                    },
+                    ShowFormat::Mermaid => print!("{}", pattern::to_mermaid(&pattern)),
+                },
+                Err(e) => {
+                    println!("{} {}", symbols::FAIL, e);
+                    println!("{} Run 'scaff list' to see available scaffs.", symbols::TIP);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Migrate { scaff, all: _ } => match scaff_dir.migrate_patterns(scaff.as_deref()) {
+            Ok(migrated) if migrated.is_empty() => {
+                println!(
+                    "{} All scaffs already use schema version {}",
+                    symbols::OK,
+                    SCHEMA_VERSION
+                );
+            }
+            Ok(migrated) => {
+                for (name, previous_version) in &migrated {
+                    println!(
+                        "{} Migrated '{}' from schema version {} to {}",
+                        symbols::UPGRADE,
+                        name,
+                        previous_version,
+                        SCHEMA_VERSION
+                    );
+                }
+                println!("{} Migrated {} scaff(s)", symbols::OK, migrated.len());
+            }
+            Err(e) => println!("{} Failed to migrate scaffs: {}", symbols::FAIL, e),
         },
-        Commands::Generate { scaff, output } => {
+        Commands::Diff { a, b, format } => {
+            let patterns = scaff_dir
+                .find_pattern(&a)
+                .and_then(|a| Ok((a, scaff_dir.find_pattern(&b)?)));
+            match patterns {
+                Ok((a, b)) => {
+                    let scaff_diff = diff::diff_patterns(&a, &b);
+                    match format {
+                        OutputFormat::Text => diff::display_diff(&scaff_diff),
+                        OutputFormat::Json => match serde_json::to_string_pretty(&scaff_diff) {
+                            Ok(content) => println!("{}", content),
+                            Err(e) => {
+                                println!("{} Failed to serialize diff: {}", symbols::FAIL, e);
+                                std::process::exit(1);
+                            }
+                        },
+                    }
+                }
+                Err(e) => {
+                    println!("{} {}", symbols::FAIL, e);
+                    println!("{} Run 'scaff list' to see available scaffs.", symbols::TIP);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Stats { format } => match scaff_dir.load_patterns() {
+            Ok(patterns) => {
+                let scaff_stats = stats::collect_stats(&patterns);
+                match format {
+                    OutputFormat::Text => stats::display_stats(&scaff_stats),
+                    OutputFormat::Json => match serde_json::to_string_pretty(&scaff_stats) {
+                        Ok(content) => println!("{}", content),
+                        Err(e) => println!("{} Failed to serialize stats: {}", symbols::FAIL, e),
+                    },
+                }
+            }
+            Err(e) => println!("{} Failed to load patterns: {}", symbols::FAIL, e),
+        },
+        Commands::Export { scaff, output } => {
+            let output = output.unwrap_or_else(|| format!("{}.json", scaff_file_stem(&scaff)));
+            match scaff_dir.export_pattern(&scaff, Path::new(&output)) {
+                Ok(_) => println!("{} Exported scaff '{}' to {}", symbols::OK, scaff, output),
+                Err(e) => {
+                    println!("{} Failed to export scaff: {}", symbols::FAIL, e);
+                    println!("{} Run 'scaff list' to see available scaffs.", symbols::TIP);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Import { file, force } => {
+            match scaff_dir.import_pattern(Path::new(&file), force) {
+                Ok((pattern, path)) => {
+                    println!(
+                        "{} Imported scaff '{}' to {}",
+                        symbols::OK,
+                        pattern.name,
+                        path.display()
+                    )
+                }
+                Err(e) => {
+                    println!("{} Failed to import scaff: {}", symbols::FAIL, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Generate {
+            scaff,
+            output,
+            template,
+            require_template,
+            output_ext,
+            dry_run,
+            print,
+            list_files,
+            force,
+            diff,
+            only,
+            target_language,
+            vars,
+            format_output,
+            no_format_output: _,
+        } => {
+            let only = match scanner::build_glob_set(&only) {
+                Ok(only) => only,
+                Err(e) => {
+                    eprintln!("{} Invalid glob: {}", symbols::FAIL, e);
+                    std::process::exit(1);
+                }
+            };
+
+            // --list-files prints bare paths for scripts, so its failures go to stderr
+            let fail = |message: String| -> ! {
+                if list_files {
+                    eprintln!("{} {}", symbols::FAIL, message);
+                } else {
+                    println!("{} {}", symbols::FAIL, message);
+                }
+                std::process::exit(1);
+            };
+
+            // Listing and generating share one configuration, so the listed paths are
+            // the ones generation writes
+            let generator = match CodeGenerator::new() {
+                Ok(generator) => generator
+                    .with_scaff_directory(scaff_dir.clone())
+                    .with_template(template)
+                    .with_require_template(require_template)
+                    .with_output_extensions(output_ext.into_iter().collect())
+                    .with_dry_run(dry_run, print)
+                    .with_force(force)
+                    .with_diff(diff)
+                    .with_target_language(target_language)
+                    .with_only(only)
+                    .with_vars(vars.into_iter().collect())
+                    .with_post_processors(if format_output {
+                        postprocess::default_formatters()
+                    } else {
+                        Vec::new()
+                    }),
+                Err(e) => fail(format!("Failed to initialize code generator: {}", e)),
+            };
+
+            if list_files {
+                match generator.planned_paths(&scaff, &output) {
+                    Ok(paths) => {
+                        for path in paths {
+                            println!("{}", path.display());
+                        }
+                    }
+                    Err(e) => fail(format!("Failed to list files: {}", e)),
+                }
+                return;
+            }
             println!(
-                "🏗️ Generating code from scaff: {} to directory: {}",
-                scaff, output
+                "{} Generating code from scaff: {} to directory: {}",
+                symbols::BUILD,
+                scaff,
+                output
             );
 
-            match CodeGenerator::new() {
-                Ok(generator) => match generator.generate_from_scaff(&scaff, &output) {
-                    Ok(_) => {
-                        println!(
-                            "💡 You can now explore the generated code in the '{}' directory",
-                            output
-                        );
+            match generator.generate_from_scaff(&scaff, &output) {
+                Ok(summary) if dry_run => {
+                    report::display_generation(&summary, &scaff, &output, dry_run, diff);
+                    println!(
+                        "{} Run again without --dry-run to write these files",
+                        symbols::TIP
+                    );
+                }
+                Ok(summary) => {
+                    report::display_generation(&summary, &scaff, &output, dry_run, diff);
+                    println!(
+                        "{} You can now explore the generated code in the '{}' directory",
+                        symbols::TIP,
+                        output
+                    );
+                    println!(
+                        "{} For Rust projects, run 'cd {} && cargo check' to verify the generated code",
+                        symbols::TIP,
+                        output
+                    );
+                }
+                Err(e) => {
+                    println!("{} Failed to generate code: {}", symbols::FAIL, e);
+                    if matches!(e, ScaffError::NotFound(_)) {
                         println!(
-                            "💡 For Rust projects, run 'cd {} && cargo check' to verify the generated code",
-                            output
+                            "{} Make sure the scaff '{}' exists. Run 'scaff list' to see available scaffs.",
+                            symbols::TIP,
+                            scaff
                         );
                     }
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Config {
+            action: ConfigAction::Path,
+        } => display_config_path(&scaff_dir),
+        Commands::Cache {
+            action: CacheAction::Clear,
+        } => match cache::cache_path(Path::new(".")).map(|path| (ScanCache::clear(&path), path)) {
+            Some((Ok(true), path)) => {
+                println!("{} Cleared scan cache {}", symbols::CLEAN, path.display())
+            }
+            Some((Ok(false), _)) | None => println!("No scan cache to clear."),
+            Some((Err(e), _)) => {
+                println!("{} Failed to clear scan cache: {}", symbols::FAIL, e)
+            }
+        },
+        Commands::Template {
+            action: TemplateAction::List,
+        } => display_templates(),
+        Commands::Template {
+            action: TemplateAction::Show { name },
+        } => match generator::template_source(Path::new(generator::TEMPLATES_DIR), &name) {
+            Ok(source) => print!("{}", source),
+            Err(e) => {
+                println!("{} {}", symbols::FAIL, e);
+                println!(
+                    "{} Run 'scaff template list' to see available templates.",
+                    symbols::TIP
+                );
+            }
+        },
+        Commands::Info {} => display_info(&scaff_dir),
+        Commands::Schema {} => match serde_json::to_string_pretty(&pattern::pattern_schema()) {
+            Ok(schema) => println!("{}", schema),
+            Err(e) => println!("{} Failed to serialize schema: {}", symbols::FAIL, e),
+        },
+        Commands::Doctor {} => {
+            let checks = doctor::run_checks(
+                &ScaffConfig::path(),
+                scaff_dir.path(),
+                Path::new(generator::TEMPLATES_DIR),
+            );
+            doctor::display_checks(&checks);
+            if checks.iter().any(|check| !check.ok) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Validate {
+            scaffs,
+            all,
+            path,
+            strict_language_match,
+            base_dir_relative,
+            treat_missing_file_as,
+            expect_count,
+            count_match,
+            strict,
+            ignore_types,
+            fuzzy,
+            fuzzy_threshold,
+            format,
+            fix,
+            no_cache,
+            changed_since,
+            #[cfg(feature = "watch")]
+            watch,
+        } => {
+            let scaffs = if all {
+                match scaff_dir.load_patterns() {
+                    Ok(patterns) if patterns.is_empty() => {
+                        println!("{} {}", symbols::FAIL, ScaffError::NoScaffs);
+                        std::process::exit(1);
+                    }
+                    Ok(patterns) => patterns.into_iter().map(|pattern| pattern.name).collect(),
                     Err(e) => {
-                        println!("❌ Failed to generate code: {}", e);
-                        if e.to_string().contains("No such file") {
+                        println!("{} Failed to load scaffs: {}", symbols::FAIL, e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                scaffs
+            };
+
+            let changed_files = match &changed_since {
+                Some(git_ref) => match changed_files(&path, git_ref) {
+                    Ok(files) => {
+                        if let Some(files) = &files
+                            && format == OutputFormat::Text
+                        {
                             println!(
-                                "💡 Make sure the scaff '{}' exists. Run 'scaff list' to see available scaffs.",
-                                scaff
+                                "{} Limiting validation to {} files changed since {}",
+                                symbols::NOTE,
+                                files.len(),
+                                git_ref
                             );
                         }
+                        files
+                    }
+                    Err(e) => {
+                        eprintln!("{} Failed to list changed files: {}", symbols::FAIL, e);
+                        std::process::exit(1);
                     }
                 },
-                Err(e) => {
-                    println!("❌ Failed to initialize code generator: {}", e);
+                None => None,
+            };
+
+            let count_expectations = !expect_count.is_empty();
+            let display = |result: &ValidationResult| {
+                report::display_validation_results(result, count_match, count_expectations)
+            };
+            let validator = ArchitectureValidator::new()
+                .with_scaff_directory(scaff_dir.clone())
+                .with_strict_language_match(strict_language_match)
+                .with_base_dir_relative(base_dir_relative)
+                .with_missing_file_severity(treat_missing_file_as)
+                .with_count_expectations(expect_count)
+                .with_count_match(count_match)
+                .with_strict(strict)
+                .with_fuzzy_threshold(
+                    fuzzy.then(|| fuzzy_threshold.unwrap_or(DEFAULT_FUZZY_THRESHOLD)),
+                )
+                .with_cache(!no_cache)
+                .with_ignored_types(ignore_types)
+                .with_changed_files(changed_files);
+
+            #[cfg(feature = "watch")]
+            if watch {
+                let [scaff] = scaffs.as_slice() else {
+                    println!("{} --watch takes a single scaff", symbols::FAIL);
+                    std::process::exit(1);
+                };
+                if let Err(e) =
+                    watch::watch_and_validate(&validator, scaff, Path::new(&path), &display)
+                {
+                    println!("{} Watch failed: {}", symbols::FAIL, e);
                 }
+                return;
             }
-        }
-        Commands::Validate { scaff } => {
-            println!("🔍 Validating codebase against scaff: {}", scaff);
 
-            let validator = ArchitectureValidator::new();
-            match validator.validate_against_scaff(&scaff) {
-                Ok(result) => {
-                    validator.display_validation_results(&result);
+            let mut results = Vec::new();
+            for scaff in scaffs {
+                if format == OutputFormat::Text {
+                    println!(
+                        "{} Validating codebase against scaff: {}",
+                        symbols::SEARCH,
+                        scaff
+                    );
                 }
-                Err(e) => {
-                    println!("❌ Validation failed: {}", e);
-                    if e.to_string().contains("not found") {
-                        println!("💡 Run 'scaff list' to see available scaffs.");
+
+                let mut validation = validator.validate_against_scaff(&scaff, Path::new(&path));
+                if fix
+                    && let Ok(result) = &validation
+                    && !result.missing_files.is_empty()
+                    && generate_missing_files(&scaff_dir, &scaff, &path, &result.missing_files)
+                {
+                    validation = validator.validate_against_scaff(&scaff, Path::new(&path));
+                }
+
+                match &validation {
+                    Ok(result) if format == OutputFormat::Text => display(result),
+                    Ok(_) => {}
+                    // Keep stdout parseable for --format json
+                    Err(e) if format == OutputFormat::Json => {
+                        eprintln!(
+                            "{} Validation against '{}' failed: {}",
+                            symbols::FAIL,
+                            scaff,
+                            e
+                        )
+                    }
+                    Err(e) => {
+                        println!("{} Validation failed: {}", symbols::FAIL, e);
+                        if matches!(e, ScaffError::NotFound(_)) {
+                            println!("{} Run 'scaff list' to see available scaffs.", symbols::TIP);
+                        }
+                    }
+                }
+                results.push((scaff, validation));
+            }
+
+            match format {
+                OutputFormat::Text if results.len() > 1 => {
+                    report::display_validation_summary(&results)
+                }
+                OutputFormat::Text => {}
+                OutputFormat::Json => {
+                    let reports: Vec<ValidationReport> = results
+                        .iter()
+                        .map(|(scaff, result)| match result {
+                            Ok(result) => ValidationReport::Result(result),
+                            Err(e) => ValidationReport::Error {
+                                scaff_name: scaff,
+                                error: e.to_string(),
+                            },
+                        })
+                        .collect();
+                    // A single scaff's result is printed on its own, several as an array
+                    let content = if results.len() == 1 {
+                        reports.first().map(serde_json::to_string_pretty)
+                    } else {
+                        Some(serde_json::to_string_pretty(&reports))
+                    };
+                    match content {
+                        None => {}
+                        Some(Ok(content)) => println!("{}", content),
+                        Some(Err(e)) => println!(
+                            "{} Failed to serialize validation result: {}",
+                            symbols::FAIL,
+                            e
+                        ),
                     }
                 }
             }
+
+            // Fails when any scaff did, or couldn't be validated at all
+            if results
+                .iter()
+                .any(|(_, result)| !result.as_ref().is_ok_and(|result| result.is_valid))
+            {
+                std::process::exit(1);
+            }
         }
     }
 }
+
+// A scaff's entry in `validate --format json`: its result, or why it couldn't be
+// validated
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ValidationReport<'a> {
+    Result(&'a ValidationResult),
+    Error { scaff_name: &'a str, error: String },
+}
+
+// Files under `path` changed since `git_ref`, or None to cover every file when `path`
+// isn't in a git repository
+fn changed_files(
+    path: &str,
+    git_ref: &str,
+) -> Result<Option<HashSet<PathBuf>>, Box<dyn std::error::Error>> {
+    if !git::is_repository(Path::new(path)) {
+        eprintln!(
+            "{} {} isn't in a git repository, ignoring --changed-since and checking every file",
+            symbols::WARN,
+            path
+        );
+        return Ok(None);
+    }
+    let files = git::changed_files_since(Path::new(path), git_ref)?;
+    Ok(Some(files.into_iter().collect()))
+}
+
+// Rescan `path` into the saved scaff `name`, keeping its metadata. Returns whether the
+// scaff was updated; the scaffs directory stays locked until then.
+fn update_scaff(
+    scaff_dir: &ScaffDirectory,
+    name: &str,
+    path: &str,
+    language: Option<&str>,
+) -> bool {
+    println!("{} Updating scaff: {}", symbols::SAVE, name);
+
+    // Held until the update is saved, so concurrent saves aren't lost
+    let (saved, lock) = match scaff_dir.find_pattern_locked(name) {
+        Ok(found) => found,
+        Err(e) => {
+            println!("{} {}", symbols::FAIL, e);
+            return false;
+        }
+    };
+
+    let language = match language.map(canonical_language) {
+        Some(language) if scanner::get_supported_languages().contains(&language) => {
+            scanner::get_language_display_name(language)
+        }
+        Some(language) => {
+            println!("{} Unsupported language: {}", symbols::FAIL, language);
+            let supported = scanner::get_supported_languages();
+            println!("Supported languages: {}", supported.join(", "));
+            return false;
+        }
+        None => saved.language.clone(),
+    };
+
+    // Re-apply the globs the scaff was saved with, as validation does
+    let options = match scanner::build_glob_set(&saved.include).and_then(|include| {
+        Ok(ScanOptions {
+            include,
+            exclude: scanner::build_glob_set(&saved.exclude)?,
+            ..ScanOptions::default()
+        })
+    }) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{} Invalid glob: {}", symbols::FAIL, e);
+            return false;
+        }
+    };
+
+    let files = match ArchitectureValidator::new().scan_current_codebase(
+        &language,
+        Path::new(path),
+        &options,
+    ) {
+        Ok(files) => files,
+        Err(e) => {
+            println!("{} {}", symbols::FAIL, e);
+            return false;
+        }
+    };
+    if files.is_empty() {
+        println!(
+            "{} No files found, keeping the saved scaff unchanged",
+            symbols::FAIL
+        );
+        return false;
+    }
+
+    let mut pattern = saved.clone();
+    pattern.language = language;
+    pattern.update_files(files);
+    diff::display_update(&diff::diff_patterns(&saved, &pattern));
+
+    match lock.save_pattern(&pattern) {
+        Ok(_) => {
+            println!("\n{} Updated scaff '{}'", symbols::OK, pattern.name);
+            true
+        }
+        Err(e) => {
+            println!("{} Failed to save pattern: {}", symbols::FAIL, e);
+            false
+        }
+    }
+}
+
+// Generate only the scaff files validation reported missing into the validated
+// directory, leaving existing files and the project manifest alone. Returns whether
+// anything was created.
+fn generate_missing_files(
+    scaff_dir: &ScaffDirectory,
+    scaff: &str,
+    path: &str,
+    missing_files: &[String],
+) -> bool {
+    let globs: Vec<String> = missing_files
+        .iter()
+        .map(|file| globset::escape(&normalize_path(file)))
+        .collect();
+    let only = match scanner::build_glob_set(&globs) {
+        Ok(only) => only,
+        Err(e) => {
+            println!("{} Failed to select missing files: {}", symbols::FAIL, e);
+            return false;
+        }
+    };
+
+    let generated = CodeGenerator::new().and_then(|generator| {
+        generator
+            .with_scaff_directory(scaff_dir.clone())
+            .with_only(only)
+            .with_manifest(false)
+            .with_module_declarations(false)
+            .generate_from_scaff(scaff, path)
+    });
+    match generated {
+        Ok(summary) if summary.new_files.is_empty() => {
+            println!(
+                "{} None of the missing files could be generated",
+                symbols::WARN
+            );
+            false
+        }
+        Ok(summary) => {
+            println!(
+                "\n{} Created {} missing file(s):",
+                symbols::FIX,
+                summary.new_files.len()
+            );
+            for file in &summary.new_files {
+                println!("  {} {}", symbols::OK, file.display());
+            }
+            println!("\n{} Validating again...", symbols::RETRY);
+            true
+        }
+        Err(e) => {
+            println!("{} Failed to generate missing files: {}", symbols::FAIL, e);
+            false
+        }
+    }
+}
+
+// Checklist of the scanned files, all selected to start with
+fn prompt_file_selection(files: &[FilePattern]) -> Result<Vec<usize>, dialoguer::Error> {
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    MultiSelect::new()
+        .with_prompt("Files to include (space to toggle, enter to confirm)")
+        .items(&paths)
+        .defaults(&vec![true; paths.len()])
+        .interact()
+}
+
+// Checklist of the selected files' items, none ticked to start with; ticked items are
+// recorded as optional on their file
+fn prompt_optional_items(files: &mut [FilePattern]) -> Result<(), dialoguer::Error> {
+    let items: Vec<(usize, &str, String)> = files
+        .iter()
+        .enumerate()
+        .flat_map(|(index, file)| {
+            comparable_items(file)
+                .into_iter()
+                .flat_map(move |(item_type, names)| {
+                    names.into_iter().map(move |name| (index, item_type, name))
+                })
+        })
+        .collect();
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let labels: Vec<String> = items
+        .iter()
+        .map(|(index, item_type, name)| format!("{}: {} {}", files[*index].path, item_type, name))
+        .collect();
+    let selections = MultiSelect::new()
+        .with_prompt("Items to mark optional (space to toggle, enter to confirm)")
+        .items(&labels)
+        .interact()?;
+
+    for selection in selections {
+        let (index, item_type, name) = &items[selection];
+        let file = &mut files[*index];
+        if !file.is_optional(item_type, name) {
+            file.optional.push(OptionalItem {
+                item_type: item_type.to_string(),
+                name: name.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn parse_similarity(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(similarity) if (0.0..=1.0).contains(&similarity) => Ok(similarity),
+        _ => Err(format!(
+            "expected a number from 0.0 to 1.0, got '{}'",
+            value
+        )),
+    }
+}
+
+fn parse_extension_mapping(value: &str) -> Result<(String, String), String> {
+    let (from, to) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected FROM=TO, got '{}'", value))?;
+    let (from, to) = (
+        from.trim().trim_start_matches('.'),
+        to.trim().trim_start_matches('.'),
+    );
+    if from.is_empty() || to.is_empty() {
+        return Err(format!("expected FROM=TO, got '{}'", value));
+    }
+    Ok((from.to_string(), to.to_string()))
+}
+
+// `scan --file`/`--stdin`: parse one source and print its pattern, exiting non-zero
+// when it can't be scanned so editor integrations notice. A --file must pass the
+// filters a scan of `dir` applies, so its ignore rules and globs are `dir`'s.
+fn scan_single_source(
+    file: Option<String>,
+    dir: &str,
+    options: &ScanOptions,
+    language: &str,
+    format: OutputFormat,
+    max_item_name_length: usize,
+) {
+    let fail = |message: String| -> ! {
+        eprintln!("{} {}", symbols::FAIL, message);
+        std::process::exit(1);
+    };
+
+    let language = match (language, &file) {
+        ("all", Some(file)) => scanner::language_for_path(Path::new(file)).unwrap_or_else(|| {
+            fail(format!(
+                "No supported language for {}, pass --language",
+                file
+            ))
+        }),
+        ("all", None) => fail("--stdin needs --language".to_string()),
+        (language, _) => canonical_language(language),
+    };
+    if !scanner::get_supported_languages().contains(&language) {
+        fail(format!("Unsupported language: {}", language));
+    }
+
+    let pattern = match &file {
+        Some(file) => {
+            let path = Path::new(file);
+            if path.is_file() && !scanner::passes_filters(path, Path::new(dir), options) {
+                fail(format!(
+                    "{} is ignored or excluded by --include, --exclude or --max-file-size",
+                    file
+                ));
+            }
+            let pattern = scanner::scan_file(path, language, options)
+                .unwrap_or_else(|| fail(format!("Failed to read {}", file)));
+            options.save_cache();
+            pattern
+        }
+        None => {
+            let mut source = String::new();
+            if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+                fail(format!("Failed to read stdin: {}", e));
+            }
+            scanner::scan_source(&source, Path::new("<stdin>"), language, options)
+                .unwrap_or_else(|| fail(format!("Unsupported language: {}", language)))
+        }
+    };
+
+    if format == OutputFormat::Json {
+        match serde_json::to_string_pretty(&pattern) {
+            Ok(json) => println!("{}", json),
+            Err(e) => fail(format!("Failed to serialize scan results: {}", e)),
+        }
+    } else {
+        report::display_scan_results(
+            &[pattern],
+            &scanner::get_language_display_name(language),
+            max_item_name_length,
+        );
+    }
+}
+
+// The scanner's name for a --language value, resolving short aliases like `js`
+fn canonical_language(language: &str) -> &str {
+    scanner::language_config(language).map_or(language, |config| config.name)
+}
+
+// KEY=VALUE for --var; the value may be empty or contain further '='
+fn parse_variable(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", arg)),
+    }
+}
+
+fn display_config_path(scaff_dir: &ScaffDirectory) {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    match config::find_project_root(&current_dir) {
+        Some(root) => println!("{} Project root: {}", symbols::FOLDER, root.display()),
+        None => println!(
+            "{} Project root: none found, using {}",
+            symbols::FOLDER,
+            current_dir.display()
+        ),
+    }
+
+    let config_path = current_dir.join(ScaffConfig::path());
+    let status = if config_path.exists() {
+        ""
+    } else {
+        " (not found)"
+    };
+    println!(
+        "{} Config: {}{}",
+        symbols::CONFIG,
+        config_path.display(),
+        status
+    );
+    println!(
+        "{} Scaffs directory: {}",
+        symbols::PACKAGE,
+        current_dir.join(scaff_dir.path()).display()
+    );
+}
+
+fn display_templates() {
+    let templates_dir = Path::new(generator::TEMPLATES_DIR);
+    println!(
+        "{} Templates ({}/ and built-in):",
+        symbols::TEMPLATES,
+        templates_dir.display()
+    );
+    for template in generator::list_templates(templates_dir) {
+        match template.source {
+            TemplateSource::File(path) => println!("  {} ({})", template.name, path.display()),
+            TemplateSource::Builtin if template.overridden => {
+                println!("  {} (built-in, overridden)", template.name)
+            }
+            TemplateSource::Builtin => println!("  {} (built-in)", template.name),
+        }
+    }
+}
+
+fn display_info(scaff_dir: &ScaffDirectory) {
+    println!("{} scaff {}", symbols::INFO, env!("CARGO_PKG_VERSION"));
+    println!("{:-<50}", "");
+
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    println!(
+        "{} Scaffs directory: {}",
+        symbols::FOLDER,
+        current_dir.join(scaff_dir.path()).display()
+    );
+    println!(
+        "{} Templates directory: {}",
+        symbols::TEMPLATES,
+        current_dir.join(generator::TEMPLATES_DIR).display()
+    );
+    let config_path = current_dir.join(ScaffConfig::path());
+    if config_path.exists() {
+        println!("{} Config: {}", symbols::CONFIG, config_path.display());
+    } else {
+        println!(
+            "{} Config: none (directories are resolved from the current directory)",
+            symbols::CONFIG
+        );
+    }
+    println!(
+        "{} RUST_LOG: {}",
+        symbols::LOG,
+        std::env::var("RUST_LOG").unwrap_or_else(|_| "(unset)".to_string())
+    );
+
+    println!(
+        "\n{} Tree-sitter ABI {} (minimum compatible {})",
+        symbols::TREE,
+        tree_sitter::LANGUAGE_VERSION,
+        tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION
+    );
+    println!(
+        "Supported languages ({}):",
+        scanner::SUPPORTED_LANGUAGES.len()
+    );
+    for config in scanner::SUPPORTED_LANGUAGES {
+        let grammar = match scanner::grammar_for(config.name) {
+            Some(grammar) => {
+                let version = grammar
+                    .metadata()
+                    .map(|m| {
+                        format!(
+                            "{}.{}.{}",
+                            m.major_version, m.minor_version, m.patch_version
+                        )
+                    })
+                    .unwrap_or_else(|| "unknown version".to_string());
+                format!("grammar {} (ABI {})", version, grammar.abi_version())
+            }
+            None => "grammar unavailable".to_string(),
+        };
+        println!(
+            "   {} [{}] .{} - {}",
+            config.display_name,
+            config.name,
+            config.extensions.join(", ."),
+            grammar
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Location of the project config, relative to the project root
+pub const CONFIG_PATH: &str = ".scaff/config.json";
+
+/// Directories whose presence marks a project root, like `.git` does for git
+const ROOT_MARKERS: &[&str] = &[".scaff", "scaffs"];
+
+/// Version control roots, which the search for a project root doesn't leave. `.git`
+/// may be a file in worktrees and submodules.
+const BOUNDARY_MARKERS: &[&str] = &[".git", ".hg", ".svn"];
+
+/// Nearest directory from `start` upward containing a `.scaff/` or `scaffs/`
+/// directory. The search stops at the enclosing repository root, so a `scaffs/` in
+/// some unrelated parent directory isn't picked up.
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        if ROOT_MARKERS.iter().any(|marker| dir.join(marker).is_dir()) {
+            return Some(dir.to_path_buf());
+        }
+        if BOUNDARY_MARKERS
+            .iter()
+            .any(|marker| dir.join(marker).exists())
+        {
+            return None;
+        }
+    }
+    None
+}
+
+/// The project root found upward from the current directory, as a path to join config
+/// and scaff locations onto: empty (the current directory itself) when the current
+/// directory is the root or no root is found
+pub fn project_root() -> PathBuf {
+    let Ok(current_dir) = std::env::current_dir() else {
+        return PathBuf::new();
+    };
+    match find_project_root(&current_dir) {
+        Some(root) if root != current_dir => {
+            info!("Using project root {}", root.display());
+            root
+        }
+        _ => PathBuf::new(),
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScaffConfig {
+    /// Directory scaffs are saved to and loaded from, relative to the project root
+    #[serde(default)]
+    pub scaff_dir: Option<PathBuf>,
+    /// Scaff used by commands whose scaff argument is optional when it's omitted
+    #[serde(default)]
+    pub default_scaff: Option<String>,
+}
+
+impl ScaffConfig {
+    /// Load the config, falling back to defaults when the file is absent or invalid
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    /// Where the config is read from, whether or not it exists
+    pub fn path() -> PathBuf {
+        project_root().join(CONFIG_PATH)
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        if !path.exists() {
+            return ScaffConfig::default();
+        }
+
+        match fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        {
+            Ok(config) => {
+                info!("Loaded config from {}", path.display());
+                config
+            }
+            Err(e) => {
+                warn!("Ignoring invalid config {}: {}", path.display(), e);
+                ScaffConfig::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_project_root_searches_upward() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let nested = temp_dir.path().join("app/src");
+        fs::create_dir_all(&nested)?;
+
+        fs::create_dir_all(temp_dir.path().join("scaffs"))?;
+        assert_eq!(
+            find_project_root(&nested),
+            Some(temp_dir.path().to_path_buf())
+        );
+
+        // The nearest marker wins, and .scaff/ counts as one
+        fs::create_dir_all(temp_dir.path().join("app/.scaff"))?;
+        assert_eq!(
+            find_project_root(&nested),
+            Some(temp_dir.path().join("app"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_project_root_stops_at_repository_root() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let nested = temp_dir.path().join("repo/src");
+        fs::create_dir_all(&nested)?;
+        fs::create_dir_all(temp_dir.path().join("scaffs"))?;
+        fs::create_dir_all(temp_dir.path().join("repo/.git"))?;
+        assert_eq!(find_project_root(&nested), None);
+
+        // A marker at the repository root itself still counts
+        fs::create_dir_all(temp_dir.path().join("repo/scaffs"))?;
+        assert_eq!(
+            find_project_root(&nested),
+            Some(temp_dir.path().join("repo"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing_config_uses_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ScaffConfig::load_from(&temp_dir.path().join("config.json"));
+        assert!(config.scaff_dir.is_none());
+    }
+
+    #[test]
+    fn test_load_config_scaff_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("config.json");
+
+        fs::write(&path, r#"{ "scaff_dir": "architecture/scaffs" }"#)?;
+        let config = ScaffConfig::load_from(&path);
+        assert_eq!(config.scaff_dir, Some(PathBuf::from("architecture/scaffs")));
+
+        fs::write(&path, "{ not json")?;
+        assert!(ScaffConfig::load_from(&path).scaff_dir.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use crate::pattern::{CodePattern, FilePattern, comparable_items};
+use crate::symbols;
+use crate::validator::{diff_items, normalize_path};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Structural differences between two scaffs, `a` being the base and `b` the other
+#[derive(Debug, Clone, Serialize)]
+pub struct ScaffDiff {
+    pub a: String,
+    pub b: String,
+    /// Files only in `a`
+    pub removed_files: Vec<String>,
+    /// Files only in `b`
+    pub added_files: Vec<String>,
+    /// Files in both whose items differ
+    pub changed_files: Vec<FileDiff>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiff {
+    pub path: String,
+    /// Items only in `a`'s version of the file
+    pub removed: Vec<DiffItem>,
+    /// Items only in `b`'s version of the file
+    pub added: Vec<DiffItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffItem {
+    pub item_type: String,
+    pub name: String,
+}
+
+impl ScaffDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed_files.is_empty()
+            && self.added_files.is_empty()
+            && self.changed_files.is_empty()
+    }
+}
+
+pub fn diff_patterns(a: &CodePattern, b: &CodePattern) -> ScaffDiff {
+    let a_files: HashMap<String, &FilePattern> = a
+        .files
+        .iter()
+        .map(|f| (normalize_path(&f.path), f))
+        .collect();
+    let b_files: HashMap<String, &FilePattern> = b
+        .files
+        .iter()
+        .map(|f| (normalize_path(&f.path), f))
+        .collect();
+
+    let mut diff = ScaffDiff {
+        a: a.name.clone(),
+        b: b.name.clone(),
+        removed_files: Vec::new(),
+        added_files: Vec::new(),
+        changed_files: Vec::new(),
+    };
+
+    for a_file in &a.files {
+        let path = normalize_path(&a_file.path);
+        match b_files.get(&path) {
+            Some(b_file) => {
+                let file_diff = diff_file(path, a_file, b_file);
+                if !file_diff.removed.is_empty() || !file_diff.added.is_empty() {
+                    diff.changed_files.push(file_diff);
+                }
+            }
+            None => diff.removed_files.push(path),
+        }
+    }
+
+    for b_file in &b.files {
+        let path = normalize_path(&b_file.path);
+        if !a_files.contains_key(&path) {
+            diff.added_files.push(path);
+        }
+    }
+
+    diff
+}
+
+fn diff_file(path: String, a_file: &FilePattern, b_file: &FilePattern) -> FileDiff {
+    let mut file_diff = FileDiff {
+        path,
+        removed: Vec::new(),
+        added: Vec::new(),
+    };
+
+    let b_items = comparable_items(b_file);
+    for ((item_type, a_items), (_, b_items)) in comparable_items(a_file).iter().zip(&b_items) {
+        let (removed, added) = diff_items(a_items, b_items);
+        let to_item = |name: &String| DiffItem {
+            item_type: item_type.to_string(),
+            name: name.clone(),
+        };
+        file_diff.removed.extend(removed.into_iter().map(to_item));
+        file_diff.added.extend(added.into_iter().map(to_item));
+    }
+
+    file_diff
+}
+
+pub fn display_diff(diff: &ScaffDiff) {
+    println!("\n{} Scaff Diff", symbols::DIFF);
+    println!("--- {}", diff.a);
+    println!("+++ {}", diff.b);
+    println!("{:-<60}", "");
+
+    if diff.is_empty() {
+        println!("{} Scaffs have the same structure", symbols::OK);
+        return;
+    }
+
+    for path in &diff.removed_files {
+        println!("- {} (only in '{}')", path, diff.a);
+    }
+    for path in &diff.added_files {
+        println!("+ {} (only in '{}')", path, diff.b);
+    }
+
+    for file_diff in &diff.changed_files {
+        println!("\n{} {}", symbols::FILE, file_diff.path);
+        for item in &file_diff.removed {
+            println!("  - {}: {}", item.item_type, item.name);
+        }
+        for item in &file_diff.added {
+            println!("  + {}: {}", item.item_type, item.name);
+        }
+    }
+}
+
+/// Summarize how `scaff update` changed a scaff, `diff` running from the saved
+/// version to the re-scanned one
+pub fn display_update(diff: &ScaffDiff) {
+    println!("\n{} Changes since the last save", symbols::DIFF);
+    println!("{:-<50}", "");
+
+    if diff.is_empty() {
+        println!("{} No structural changes", symbols::OK);
+        return;
+    }
+
+    for path in &diff.added_files {
+        println!("{} {}", symbols::ADDED, path);
+    }
+    for path in &diff.removed_files {
+        println!("{} {}", symbols::REMOVED, path);
+    }
+    for file_diff in &diff.changed_files {
+        println!(
+            "{} {} ({} item(s) added, {} removed)",
+            symbols::CHANGED,
+            file_diff.path,
+            file_diff.added.len(),
+            file_diff.removed.len()
+        );
+    }
+    println!(
+        "\n{} file(s) added, {} removed, {} changed",
+        diff.added_files.len(),
+        diff.removed_files.len(),
+        diff.changed_files.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::SCHEMA_VERSION;
+
+    fn create_pattern(name: &str, files: Vec<FilePattern>) -> CodePattern {
+        CodePattern {
+            schema_version: SCHEMA_VERSION,
+            name: name.to_string(),
+            description: String::new(),
+            language: "Rust".to_string(),
+            files,
+            directories: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            tags: Vec::new(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn create_file(path: &str, functions: &[&str]) -> FilePattern {
+        FilePattern {
+            path: path.to_string(),
+            extension: "rs".to_string(),
+            functions: functions.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_one_function() {
+        let a = create_pattern("a", vec![create_file("src/lib.rs", &["run", "stop"])]);
+        let b = create_pattern("b", vec![create_file("./src/lib.rs", &["run", "pause"])]);
+
+        let diff = diff_patterns(&a, &b);
+        assert!(diff.removed_files.is_empty() && diff.added_files.is_empty());
+        assert_eq!(diff.changed_files.len(), 1);
+
+        let file_diff = &diff.changed_files[0];
+        assert_eq!(file_diff.path, "src/lib.rs");
+        assert_eq!(
+            file_diff.removed,
+            vec![DiffItem {
+                item_type: "function".to_string(),
+                name: "stop".to_string()
+            }]
+        );
+        assert_eq!(file_diff.added[0].name, "pause");
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let a = create_pattern(
+            "a",
+            vec![
+                create_file("src/lib.rs", &["run"]),
+                create_file("src/old.rs", &[]),
+            ],
+        );
+        let b = create_pattern(
+            "b",
+            vec![
+                create_file("src/lib.rs", &["run"]),
+                create_file("src/new.rs", &[]),
+            ],
+        );
+
+        let diff = diff_patterns(&a, &b);
+        assert_eq!(diff.removed_files, vec!["src/old.rs"]);
+        assert_eq!(diff.added_files, vec!["src/new.rs"]);
+        assert!(diff.changed_files.is_empty());
+
+        assert!(diff_patterns(&a, &a).is_empty());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::config::ScaffConfig;
+use crate::generator::{self, CodeGenerator};
+use crate::pattern::ScaffDirectory;
+use crate::symbols;
+use std::fs;
+use std::path::Path;
+
+/// One line of the `scaff doctor` checklist
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub ok: bool,
+    pub message: String,
+    /// How to fix a failed check
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(message: String) -> Self {
+        Check {
+            ok: true,
+            message,
+            hint: None,
+        }
+    }
+
+    fn fail(message: String, hint: String) -> Self {
+        Check {
+            ok: false,
+            message,
+            hint: Some(hint),
+        }
+    }
+}
+
+/// Check the config, scaffs directory, every scaff file, the default scaff and the
+/// templates directory, in that order
+pub fn run_checks(config_path: &Path, scaffs_dir: &Path, templates_dir: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let config = check_config(config_path, &mut checks);
+    check_scaffs(scaffs_dir, &mut checks);
+
+    if let Some(name) = config.and_then(|config| config.default_scaff) {
+        if ScaffDirectory::at(scaffs_dir).find_pattern(&name).is_ok() {
+            checks.push(Check::pass(format!("Default scaff '{}' exists", name)));
+        } else {
+            checks.push(Check::fail(
+                format!("Default scaff '{}' doesn't exist", name),
+                format!(
+                    "Save it with 'scaff save {}' or change default_scaff in {}",
+                    name,
+                    config_path.display()
+                ),
+            ));
+        }
+    }
+
+    checks.push(check_templates(templates_dir));
+    checks
+}
+
+// The parsed config, or None when it's absent or invalid
+fn check_config(config_path: &Path, checks: &mut Vec<Check>) -> Option<ScaffConfig> {
+    if !config_path.exists() {
+        checks.push(Check::pass(format!(
+            "No config at {}, using defaults",
+            config_path.display()
+        )));
+        return None;
+    }
+
+    match fs::read_to_string(config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(config) => {
+            checks.push(Check::pass(format!(
+                "Config {} is valid",
+                config_path.display()
+            )));
+            Some(config)
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                format!("Config {} is invalid: {}", config_path.display(), e),
+                "Fix the JSON, or delete the file to use the defaults".to_string(),
+            ));
+            None
+        }
+    }
+}
+
+fn check_scaffs(scaffs_dir: &Path, checks: &mut Vec<Check>) {
+    if !scaffs_dir.is_dir() {
+        checks.push(Check::fail(
+            format!("Scaffs directory {} doesn't exist", scaffs_dir.display()),
+            "Run 'scaff save <name>' to create it, or point SCAFF_DIR or scaff_dir at your scaffs"
+                .to_string(),
+        ));
+        return;
+    }
+
+    match ScaffDirectory::at(scaffs_dir).check_pattern_files() {
+        Ok((patterns, failures)) => {
+            checks.push(Check::pass(format!(
+                "Scaffs directory {} is readable ({} scaff(s))",
+                scaffs_dir.display(),
+                patterns.len()
+            )));
+            for (path, reason) in failures {
+                checks.push(Check::fail(
+                    format!("{}: {}", path.display(), reason),
+                    "Fix the file or remove it; until then it's skipped".to_string(),
+                ));
+            }
+        }
+        Err(e) => checks.push(Check::fail(
+            format!(
+                "Scaffs directory {} isn't readable: {}",
+                scaffs_dir.display(),
+                e
+            ),
+            "Check the directory's permissions".to_string(),
+        )),
+    }
+}
+
+fn check_templates(templates_dir: &Path) -> Check {
+    if !templates_dir.is_dir() {
+        return Check::pass(format!(
+            "No templates directory at {}, using the built-in templates",
+            templates_dir.display()
+        ));
+    }
+
+    match CodeGenerator::from_templates_dir(templates_dir) {
+        Ok(_) => {
+            let count = generator::list_templates(templates_dir)
+                .iter()
+                .filter(|template| matches!(template.source, generator::TemplateSource::File(_)))
+                .count();
+            Check::pass(format!(
+                "Templates directory {} loads ({} template(s))",
+                templates_dir.display(),
+                count
+            ))
+        }
+        Err(e) => Check::fail(
+            format!(
+                "Templates directory {} failed to load: {}",
+                templates_dir.display(),
+                e
+            ),
+            "Fix the template or helper named in the error".to_string(),
+        ),
+    }
+}
+
+pub fn display_checks(checks: &[Check]) {
+    println!("\n{} Scaff Doctor", symbols::DOCTOR);
+    println!("{:-<50}", "");
+
+    for check in checks {
+        let mark = if check.ok {
+            symbols::PASSED
+        } else {
+            symbols::FAILED
+        };
+        println!("{} {}", mark, check.message);
+        if let Some(hint) = &check.hint {
+            println!("  {} {}", symbols::TIP, hint);
+        }
+    }
+
+    let problems = checks.iter().filter(|check| !check.ok).count();
+    if problems == 0 {
+        println!("\n{} No problems found", symbols::OK);
+    } else {
+        println!("\n{} {} problem(s) found", symbols::FAIL, problems);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PATTERN: &str = r#"{
+  "name": "api",
+  "description": "",
+  "language": "Rust",
+  "files": [],
+  "created_at": "2024-01-01T00:00:00Z"
+}"#;
+
+    fn failed(checks: &[Check]) -> Vec<&str> {
+        checks
+            .iter()
+            .filter(|check| !check.ok)
+            .map(|check| check.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_healthy_setup() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let scaffs_dir = temp_dir.path().join("scaffs");
+        fs::create_dir_all(&scaffs_dir)?;
+        fs::write(scaffs_dir.join("api.json"), PATTERN)?;
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, r#"{ "default_scaff": "api" }"#)?;
+
+        let checks = run_checks(
+            &config_path,
+            &scaffs_dir,
+            &temp_dir.path().join("templates"),
+        );
+        assert!(failed(&checks).is_empty(), "{:?}", checks);
+        assert!(
+            checks
+                .iter()
+                .any(|c| c.message == "Default scaff 'api' exists")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dangling_default_scaff() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let scaffs_dir = temp_dir.path().join("scaffs");
+        fs::create_dir_all(&scaffs_dir)?;
+        fs::write(scaffs_dir.join("api.json"), PATTERN)?;
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, r#"{ "default_scaff": "deleted" }"#)?;
+
+        let checks = run_checks(
+            &config_path,
+            &scaffs_dir,
+            &temp_dir.path().join("templates"),
+        );
+        assert_eq!(
+            failed(&checks),
+            vec!["Default scaff 'deleted' doesn't exist"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_config() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let scaffs_dir = temp_dir.path().join("scaffs");
+        fs::create_dir_all(&scaffs_dir)?;
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, r#"{ "scaff_dir": 42 }"#)?;
+
+        let checks = run_checks(
+            &config_path,
+            &scaffs_dir,
+            &temp_dir.path().join("templates"),
+        );
+        let failed = failed(&checks);
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains("is invalid"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_pattern_file() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let scaffs_dir = temp_dir.path().join("scaffs");
+        fs::create_dir_all(&scaffs_dir)?;
+        fs::write(scaffs_dir.join("api.json"), PATTERN)?;
+        fs::write(scaffs_dir.join("broken.json"), "{ not json")?;
+
+        let checks = run_checks(
+            &temp_dir.path().join("config.json"),
+            &scaffs_dir,
+            &temp_dir.path().join("templates"),
+        );
+        let failed = failed(&checks);
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains("broken.json"));
+        assert!(failed[0].contains("failed to parse pattern"));
+        assert!(
+            checks
+                .iter()
+                .any(|c| c.ok && c.message.ends_with("is readable (1 scaff(s))"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_scaffs_dir_and_broken_template() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let templates_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&templates_dir)?;
+        fs::write(templates_dir.join("rust_file.hbs"), "{{#each structs}}")?;
+
+        let checks = run_checks(
+            &temp_dir.path().join("config.json"),
+            &temp_dir.path().join("scaffs"),
+            &templates_dir,
+        );
+        let failed = failed(&checks);
+        assert_eq!(failed.len(), 2);
+        assert!(failed[0].contains("doesn't exist"));
+        assert!(failed[1].contains("failed to load"));
+
+        Ok(())
+    }
+}
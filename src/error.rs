@@ -0,0 +1,62 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Why loading, generating from or validating against a scaff failed
+#[derive(Debug, Error)]
+pub enum ScaffError {
+    /// No saved scaff has this internal or file name
+    #[error("Scaff '{0}' not found. Use 'scaff list' to see available scaffs.")]
+    NotFound(String),
+    #[error("No scaffs found. Use 'scaff save <name>' to save patterns.")]
+    NoScaffs,
+    /// No scaff name was given and more than one scaff is saved
+    #[error("No scaff name given and {0} scaffs exist. Use 'scaff list' to pick one.")]
+    AmbiguousScaff(usize),
+    #[error("Scaff '{0}' already exists. Use --force to replace it.")]
+    AlreadyExists(String),
+    #[error(
+        "'{}' uses schema version {version}, but this scaff only supports up to {supported}. Upgrade scaff to import it.",
+        .path.display()
+    )]
+    UnsupportedSchema {
+        path: PathBuf,
+        version: u32,
+        supported: u32,
+    },
+    /// An exported scaff file that doesn't hold a scaff
+    #[error("'{}' is not a valid scaff: {source}", .path.display())]
+    InvalidScaff {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// Another `scaff` process kept the scaffs directory locked for too long
+    #[error(
+        "Timed out waiting for another scaff process to release '{}'. Delete it if none is running.",
+        .0.display()
+    )]
+    Locked(PathBuf),
+    #[error("Unsupported language: {0}")]
+    UnsupportedLanguage(String),
+    #[error("Directory '{}' does not exist", .0.display())]
+    MissingDirectory(PathBuf),
+    #[error("Template '{0}' not found")]
+    TemplateNotFound(String),
+    #[error("Template '{0}' not found in templates directory (required by --require-template)")]
+    RequiredTemplateNotFound(String),
+    #[error(
+        "Front-matter path '{path}' of template '{template}' must be relative and stay inside the output directory"
+    )]
+    InvalidOutputPath { template: String, path: String },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Parse(#[from] serde_json::Error),
+    #[error(transparent)]
+    Glob(#[from] globset::Error),
+    /// A template that doesn't compile
+    #[error(transparent)]
+    Template(#[from] handlebars::TemplateError),
+    #[error(transparent)]
+    TemplateRender(#[from] handlebars::RenderError),
+}
@@ -1,16 +1,214 @@
-use crate::pattern::{CodePattern, FilePattern};
+use crate::error::ScaffError;
+use crate::pattern::{CodePattern, FilePattern, ScaffDirectory, unqualified};
+use crate::postprocess::{self, PostProcessor};
+use crate::scanner;
+use crate::validator::normalize_path;
+use clap::ValueEnum;
+use globset::GlobSet;
 use handlebars::Handlebars;
 use log::{debug, error, info, warn};
+use serde::Deserialize;
 use serde_json::json;
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 pub struct CodeGenerator<'a> {
     handlebars: Handlebars<'a>,
+    scaffs: ScaffDirectory,
+    template_override: Option<String>,
+    require_template: bool,
+    output_extensions: HashMap<String, String>,
+    dry_run: bool,
+    print_content: bool,
+    force: bool,
+    show_diff: bool,
+    target_language: Option<TargetLanguage>,
+    only: Option<GlobSet>,
+    manifest: bool,
+    declare_modules: bool,
+    /// Run on each written file, e.g. formatters
+    post_processors: Vec<Box<dyn PostProcessor>>,
+    /// `--var` values, taking precedence over the scaff's own `variables`
+    vars: HashMap<String, String>,
+    /// Front-matter of templates loaded from the templates directory, by template name
+    front_matter: HashMap<String, TemplateFrontMatter>,
+}
+
+/// Language to generate into, independent of the language the scaff was scanned from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TargetLanguage {
+    Rust,
+    #[value(alias = "js")]
+    Javascript,
+    #[value(alias = "ts")]
+    Typescript,
+    #[value(alias = "py")]
+    Python,
+}
+
+impl TargetLanguage {
+    /// The scanner's identifier for this language, e.g. `javascript`
+    fn language(self) -> &'static str {
+        match self {
+            TargetLanguage::Rust => "rust",
+            TargetLanguage::Javascript => "javascript",
+            TargetLanguage::Typescript => "typescript",
+            TargetLanguage::Python => "python",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            TargetLanguage::Rust => "rs",
+            TargetLanguage::Javascript => "js",
+            TargetLanguage::Typescript => "ts",
+            TargetLanguage::Python => "py",
+        }
+    }
+}
+
+/// Directory templates are loaded from, relative to the current directory
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Templates compiled into scaff, by name. Source file templates are the fallbacks when
+/// the templates directory has no `rust_file`/`js_file`/`python_file`; a template of the
+/// same name in the directory replaces the built-in one.
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("default_rust_file", DEFAULT_RUST_TEMPLATE),
+    ("default_js_file", DEFAULT_JS_TEMPLATE),
+    ("default_python_file", DEFAULT_PYTHON_TEMPLATE),
+    ("default_cargo_toml", DEFAULT_CARGO_TEMPLATE),
+    ("default_package_json", DEFAULT_PACKAGE_TEMPLATE),
+    ("default_pyproject_toml", DEFAULT_PYPROJECT_TEMPLATE),
+];
+
+/// Where a template listed by `list_templates` comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateSource {
+    /// A `.hbs` file in the templates directory
+    File(PathBuf),
+    Builtin,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub source: TemplateSource,
+    /// A built-in template replaced by a file of the same name
+    pub overridden: bool,
+}
+
+/// Project manifests written next to the generated files, one per language
+const MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "pyproject.toml"];
+
+/// Extensions of source files that can be translated to another language; data and
+/// markup files (JSON, HTML, CSS) are left out of cross-language generation
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "js", "jsx", "ts", "tsx", "py", "pyi", "java", "go", "rb",
+];
+
+/// Whether a generated file was new, replaced one already on disk, or was left alone
+/// because it already existed and overwriting wasn't forced. In diff mode existing
+/// files are compared instead and reported as `Differs` or `Unchanged`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    New,
+    Existing,
+    Skipped,
+    Differs,
+    Unchanged,
+}
+
+/// One file generation wrote, or in a dry run would write
+#[derive(Debug, Clone)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub status: FileStatus,
+    /// The rendered content, kept in dry runs that print it
+    pub content: Option<String>,
+    /// Unified diff of the file on disk against the rendered content, for `Differs`
+    pub diff: Option<String>,
+}
+
+impl GeneratedFile {
+    fn new(path: PathBuf, status: FileStatus) -> Self {
+        GeneratedFile {
+            path,
+            status,
+            content: None,
+            diff: None,
+        }
+    }
+}
+
+/// Files written (or, in a dry run, that would be written) by one generation
+#[derive(Debug, Default)]
+pub struct GenerationSummary {
+    pub new_files: Vec<PathBuf>,
+    pub existing_files: Vec<PathBuf>,
+    pub skipped_files: Vec<PathBuf>,
+    pub differing_files: Vec<PathBuf>,
+    pub unchanged_files: Vec<PathBuf>,
+    /// Every file above, in the order generation reached them
+    pub files: Vec<GeneratedFile>,
+}
+
+impl GenerationSummary {
+    fn record(&mut self, file: GeneratedFile) {
+        let path = file.path.clone();
+        match file.status {
+            FileStatus::New => self.new_files.push(path),
+            FileStatus::Existing => self.existing_files.push(path),
+            FileStatus::Skipped => self.skipped_files.push(path),
+            FileStatus::Differs => self.differing_files.push(path),
+            FileStatus::Unchanged => self.unchanged_files.push(path),
+        }
+        self.files.push(file);
+    }
+
+    /// Whether a file other than a kept manifest was skipped for already existing,
+    /// so forcing would have written more
+    pub fn skipped_without_force(&self, output_dir: &Path) -> bool {
+        self.skipped_files
+            .iter()
+            .any(|path| !is_manifest(path, output_dir))
+    }
+}
+
+// Builds the data a source file template is rendered with
+type TemplateData<'a> = fn(&CodeGenerator<'a>, &FilePattern, &CodePattern) -> serde_json::Value;
+
+// Renders a language's project manifest
+type ManifestPlan<'a> =
+    fn(&CodeGenerator<'a>, &CodePattern, &Path) -> Result<PlannedFile, ScaffError>;
+
+/// How a planned file is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    /// Rendered from one of the scaff's files
+    Source,
+    /// A parent module gaining the `pub mod` declarations generated files need
+    Modules,
+    /// The project manifest, only ever created
+    Manifest,
+}
+
+/// A file generation writes, rendered but not yet on disk
+#[derive(Debug)]
+struct PlannedFile {
+    path: PathBuf,
+    content: String,
+    kind: FileKind,
 }
 
 impl<'a> CodeGenerator<'a> {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new() -> Result<Self, ScaffError> {
+        Self::from_templates_dir(Path::new(TEMPLATES_DIR))
+    }
+
+    pub fn from_templates_dir(templates_dir: &Path) -> Result<Self, ScaffError> {
         let mut handlebars = Handlebars::new();
 
         // Register built-in helpers
@@ -18,242 +216,906 @@ impl<'a> CodeGenerator<'a> {
         handlebars.register_helper("lowercase", Box::new(lowercase_helper));
         handlebars.register_helper("pascal_case", Box::new(pascal_case_helper));
         handlebars.register_helper("snake_case", Box::new(snake_case_helper));
+        handlebars.register_helper("kebab_case", Box::new(kebab_case_helper));
+        handlebars.register_helper("camel_case", Box::new(camel_case_helper));
+
+        // Register built-in fallbacks up front so they're always renderable
+        for (name, source) in BUILTIN_TEMPLATES {
+            handlebars.register_template_string(name, source)?;
+        }
 
         // Load templates from templates directory
-        let templates_dir = Path::new("templates");
+        let mut front_matter = HashMap::new();
         if templates_dir.exists() {
             info!("Loading templates from templates directory");
-            load_templates_from_directory(&mut handlebars, templates_dir)?;
+            front_matter = load_templates_from_directory(&mut handlebars, templates_dir)?;
         } else {
             warn!("Templates directory not found, will use inline templates");
         }
 
-        Ok(CodeGenerator { handlebars })
+        // Project-specific helpers may override the built-ins of the same name
+        let helpers_dir = templates_dir.join("helpers");
+        if helpers_dir.exists() {
+            load_helpers_from_directory(&mut handlebars, &helpers_dir)?;
+        }
+
+        Ok(CodeGenerator {
+            handlebars,
+            scaffs: ScaffDirectory::new(),
+            template_override: None,
+            require_template: false,
+            output_extensions: HashMap::new(),
+            dry_run: false,
+            print_content: false,
+            force: false,
+            show_diff: false,
+            target_language: None,
+            only: None,
+            manifest: true,
+            declare_modules: true,
+            post_processors: Vec::new(),
+            vars: HashMap::new(),
+            front_matter,
+        })
+    }
+
+    /// Load scaffs from `scaffs` instead of the default scaffs directory
+    pub fn with_scaff_directory(mut self, scaffs: ScaffDirectory) -> Self {
+        self.scaffs = scaffs;
+        self
+    }
+
+    /// Render files with this template instead of `rust_file`/`js_file`.
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        self.template_override = template;
+        self
+    }
+
+    /// Fail instead of falling back to the built-in default template.
+    pub fn with_require_template(mut self, require: bool) -> Self {
+        self.require_template = require;
+        self
+    }
+
+    /// Write files recorded with extension `from` as `to` instead, keyed `from -> to`
+    /// without the leading dot (e.g. `rs -> txt`).
+    pub fn with_output_extensions(mut self, extensions: HashMap<String, String>) -> Self {
+        self.output_extensions = extensions;
+        self
+    }
+
+    /// Print the files that would be written instead of touching disk. With
+    /// `print_content`, also print each file's rendered content.
+    pub fn with_dry_run(mut self, dry_run: bool, print_content: bool) -> Self {
+        self.dry_run = dry_run;
+        self.print_content = print_content;
+        self
+    }
+
+    /// Overwrite files that already exist instead of skipping them. Existing project
+    /// manifests are kept regardless.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Print a unified diff for existing files whose content would change instead of
+    /// writing them.
+    pub fn with_diff(mut self, show_diff: bool) -> Self {
+        self.show_diff = show_diff;
+        self
+    }
+
+    /// Generate into `target` instead of the scaff's own language
+    pub fn with_target_language(mut self, target: Option<TargetLanguage>) -> Self {
+        self.target_language = target;
+        self
+    }
+
+    /// Only generate scaff files whose path matches one of these globs
+    pub fn with_only(mut self, only: Option<GlobSet>) -> Self {
+        self.only = only;
+        self
+    }
+
+    /// Template variables for `{{vars.<key>}}`, overriding the scaff's defaults of the
+    /// same key
+    pub fn with_vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// Whether to also write the project manifest (Cargo.toml, package.json or
+    /// pyproject.toml). On by default.
+    pub fn with_manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Whether to add `pub mod` declarations for generated Rust files to their parent
+    /// modules, creating those and the crate root as needed. Existing parents are only
+    /// edited with `with_force`, like any other existing file. On by default.
+    pub fn with_module_declarations(mut self, declare_modules: bool) -> Self {
+        self.declare_modules = declare_modules;
+        self
+    }
+
+    /// Run these on the files written, once all of them are, e.g.
+    /// `postprocess::default_formatters()` to format generated code. Processors whose
+    /// tool isn't installed are skipped.
+    pub fn with_post_processors(mut self, processors: Vec<Box<dyn PostProcessor>>) -> Self {
+        self.post_processors = processors;
+        self
     }
 
     pub fn generate_from_scaff(
         &self,
         scaff_name: &str,
         output_dir: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<GenerationSummary, ScaffError> {
         info!("Generating code from scaff: {}", scaff_name);
-
-        // Load the scaff pattern
-        let pattern = self.load_scaff_pattern(scaff_name)?;
+        let pattern = self.load_pattern(scaff_name)?;
 
         // Create output directory
         let output_path = Path::new(output_dir);
-        if !output_path.exists() {
+        if !output_path.exists() && !self.dry_run {
             fs::create_dir_all(output_path)?;
             info!("Created output directory: {}", output_dir);
         }
 
-        // Generate files based on the pattern
-        match pattern.language.as_str() {
-            "Rust" => self.generate_rust_files(&pattern, output_path)?,
-            "JavaScript/TypeScript" => self.generate_js_files(&pattern, output_path)?,
-            _ => {
-                error!("Unsupported language for generation: {}", pattern.language);
-                return Err(format!("Unsupported language: {}", pattern.language).into());
-            }
-        }
-
-        println!(
-            "✅ Successfully generated code from scaff '{}' to '{}'",
-            scaff_name, output_dir
-        );
-        Ok(())
+        self.generate_files(&pattern, output_path)
     }
 
-    fn load_scaff_pattern(
+    /// The paths generating from the scaff would write, in order, without touching
+    /// disk. They come from the same plan generation writes from, so front-matter
+    /// relocation, `--only`, extension mappings and module declarations apply, and
+    /// files generation would skip (existing ones without `--force`, a kept manifest)
+    /// are left out.
+    pub fn planned_paths(
         &self,
         scaff_name: &str,
-    ) -> Result<CodePattern, Box<dyn std::error::Error>> {
-        let scaff_file = format!(
-            "scaffs/{}.json",
-            scaff_name.replace(" ", "_").to_lowercase()
-        );
-        let content = fs::read_to_string(&scaff_file)?;
-        let pattern: CodePattern = serde_json::from_str(&content)?;
-        Ok(pattern)
+        output_dir: &str,
+    ) -> Result<Vec<PathBuf>, ScaffError> {
+        let pattern = self.load_pattern(scaff_name)?;
+        Ok(self
+            .plan(&pattern, Path::new(output_dir))?
+            .into_iter()
+            .filter(|file| self.status(file) != FileStatus::Skipped)
+            .map(|file| file.path)
+            .collect())
+    }
+
+    // The scaff, translated when a target language other than its own was chosen
+    fn load_pattern(&self, scaff_name: &str) -> Result<CodePattern, ScaffError> {
+        let pattern = self.scaffs.find_pattern(scaff_name)?;
+        Ok(match self.target_language {
+            Some(target) if !is_source_language(&pattern, target) => {
+                translate_pattern(&pattern, target)
+            }
+            _ => pattern,
+        })
     }
 
-    fn generate_rust_files(
+    // Render everything first, so a template error leaves the output untouched, and
+    // post-process last, so processors see the final files and the manifest
+    fn generate_files(
         &self,
         pattern: &CodePattern,
         output_dir: &Path,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Generating Rust files from pattern");
-
-        for file_pattern in &pattern.files {
-            if file_pattern.extension == "rs" {
-                self.generate_rust_file(file_pattern, output_dir, pattern)?;
+    ) -> Result<GenerationSummary, ScaffError> {
+        let mut summary = GenerationSummary::default();
+        let mut written = Vec::new();
+        for file in self.plan(pattern, output_dir)? {
+            let generated = self.write_planned(&file)?;
+            if !self.dry_run && matches!(generated.status, FileStatus::New | FileStatus::Existing) {
+                written.push(generated.path.clone());
             }
+            summary.record(generated);
         }
 
-        // Generate Cargo.toml if it doesn't exist
-        let cargo_toml_path = output_dir.join("Cargo.toml");
-        if !cargo_toml_path.exists() {
-            self.generate_cargo_toml(pattern, output_dir)?;
-        }
-
-        Ok(())
+        postprocess::run_post_processors(&self.post_processors, &written);
+        Ok(summary)
     }
 
-    fn generate_rust_file(
+    // Every file generating the pattern writes, rendered, in the order they're written:
+    // the scaff's source files, the module declarations they need, then the manifest
+    fn plan(
         &self,
-        file_pattern: &FilePattern,
-        output_dir: &Path,
         pattern: &CodePattern,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let template_data = json!({
-            "file_name": Path::new(&file_pattern.path).file_stem().unwrap_or_default(),
-            "structs": file_pattern.structs,
-            "functions": file_pattern.functions,
-            "implementations": file_pattern.implementations,
-            "pattern_name": pattern.name,
-            "original_path": file_pattern.path
-        });
+        output_dir: &Path,
+    ) -> Result<Vec<PlannedFile>, ScaffError> {
+        let language = scanner::languages_for_scaff(&pattern.language)
+            .first()
+            .copied();
+        let (extensions, expected, default, template_data, manifest): (
+            &[&str],
+            _,
+            _,
+            TemplateData<'a>,
+            ManifestPlan<'a>,
+        ) = match language {
+            Some("rust") => {
+                info!("Generating Rust files from pattern");
+                (
+                    &["rs"],
+                    "rust_file",
+                    "default_rust_file",
+                    Self::rust_template_data,
+                    Self::plan_cargo_toml,
+                )
+            }
+            Some("javascript" | "typescript") => {
+                info!("Generating JavaScript/TypeScript files from pattern");
+                (
+                    &["js", "ts", "jsx", "tsx"],
+                    "js_file",
+                    "default_js_file",
+                    Self::js_template_data,
+                    Self::plan_package_json,
+                )
+            }
+            Some("python") => {
+                info!("Generating Python files from pattern");
+                (
+                    &["py", "pyi"],
+                    "python_file",
+                    "default_python_file",
+                    Self::python_template_data,
+                    Self::plan_pyproject_toml,
+                )
+            }
+            _ => {
+                error!("Unsupported language for generation: {}", pattern.language);
+                return Err(ScaffError::UnsupportedLanguage(pattern.language.clone()));
+            }
+        };
+
+        let template_name = self.resolve_template_name(expected, default)?;
+        let mut files = Vec::new();
+        for file_pattern in pattern.files.iter().filter(|f| self.selects(f)) {
+            if extensions.contains(&file_pattern.extension.as_str()) {
+                let data = template_data(self, file_pattern, pattern);
+                files.push(PlannedFile {
+                    path: self.template_output_path(
+                        template_name,
+                        file_pattern,
+                        output_dir,
+                        &data,
+                    )?,
+                    content: self.handlebars.render(template_name, &data)?,
+                    kind: FileKind::Source,
+                });
+            }
+        }
 
-        let template_name = if self.handlebars.get_template("rust_file").is_some() {
-            "rust_file"
+        if language == Some("rust") && self.declare_modules {
+            plan_module_declarations(output_dir, &mut files)?;
+        }
+
+        if self.wants_manifest(!files.is_empty()) {
+            files.push(manifest(self, pattern, output_dir)?);
+        }
+        Ok(files)
+    }
+
+    // What writing the planned file does, diffs aside. Existing files, including
+    // modules gaining declarations, are skipped unless overwriting is forced, and
+    // manifests are only ever created: an existing one holds the project's real
+    // dependencies, so it's kept even when forced.
+    fn status(&self, file: &PlannedFile) -> FileStatus {
+        if !file.path.exists() {
+            FileStatus::New
+        } else if self.force && file.kind != FileKind::Manifest {
+            FileStatus::Existing
         } else {
-            "default_rust_file"
-        };
+            FileStatus::Skipped
+        }
+    }
 
-        // Register default template if not found
-        if template_name == "default_rust_file" {
-            let mut handlebars = self.handlebars.clone();
-            handlebars.register_template_string("default_rust_file", DEFAULT_RUST_TEMPLATE)?;
+    // Write a planned file, creating parent directories, or only report it in a dry run
+    fn write_planned(&self, file: &PlannedFile) -> Result<GeneratedFile, ScaffError> {
+        let file_path = file.path.as_path();
+        if self.show_diff && file_path.exists() && file.kind != FileKind::Manifest {
+            return self.diff_output(file_path, &file.content);
         }
 
-        let generated_content = self.handlebars.render(template_name, &template_data)?;
+        let status = self.status(file);
+        let mut generated = GeneratedFile::new(file_path.to_path_buf(), status);
+        if file.kind == FileKind::Manifest && status == FileStatus::Skipped {
+            info!("Keeping existing manifest {}", file_path.display());
+            return Ok(generated);
+        }
+        if status == FileStatus::Skipped && !self.dry_run {
+            warn!(
+                "Skipping existing file {} (use --force to overwrite)",
+                file_path.display()
+            );
+            return Ok(generated);
+        }
 
-        // Create the file path - use the full relative path to preserve directory structure
-        let file_path = output_dir.join(&file_pattern.path);
+        if self.dry_run {
+            if self.print_content {
+                generated.content = Some(file.content.clone());
+            }
+            return Ok(generated);
+        }
 
         // Ensure parent directory exists
         if let Some(parent) = file_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        fs::write(&file_path, generated_content)?;
-        info!("Generated file: {}", file_path.display());
+        fs::write(file_path, &file.content)?;
+        if file.kind == FileKind::Modules {
+            info!("Declared generated modules in {}", file_path.display());
+        } else {
+            info!("Generated file: {}", file_path.display());
+        }
+
+        Ok(generated)
+    }
 
-        Ok(())
+    // Compare the file on disk with the generated content, without writing
+    fn diff_output(&self, file_path: &Path, content: &str) -> Result<GeneratedFile, ScaffError> {
+        let current = fs::read_to_string(file_path)?;
+        if current == content {
+            debug!("Unchanged: {}", file_path.display());
+            return Ok(GeneratedFile::new(
+                file_path.to_path_buf(),
+                FileStatus::Unchanged,
+            ));
+        }
+
+        let path = file_path.display().to_string();
+        let diff = TextDiff::from_lines(current.as_str(), content)
+            .unified_diff()
+            .header(
+                &format!("{} (on disk)", path),
+                &format!("{} (generated)", path),
+            )
+            .to_string();
+        Ok(GeneratedFile {
+            diff: Some(diff),
+            ..GeneratedFile::new(file_path.to_path_buf(), FileStatus::Differs)
+        })
+    }
+
+    // Glob paths can't be written, and --only may leave files out
+    fn selects(&self, file_pattern: &FilePattern) -> bool {
+        !skip_glob(file_pattern)
+            && self
+                .only
+                .as_ref()
+                .is_none_or(|only| only.is_match(normalize_path(&file_pattern.path)))
+    }
+
+    // With --only, the project manifest only comes along with a matching file
+    fn wants_manifest(&self, generated_any: bool) -> bool {
+        self.manifest && (self.only.is_none() || generated_any)
+    }
+
+    // Use the full relative path to preserve directory structure, swapping the
+    // extension if it's remapped
+    fn output_path(&self, file_pattern: &FilePattern, output_dir: &Path) -> PathBuf {
+        let mut file_path = output_dir.join(&file_pattern.path);
+        if let Some(extension) = self.output_extensions.get(&file_pattern.extension) {
+            file_path.set_extension(extension);
+        }
+        file_path
     }
 
-    fn generate_js_files(
+    // Where a template puts its output: the rendered front-matter `path` under the
+    // output directory when the template has one, otherwise the scaff file's own path
+    fn template_output_path(
         &self,
-        pattern: &CodePattern,
+        template_name: &str,
+        file_pattern: &FilePattern,
         output_dir: &Path,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Generating JavaScript/TypeScript files from pattern");
+        template_data: &serde_json::Value,
+    ) -> Result<PathBuf, ScaffError> {
+        let Some(expression) = self
+            .front_matter
+            .get(template_name)
+            .and_then(|front_matter| front_matter.path.as_deref())
+        else {
+            return Ok(self.output_path(file_pattern, output_dir));
+        };
 
-        for file_pattern in &pattern.files {
-            if ["js", "ts", "jsx", "tsx"].contains(&file_pattern.extension.as_str()) {
-                self.generate_js_file(file_pattern, output_dir, pattern)?;
-            }
+        let rendered = self.handlebars.render_template(expression, template_data)?;
+        let relative = Path::new(rendered.trim());
+        let escapes = relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if relative.as_os_str().is_empty() || escapes {
+            return Err(ScaffError::InvalidOutputPath {
+                template: template_name.to_string(),
+                path: rendered,
+            });
         }
+        Ok(output_dir.join(relative))
+    }
+
+    // The scaff's variables with those passed to the generator layered on top
+    fn template_vars(&self, pattern: &CodePattern) -> HashMap<String, String> {
+        let mut vars = pattern.variables.clone();
+        vars.extend(self.vars.clone());
+        vars
+    }
 
-        // Generate package.json if it doesn't exist
-        let package_json_path = output_dir.join("package.json");
-        if !package_json_path.exists() {
-            self.generate_package_json(pattern, output_dir)?;
+    fn resolve_template_name<'t>(
+        &'t self,
+        expected: &'t str,
+        default: &'t str,
+    ) -> Result<&'t str, ScaffError> {
+        let expected = self.template_override.as_deref().unwrap_or(expected);
+
+        if self.handlebars.get_template(expected).is_some() {
+            Ok(expected)
+        } else if self.require_template {
+            Err(ScaffError::RequiredTemplateNotFound(expected.to_string()))
+        } else {
+            warn!(
+                "Template '{}' not found, falling back to {}",
+                expected, default
+            );
+            Ok(default)
         }
+    }
 
-        Ok(())
+    fn rust_template_data(
+        &self,
+        file_pattern: &FilePattern,
+        pattern: &CodePattern,
+    ) -> serde_json::Value {
+        json!({
+            "file_name": Path::new(&file_pattern.path).file_stem().unwrap_or_default().to_string_lossy(),
+            "structs": unqualified_names(&file_pattern.structs),
+            "struct_items": type_items(&file_pattern.structs, file_pattern),
+            "functions": unqualified_names(&file_pattern.functions),
+            "implementations": unqualified_names(&file_pattern.implementations),
+            "impl_items": type_items(&file_pattern.implementations, file_pattern),
+            "enums": unqualified_names(&file_pattern.enums),
+            "traits": unqualified_names(&file_pattern.traits),
+            "pattern_name": pattern.name,
+            "vars": self.template_vars(pattern),
+            "original_path": file_pattern.path
+        })
     }
 
-    fn generate_js_file(
+    fn js_template_data(
         &self,
         file_pattern: &FilePattern,
-        output_dir: &Path,
         pattern: &CodePattern,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let template_data = json!({
-            "file_name": Path::new(&file_pattern.path).file_stem().unwrap_or_default(),
+    ) -> serde_json::Value {
+        json!({
+            "file_name": Path::new(&file_pattern.path).file_stem().unwrap_or_default().to_string_lossy(),
             "classes": file_pattern.classes,
             "functions": file_pattern.functions,
             "pattern_name": pattern.name,
+            "vars": self.template_vars(pattern),
             "original_path": file_pattern.path,
             "extension": file_pattern.extension
-        });
-
-        let template_name = if self.handlebars.get_template("js_file").is_some() {
-            "js_file"
-        } else {
-            "default_js_file"
-        };
-
-        // Register default template if not found
-        if template_name == "default_js_file" {
-            let mut handlebars = self.handlebars.clone();
-            handlebars.register_template_string("default_js_file", DEFAULT_JS_TEMPLATE)?;
-        }
-
-        let generated_content = self.handlebars.render(template_name, &template_data)?;
-
-        // Create the file path - use the full relative path to preserve directory structure
-        let file_path = output_dir.join(&file_pattern.path);
-
-        // Ensure parent directory exists
-        if let Some(parent) = file_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        fs::write(&file_path, generated_content)?;
-        info!("Generated file: {}", file_path.display());
+        })
+    }
 
-        Ok(())
+    fn python_template_data(
+        &self,
+        file_pattern: &FilePattern,
+        pattern: &CodePattern,
+    ) -> serde_json::Value {
+        json!({
+            "file_name": Path::new(&file_pattern.path).file_stem().unwrap_or_default().to_string_lossy(),
+            "classes": file_pattern.classes,
+            "functions": file_pattern.functions,
+            "pattern_name": pattern.name,
+            "vars": self.template_vars(pattern),
+            "original_path": file_pattern.path
+        })
     }
 
-    fn generate_cargo_toml(
+    fn plan_cargo_toml(
         &self,
         pattern: &CodePattern,
         output_dir: &Path,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<PlannedFile, ScaffError> {
         let template_data = json!({
             "project_name": pattern.name.replace(" ", "_").to_lowercase(),
-            "pattern_name": pattern.name
+            "pattern_name": pattern.name,
+            "vars": self.template_vars(pattern)
         });
 
         let cargo_toml_content = self
             .handlebars
-            .render_template(DEFAULT_CARGO_TEMPLATE, &template_data)?;
-        let cargo_path = output_dir.join("Cargo.toml");
-        fs::write(&cargo_path, cargo_toml_content)?;
-        info!("Generated Cargo.toml");
-
-        Ok(())
+            .render("default_cargo_toml", &template_data)?;
+        Ok(PlannedFile {
+            path: output_dir.join("Cargo.toml"),
+            content: cargo_toml_content,
+            kind: FileKind::Manifest,
+        })
     }
 
-    fn generate_package_json(
+    fn plan_package_json(
         &self,
         pattern: &CodePattern,
         output_dir: &Path,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<PlannedFile, ScaffError> {
         let template_data = json!({
             "project_name": pattern.name.replace(" ", "-").to_lowercase(),
-            "pattern_name": pattern.name
+            "pattern_name": pattern.name,
+            "vars": self.template_vars(pattern)
         });
 
         let package_json_content = self
             .handlebars
-            .render_template(DEFAULT_PACKAGE_TEMPLATE, &template_data)?;
-        let package_path = output_dir.join("package.json");
-        fs::write(&package_path, package_json_content)?;
-        info!("Generated package.json");
+            .render("default_package_json", &template_data)?;
+        Ok(PlannedFile {
+            path: output_dir.join("package.json"),
+            content: package_json_content,
+            kind: FileKind::Manifest,
+        })
+    }
 
-        Ok(())
+    fn plan_pyproject_toml(
+        &self,
+        pattern: &CodePattern,
+        output_dir: &Path,
+    ) -> Result<PlannedFile, ScaffError> {
+        let template_data = json!({
+            "project_name": pattern.name.replace(" ", "-").to_lowercase(),
+            "pattern_name": pattern.name,
+            "vars": self.template_vars(pattern)
+        });
+
+        let pyproject_content = self
+            .handlebars
+            .render("default_pyproject_toml", &template_data)?;
+        Ok(PlannedFile {
+            path: output_dir.join("pyproject.toml"),
+            content: pyproject_content,
+            kind: FileKind::Manifest,
+        })
     }
 }
 
+// Whether `path` is the project manifest at the root of the output directory
+fn is_manifest(path: &Path, output_dir: &Path) -> bool {
+    path.parent() == Some(output_dir)
+        && path
+            .file_name()
+            .is_some_and(|name| MANIFESTS.iter().any(|manifest| name == *manifest))
+}
+
+// Whether the scaff was scanned from `target` already, counting either side of the
+// combined JavaScript/TypeScript label
+fn is_source_language(pattern: &CodePattern, target: TargetLanguage) -> bool {
+    scanner::languages_for_scaff(&pattern.language).contains(&target.language())
+}
+
+// Rewrite a scaff for another language: source files take the target's extension,
+// Rust structs become classes and vice versa. Items with no counterpart in the
+// target (enums, traits, impls when leaving Rust) are dropped.
+fn translate_pattern(pattern: &CodePattern, target: TargetLanguage) -> CodePattern {
+    let language = scanner::get_language_display_name(target.language());
+    info!(
+        "Translating scaff '{}' from {} to {}",
+        pattern.name, pattern.language, language
+    );
+
+    let files = pattern
+        .files
+        .iter()
+        .filter(|f| SOURCE_EXTENSIONS.contains(&f.extension.as_str()))
+        .map(|f| {
+            let mut types = unqualified_names(&f.structs);
+            types.extend(f.classes.iter().cloned());
+            let (classes, structs) = match target {
+                TargetLanguage::Rust => (Vec::new(), types),
+                TargetLanguage::Javascript
+                | TargetLanguage::Typescript
+                | TargetLanguage::Python => (types, Vec::new()),
+            };
+            FilePattern {
+                path: Path::new(&f.path)
+                    .with_extension(target.extension())
+                    .to_string_lossy()
+                    .into_owned(),
+                glob: f.glob,
+                extension: target.extension().to_string(),
+                language: Some(language.to_string()),
+                classes,
+                functions: unqualified_names(&f.functions),
+                structs,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    CodePattern {
+        language: language.to_string(),
+        files,
+        ..pattern.clone()
+    }
+}
+
+// Generated files don't recreate inline modules, so module-qualified Rust names like
+// `api::Config` are reduced to their last segment, keeping the first of any duplicates
+fn unqualified_names(names: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    names
+        .iter()
+        .map(|name| unqualified(name).to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+// Structs or impl targets as `{ name, fields, methods }` for templates, with the
+// field names and impl methods the scanner captured for each; deduplicated like
+// `unqualified_names`
+fn type_items(names: &[String], file_pattern: &FilePattern) -> Vec<serde_json::Value> {
+    let mut seen = HashSet::new();
+    names
+        .iter()
+        .filter(|name| seen.insert(unqualified(name)))
+        .map(|name| {
+            json!({
+                "name": unqualified(name),
+                "fields": file_pattern.struct_fields.get(name).cloned().unwrap_or_default(),
+                "methods": file_pattern.impl_methods.get(name).cloned().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+// Add the `pub mod` declarations the planned Rust files need to be part of the crate
+// to their parent modules, so it compiles without manual wiring. A parent that is
+// itself planned gains them in its rendered content; any other is planned as a
+// module file, created when it doesn't exist. Only files under src/ are wired up,
+// binaries aside, and the crate root is src/lib.rs unless only src/main.rs exists.
+// Declarations already present, with any visibility, aren't repeated.
+fn plan_module_declarations(
+    output_dir: &Path,
+    files: &mut Vec<PlannedFile>,
+) -> Result<(), ScaffError> {
+    let src = output_dir.join("src");
+    let exists = |path: &Path| files.iter().any(|f| f.path == path) || path.exists();
+    let crate_root = if !exists(&src.join("lib.rs")) && exists(&src.join("main.rs")) {
+        src.join("main.rs")
+    } else {
+        src.join("lib.rs")
+    };
+
+    let mut declarations: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+    for file in files.iter().map(|f| &f.path) {
+        let Ok(relative) = file.strip_prefix(&src) else {
+            continue;
+        };
+        let is_root = ["lib.rs", "main.rs", "mod.rs"]
+            .iter()
+            .any(|root| relative == Path::new(root));
+        if file.extension().is_none_or(|e| e != "rs") || is_root || relative.starts_with("bin") {
+            continue;
+        }
+
+        let mut dir = file.parent().unwrap_or(&src).to_path_buf();
+        let mut name = file
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        if name == "mod" {
+            name = dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            dir.pop();
+        }
+
+        // Declare the module in its parent, then the parent in its own, up to the root
+        loop {
+            if !is_module_name(&name) {
+                warn!(
+                    "Not declaring module '{}' for {}: not a valid Rust identifier",
+                    name,
+                    file.display()
+                );
+                break;
+            }
+            let parent = if dir == src {
+                crate_root.clone()
+            } else if exists(&dir.with_extension("rs")) {
+                dir.with_extension("rs")
+            } else {
+                dir.join("mod.rs")
+            };
+            if !declarations.entry(parent).or_default().insert(name) || dir == src {
+                break;
+            }
+            name = dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            dir.pop();
+        }
+    }
+
+    for (path, names) in declarations {
+        let index = files.iter().position(|f| f.path == path);
+        let mut content = match index {
+            Some(index) => files[index].content.clone(),
+            None => match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e.into()),
+            },
+        };
+        let missing: Vec<String> = names
+            .into_iter()
+            .filter(|name| !declares_module(&content, name))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+
+        if !content.trim().is_empty() {
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            if !content.ends_with("\n\n") {
+                content.push('\n');
+            }
+        }
+        for name in missing {
+            content.push_str(&format!("pub mod {};\n", name));
+        }
+        match index {
+            Some(index) => files[index].content = content,
+            None => files.push(PlannedFile {
+                path,
+                content,
+                kind: FileKind::Modules,
+            }),
+        }
+    }
+    Ok(())
+}
+
+// Whether the source already has `mod name;` or `mod name { .. }`, with any visibility
+fn declares_module(content: &str, name: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("//"))
+        .any(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            tokens
+                .windows(2)
+                .any(|pair| pair[0] == "mod" && pair[1].trim_end_matches([';', '{']) == name)
+        })
+}
+
+fn is_module_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Glob paths describe files to validate, not a single file that can be written
+fn skip_glob(file_pattern: &FilePattern) -> bool {
+    if file_pattern.glob {
+        warn!("Skipping glob path {} during generation", file_pattern.path);
+    }
+    file_pattern.glob
+}
+
+/// Settings from the `---`-delimited block at the top of a `.hbs` template
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TemplateFrontMatter {
+    /// Handlebars expression for the output path relative to the output directory,
+    /// rendered with the same data as the template body
+    path: Option<String>,
+}
+
+// Split YAML-style `key: value` front-matter off the top of a template, returning it
+// with the remaining body. Content without a closing `---` is all body.
+fn split_front_matter(content: &str) -> (Option<TemplateFrontMatter>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut front_matter = TemplateFrontMatter::default();
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim();
+        if line == "---" {
+            return (Some(front_matter), &rest[offset..]);
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.split_once(':') {
+            Some((key, value)) if key.trim() == "path" => {
+                let value = value.trim();
+                let unquoted = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                    .unwrap_or(value);
+                front_matter.path = Some(unquoted.to_string());
+            }
+            _ => warn!("Ignoring unsupported template front-matter line: {}", line),
+        }
+    }
+
+    (None, content)
+}
+
+// `.hbs` files in the templates directory by template name, sorted; empty when the
+// directory is absent
+fn template_files(templates_dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(templates_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(String, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("hbs"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some((name, path))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Templates loaded from `templates_dir` followed by the built-ins
+pub fn list_templates(templates_dir: &Path) -> Vec<TemplateInfo> {
+    let files = template_files(templates_dir);
+    let mut templates: Vec<TemplateInfo> = files
+        .iter()
+        .map(|(name, path)| TemplateInfo {
+            name: name.clone(),
+            source: TemplateSource::File(path.clone()),
+            overridden: false,
+        })
+        .collect();
+    templates.extend(BUILTIN_TEMPLATES.iter().map(|(name, _)| TemplateInfo {
+        name: name.to_string(),
+        source: TemplateSource::Builtin,
+        overridden: files.iter().any(|(file_name, _)| file_name == name),
+    }));
+    templates
+}
+
+/// Source of the template generation would use under `name`, front-matter included
+pub fn template_source(templates_dir: &Path, name: &str) -> Result<String, ScaffError> {
+    if let Some((_, path)) = template_files(templates_dir)
+        .into_iter()
+        .find(|(file_name, _)| file_name == name)
+    {
+        return Ok(fs::read_to_string(path)?);
+    }
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, source)| source.trim_start().to_string())
+        .ok_or_else(|| ScaffError::TemplateNotFound(name.to_string()))
+}
+
+// Register every `.hbs` template in the directory, returning the front-matter of
+// those that have it
 fn load_templates_from_directory(
     handlebars: &mut Handlebars,
     templates_dir: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<HashMap<String, TemplateFrontMatter>, ScaffError> {
     let entries = fs::read_dir(templates_dir)?;
+    let mut front_matters = HashMap::new();
 
     for entry in entries {
         let entry = entry?;
@@ -267,7 +1129,11 @@ fn load_templates_from_directory(
 
             match fs::read_to_string(&path) {
                 Ok(content) => {
-                    handlebars.register_template_string(template_name, content)?;
+                    let (front_matter, body) = split_front_matter(&content);
+                    handlebars.register_template_string(template_name, body)?;
+                    if let Some(front_matter) = front_matter {
+                        front_matters.insert(template_name.to_string(), front_matter);
+                    }
                     debug!("Loaded template: {}", template_name);
                 }
                 Err(e) => {
@@ -277,9 +1143,165 @@ fn load_templates_from_directory(
         }
     }
 
+    Ok(front_matters)
+}
+
+/// A string-transform helper defined in `templates/helpers/<name>.json`. Steps are
+/// applied in order: `replace` pairs, then `case`, then `prefix`/`suffix`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransformHelper {
+    #[serde(default)]
+    replace: Vec<(String, String)>,
+    #[serde(default)]
+    case: Option<String>,
+    #[serde(default)]
+    prefix: String,
+    #[serde(default)]
+    suffix: String,
+}
+
+impl TransformHelper {
+    fn apply(&self, value: &str) -> String {
+        let mut value = value.to_string();
+        for (from, to) in &self.replace {
+            value = value.replace(from.as_str(), to);
+        }
+        if let Some(case) = &self.case {
+            value = convert_case(case, &value).unwrap_or(value);
+        }
+        format!("{}{}{}", self.prefix, value, self.suffix)
+    }
+}
+
+impl handlebars::HelperDef for TransformHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &handlebars::Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut handlebars::RenderContext<'reg, 'rc>,
+        out: &mut dyn handlebars::Output,
+    ) -> handlebars::HelperResult {
+        let param = h.param(0).and_then(|v| v.value().as_str()).unwrap_or("");
+        out.write(&self.apply(param))?;
+        Ok(())
+    }
+}
+
+fn load_helpers_from_directory(
+    handlebars: &mut Handlebars,
+    helpers_dir: &Path,
+) -> Result<(), ScaffError> {
+    for entry in fs::read_dir(helpers_dir)? {
+        let path = entry?.path();
+        let extension = path.extension().and_then(|s| s.to_str());
+
+        if path.is_file() && extension == Some("rhai") {
+            warn!(
+                "Script helpers are not supported, skipping {}",
+                path.display()
+            );
+            continue;
+        }
+        if !path.is_file() || extension != Some("json") {
+            continue;
+        }
+
+        let helper_name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let helper = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<TransformHelper>(&content).map_err(|e| e.to_string())
+            })
+            .and_then(|helper| match &helper.case {
+                Some(case) if convert_case(case, "").is_none() => {
+                    Err(format!("unknown case '{}'", case))
+                }
+                _ => Ok(helper),
+            });
+
+        match helper {
+            Ok(helper) => {
+                handlebars.register_helper(&helper_name, Box::new(helper));
+                debug!("Loaded helper: {}", helper_name);
+            }
+            Err(e) => {
+                warn!("Failed to load helper {}: {}", path.display(), e);
+            }
+        }
+    }
+
     Ok(())
 }
 
+// Apply one of the built-in case helpers by name
+fn convert_case(case: &str, value: &str) -> Option<String> {
+    match case {
+        "uppercase" => Some(value.to_uppercase()),
+        "lowercase" => Some(value.to_lowercase()),
+        "pascal_case" => Some(to_pascal_case(value)),
+        "snake_case" => Some(to_snake_case(value)),
+        "kebab_case" => Some(to_kebab_case(value)),
+        "camel_case" => Some(to_camel_case(value)),
+        _ => None,
+    }
+}
+
+fn to_pascal_case(value: &str) -> String {
+    value
+        .split('_')
+        .map(|word| {
+            let mut chars: Vec<char> = word.chars().collect();
+            if !chars.is_empty() {
+                chars[0] = chars[0].to_uppercase().next().unwrap_or(chars[0]);
+            }
+            chars.into_iter().collect::<String>()
+        })
+        .collect::<String>()
+}
+
+// Word boundaries fall before an uppercase letter that follows a lowercase letter or
+// digit, and before the last capital of an acronym run (`HTTPServer` -> `http_server`)
+fn to_snake_case(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut snake_case = String::with_capacity(value.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                snake_case.push('_');
+            }
+        }
+        snake_case.extend(c.to_lowercase());
+    }
+
+    snake_case
+}
+
+fn to_kebab_case(value: &str) -> String {
+    to_snake_case(value).replace('_', "-")
+}
+
+fn to_camel_case(value: &str) -> String {
+    let pascal_case = to_pascal_case(value);
+    let mut chars = pascal_case.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 // Helper functions for Handlebars
 fn uppercase_helper(
     h: &handlebars::Helper,
@@ -313,17 +1335,7 @@ fn pascal_case_helper(
     out: &mut dyn handlebars::Output,
 ) -> handlebars::HelperResult {
     let param = h.param(0).and_then(|v| v.value().as_str()).unwrap_or("");
-    let pascal_case = param
-        .split('_')
-        .map(|word| {
-            let mut chars: Vec<char> = word.chars().collect();
-            if !chars.is_empty() {
-                chars[0] = chars[0].to_uppercase().next().unwrap_or(chars[0]);
-            }
-            chars.into_iter().collect::<String>()
-        })
-        .collect::<String>();
-    out.write(&pascal_case)?;
+    out.write(&to_pascal_case(param))?;
     Ok(())
 }
 
@@ -335,18 +1347,31 @@ fn snake_case_helper(
     out: &mut dyn handlebars::Output,
 ) -> handlebars::HelperResult {
     let param = h.param(0).and_then(|v| v.value().as_str()).unwrap_or("");
-    let snake_case = param
-        .chars()
-        .enumerate()
-        .map(|(i, c)| {
-            if c.is_uppercase() && i > 0 {
-                format!("_{}", c.to_lowercase())
-            } else {
-                c.to_lowercase().to_string()
-            }
-        })
-        .collect::<String>();
-    out.write(&snake_case)?;
+    out.write(&to_snake_case(param))?;
+    Ok(())
+}
+
+fn kebab_case_helper(
+    h: &handlebars::Helper,
+    _: &Handlebars,
+    _: &handlebars::Context,
+    _: &mut handlebars::RenderContext,
+    out: &mut dyn handlebars::Output,
+) -> handlebars::HelperResult {
+    let param = h.param(0).and_then(|v| v.value().as_str()).unwrap_or("");
+    out.write(&to_kebab_case(param))?;
+    Ok(())
+}
+
+fn camel_case_helper(
+    h: &handlebars::Helper,
+    _: &Handlebars,
+    _: &handlebars::Context,
+    _: &mut handlebars::RenderContext,
+    out: &mut dyn handlebars::Output,
+) -> handlebars::HelperResult {
+    let param = h.param(0).and_then(|v| v.value().as_str()).unwrap_or("");
+    out.write(&to_camel_case(param))?;
     Ok(())
 }
 
@@ -355,21 +1380,52 @@ const DEFAULT_RUST_TEMPLATE: &str = r#"
 // Generated from scaff pattern: {{pattern_name}}
 // Original file: {{original_path}}
 
-{{#each structs}}
+{{#each struct_items}}
 #[derive(Debug, Clone)]
-pub struct {{this}} {
-    // TODO: Add fields for {{this}}
+pub struct {{name}} {
+{{#each fields}}
+    pub {{this}}: (), // TODO: Set the type of {{this}}
+{{else}}
+    // TODO: Add fields for {{name}}
+{{/each}}
 }
 
 {{/each}}
 
-{{#each implementations}}
-impl {{this}} {
+{{#each enums}}
+#[derive(Debug, Clone)]
+pub enum {{this}} {
+    // TODO: Add variants for {{this}}
+}
+
+{{/each}}
+
+{{#each traits}}
+pub trait {{this}} {
+    // TODO: Add methods for {{this}}
+}
+
+{{/each}}
+
+{{#each impl_items}}
+impl {{name}} {
     pub fn new() -> Self {
-        {{this}} {
+        {{name}} {
+{{#each fields}}
+            {{this}}: (),
+{{else}}
             // TODO: Initialize fields
+{{/each}}
         }
     }
+{{#each methods}}
+{{#if (ne this "new")}}
+
+    pub fn {{this}}(&self) {
+        // TODO: Implement {{this}}
+    }
+{{/if}}
+{{/each}}
 }
 
 {{/each}}
@@ -410,6 +1466,28 @@ export { {{this}} };
 {{/if}}
 "#;
 
+const DEFAULT_PYTHON_TEMPLATE: &str = r#"
+# Generated from scaff pattern: {{pattern_name}}
+# Original file: {{original_path}}
+
+{{#each classes}}
+class {{this}}:
+    """TODO: Document {{this}}."""
+
+    def __init__(self):
+        pass
+
+
+{{/each}}
+{{#each functions}}
+def {{this}}():
+    """TODO: Implement {{this}}."""
+    raise NotImplementedError
+
+
+{{/each}}
+"#;
+
 const DEFAULT_CARGO_TEMPLATE: &str = r#"
 [package]
 name = "{{project_name}}"
@@ -421,6 +1499,15 @@ edition = "2021"
 [dependencies]
 "#;
 
+const DEFAULT_PYPROJECT_TEMPLATE: &str = r#"
+[project]
+name = "{{project_name}}"
+version = "0.1.0"
+description = "Generated from scaff pattern: {{pattern_name}}"
+requires-python = ">=3.8"
+dependencies = []
+"#;
+
 const DEFAULT_PACKAGE_TEMPLATE: &str = r#"
 {
   "name": "{{project_name}}",
@@ -437,9 +1524,10 @@ const DEFAULT_PACKAGE_TEMPLATE: &str = r#"
 "#;
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
-    use crate::pattern::{CodePattern, FilePattern};
+    use crate::pattern::{CodePattern, FilePattern, SCHEMA_VERSION};
     use std::fs;
     use tempfile::TempDir;
 
@@ -447,10 +1535,10 @@ mod tests {
         FilePattern {
             path: "src/main.rs".to_string(),
             extension: "rs".to_string(),
-            classes: vec![],
             functions: vec!["main".to_string(), "test_function".to_string()],
             structs: vec!["TestStruct".to_string()],
             implementations: vec!["TestStruct".to_string()],
+            ..Default::default()
         }
     }
 
@@ -460,28 +1548,31 @@ mod tests {
             extension: "js".to_string(),
             classes: vec!["TestClass".to_string()],
             functions: vec!["testFunction".to_string()],
-            structs: vec![],
-            implementations: vec![],
+            ..Default::default()
         }
     }
 
     fn create_test_pattern() -> CodePattern {
         CodePattern {
+            schema_version: SCHEMA_VERSION,
             name: "test_pattern".to_string(),
             description: "Test pattern".to_string(),
             language: "Rust".to_string(),
             files: vec![create_test_file_pattern()],
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
         }
     }
 
     fn create_test_js_pattern() -> CodePattern {
         CodePattern {
+            schema_version: SCHEMA_VERSION,
             name: "test_js_pattern".to_string(),
             description: "Test JavaScript pattern".to_string(),
             language: "JavaScript/TypeScript".to_string(),
             files: vec![create_test_js_file_pattern()],
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
         }
     }
 
@@ -502,46 +1593,123 @@ mod tests {
     }
 
     #[test]
-    fn test_uppercase_helper() -> Result<(), Box<dyn std::error::Error>> {
-        let mut handlebars = Handlebars::new();
-        handlebars.register_helper("uppercase", Box::new(uppercase_helper));
+    fn test_uppercase_helper() -> Result<(), Box<dyn std::error::Error>> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("uppercase", Box::new(uppercase_helper));
+
+        let template = "{{uppercase \"hello\"}}";
+        let result = handlebars.render_template(template, &json!({}))?;
+        assert_eq!(result, "HELLO");
+        Ok(())
+    }
+
+    #[test]
+    fn test_lowercase_helper() -> Result<(), Box<dyn std::error::Error>> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("lowercase", Box::new(lowercase_helper));
+
+        let template = "{{lowercase \"HELLO\"}}";
+        let result = handlebars.render_template(template, &json!({}))?;
+        assert_eq!(result, "hello");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pascal_case_helper() -> Result<(), Box<dyn std::error::Error>> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("pascal_case", Box::new(pascal_case_helper));
+
+        let template = "{{pascal_case \"hello_world\"}}";
+        let result = handlebars.render_template(template, &json!({}))?;
+        assert_eq!(result, "HelloWorld");
+        Ok(())
+    }
+
+    #[test]
+    fn test_snake_case_helper() -> Result<(), Box<dyn std::error::Error>> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("snake_case", Box::new(snake_case_helper));
+
+        let template = "{{snake_case \"HelloWorld\"}}";
+        let result = handlebars.render_template(template, &json!({}))?;
+        assert_eq!(result, "hello_world");
+        Ok(())
+    }
+
+    #[test]
+    fn test_snake_case_acronyms_and_digits() {
+        assert_eq!(to_snake_case("HTTPServer"), "http_server");
+        assert_eq!(to_snake_case("parseJSON2"), "parse_json2");
+        assert_eq!(
+            to_snake_case("getHTTPResponseCode"),
+            "get_http_response_code"
+        );
+        assert_eq!(to_snake_case("Version2Parser"), "version2_parser");
+        assert_eq!(to_snake_case("IO"), "io");
+        assert_eq!(to_snake_case("already_snake_case"), "already_snake_case");
+        assert_eq!(to_snake_case("Mixed_Case"), "mixed_case");
+    }
 
-        let template = "{{uppercase \"hello\"}}";
-        let result = handlebars.render_template(template, &json!({}))?;
-        assert_eq!(result, "HELLO");
-        Ok(())
+    #[test]
+    fn test_pascal_case_round_trips_snake_case() {
+        for name in ["HttpServer", "ParseJson2", "UserAccount"] {
+            assert_eq!(to_pascal_case(&to_snake_case(name)), name);
+        }
+        for name in ["http_server", "parse_json2", "user_account"] {
+            assert_eq!(to_snake_case(&to_pascal_case(name)), name);
+        }
+        assert_eq!(to_pascal_case(&to_snake_case("HTTPServer")), "HttpServer");
     }
 
     #[test]
-    fn test_lowercase_helper() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_kebab_case_helper() -> Result<(), Box<dyn std::error::Error>> {
         let mut handlebars = Handlebars::new();
-        handlebars.register_helper("lowercase", Box::new(lowercase_helper));
+        handlebars.register_helper("kebab_case", Box::new(kebab_case_helper));
 
-        let template = "{{lowercase \"HELLO\"}}";
+        let template = "{{kebab_case \"HelloWorld\"}} {{kebab_case \"hello_world\"}}";
         let result = handlebars.render_template(template, &json!({}))?;
-        assert_eq!(result, "hello");
+        assert_eq!(result, "hello-world hello-world");
         Ok(())
     }
 
     #[test]
-    fn test_pascal_case_helper() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_camel_case_helper() -> Result<(), Box<dyn std::error::Error>> {
         let mut handlebars = Handlebars::new();
-        handlebars.register_helper("pascal_case", Box::new(pascal_case_helper));
+        handlebars.register_helper("camel_case", Box::new(camel_case_helper));
 
-        let template = "{{pascal_case \"hello_world\"}}";
+        let template = "{{camel_case \"hello_world\"}}";
         let result = handlebars.render_template(template, &json!({}))?;
-        assert_eq!(result, "HelloWorld");
+        assert_eq!(result, "helloWorld");
         Ok(())
     }
 
     #[test]
-    fn test_snake_case_helper() -> Result<(), Box<dyn std::error::Error>> {
-        let mut handlebars = Handlebars::new();
-        handlebars.register_helper("snake_case", Box::new(snake_case_helper));
+    fn test_load_json_helpers() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let helpers_dir = temp_dir.path().join("helpers");
+        fs::create_dir_all(&helpers_dir)?;
+        fs::write(
+            helpers_dir.join("pluralize.json"),
+            r#"{ "case": "snake_case", "suffix": "s" }"#,
+        )?;
+        fs::write(
+            helpers_dir.join("dotted.json"),
+            r#"{ "replace": [["_", "."]], "prefix": "app." }"#,
+        )?;
+        fs::write(helpers_dir.join("broken.json"), r#"{ "case": "shouting" }"#)?;
 
-        let template = "{{snake_case \"HelloWorld\"}}";
-        let result = handlebars.render_template(template, &json!({}))?;
-        assert_eq!(result, "hello_world");
+        let generator = CodeGenerator::from_templates_dir(temp_dir.path())?;
+        let result = generator.handlebars.render_template(
+            "{{pluralize \"UserAccount\"}} {{dotted \"user_id\"}}",
+            &json!({}),
+        )?;
+        assert_eq!(result, "user_accounts app.user.id");
+        assert!(
+            generator
+                .handlebars
+                .render_template("{{broken \"x\"}}", &json!({}))
+                .is_err()
+        );
         Ok(())
     }
 
@@ -549,12 +1717,11 @@ mod tests {
     fn test_generate_rust_file() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
         let pattern = create_test_pattern();
-        let file_pattern = &pattern.files[0];
 
         // Test might fail if generator can't be created due to missing templates
         match CodeGenerator::new() {
             Ok(generator) => {
-                match generator.generate_rust_file(file_pattern, temp_dir.path(), &pattern) {
+                match generator.generate_files(&pattern, temp_dir.path()) {
                     Ok(_) => {
                         let generated_file = temp_dir.path().join("src/main.rs");
                         assert!(generated_file.exists());
@@ -580,14 +1747,144 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_list_templates() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let templates_dir = temp_dir.path().join("templates");
+
+        // Built-ins are listed even without a templates directory
+        let names: Vec<String> = list_templates(&templates_dir)
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(
+            names,
+            BUILTIN_TEMPLATES
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>()
+        );
+
+        fs::create_dir_all(&templates_dir)?;
+        fs::write(templates_dir.join("rust_file.hbs"), "// {{pattern_name}}")?;
+        fs::write(templates_dir.join("default_js_file.hbs"), "// custom")?;
+        fs::write(templates_dir.join("notes.txt"), "not a template")?;
+
+        let templates = list_templates(&templates_dir);
+        assert_eq!(templates.len(), BUILTIN_TEMPLATES.len() + 2);
+        assert_eq!(templates[0].name, "default_js_file");
+        assert_eq!(
+            templates[1].source,
+            TemplateSource::File(templates_dir.join("rust_file.hbs"))
+        );
+        let overridden: Vec<&str> = templates
+            .iter()
+            .filter(|t| t.overridden)
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(overridden, vec!["default_js_file"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_source() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("default_js_file.hbs"), "// custom")?;
+
+        assert_eq!(
+            template_source(temp_dir.path(), "default_js_file")?,
+            "// custom"
+        );
+        assert!(template_source(temp_dir.path(), "default_cargo_toml")?.starts_with("[package]"));
+        assert!(matches!(
+            template_source(temp_dir.path(), "missing"),
+            Err(ScaffError::TemplateNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_rust_struct_fields_and_methods() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let mut pattern = create_test_pattern();
+        let file = &mut pattern.files[0];
+        file.structs = vec!["Server".to_string(), "Marker".to_string()];
+        file.implementations = vec!["Server".to_string()];
+        file.functions = vec!["new".to_string(), "start".to_string()];
+        file.struct_fields = HashMap::from([
+            (
+                "Server".to_string(),
+                vec!["host".to_string(), "port".to_string()],
+            ),
+            ("Marker".to_string(), Vec::new()),
+        ]);
+        file.impl_methods = HashMap::from([(
+            "Server".to_string(),
+            vec!["new".to_string(), "start".to_string()],
+        )]);
+
+        let generator = CodeGenerator::from_templates_dir(&temp_dir.path().join("templates"))?;
+        generator.generate_files(&pattern, temp_dir.path())?;
+        let content = fs::read_to_string(temp_dir.path().join("src/main.rs"))?;
+
+        assert!(content.contains("pub struct Server {\n    pub host: (),"));
+        assert!(content.contains("    pub port: (),"));
+        assert!(content.contains("// TODO: Add fields for Marker"));
+        assert!(content.contains("            host: (),\n            port: (),"));
+        assert!(content.contains("    pub fn start(&self) {"));
+        // The captured `new` isn't stubbed a second time next to the generated one
+        assert_eq!(content.matches("pub fn new() -> Self").count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_struct_name_still_renders_with_this() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let templates_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&templates_dir)?;
+        fs::write(
+            templates_dir.join("rust_file.hbs"),
+            "{{#each structs}}struct {{this}};{{/each}}",
+        )?;
+
+        let pattern = create_test_pattern();
+        let generator = CodeGenerator::from_templates_dir(&templates_dir)?;
+        generator.generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("src/main.rs"))?,
+            "struct TestStruct;"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unqualified_names() {
+        let names: Vec<String> = [
+            "Config",
+            "api::Config",
+            "db::v2::Pool",
+            "Wrapper<std::io::Error>",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+        assert_eq!(
+            unqualified_names(&names),
+            vec!["Config", "Pool", "Wrapper<std::io::Error>"]
+        );
+    }
+
     #[test]
     fn test_generate_js_file() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
         let generator = CodeGenerator::new()?;
         let pattern = create_test_js_pattern();
-        let file_pattern = &pattern.files[0];
 
-        generator.generate_js_file(file_pattern, temp_dir.path(), &pattern)?;
+        generator.generate_files(&pattern, temp_dir.path())?;
 
         let generated_file = temp_dir.path().join("src/index.js");
         assert!(generated_file.exists());
@@ -601,6 +1898,125 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_require_template_errors_when_missing() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let pattern = create_test_pattern();
+        let generator = CodeGenerator::new()?
+            .with_template(Some("missing_template".to_string()))
+            .with_require_template(true);
+
+        let result = generator.generate_files(&pattern, temp_dir.path());
+
+        let err = result.expect_err("generation should fail without the named template");
+        assert!(
+            matches!(&err, ScaffError::RequiredTemplateNotFound(name) if name == "missing_template")
+        );
+        assert!(err.to_string().contains("missing_template"));
+        assert!(!temp_dir.path().join("src/main.rs").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_with_builtin_default_templates() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let generator = CodeGenerator::from_templates_dir(&temp_dir.path().join("templates"))?;
+
+        let pattern = create_test_pattern();
+        generator.generate_files(&pattern, temp_dir.path())?;
+        let content = fs::read_to_string(temp_dir.path().join("src/main.rs"))?;
+        assert!(content.contains("pub struct TestStruct"));
+        assert!(content.contains("pub fn test_function()"));
+
+        let js_pattern = create_test_js_pattern();
+        generator.generate_files(&js_pattern, temp_dir.path())?;
+        let content = fs::read_to_string(temp_dir.path().join("src/index.js"))?;
+        assert!(content.contains("class TestClass"));
+        assert!(content.contains("function testFunction()"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_writes_nothing() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let generator = CodeGenerator::new()?.with_dry_run(true, true);
+        let pattern = create_test_pattern();
+
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]")?;
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.new_files, vec![temp_dir.path().join("src/main.rs")]);
+        assert!(summary.existing_files.is_empty());
+        assert!(!temp_dir.path().join("src").exists());
+
+        let manifest =
+            generator.write_planned(&generator.plan_cargo_toml(&pattern, temp_dir.path())?)?;
+        assert_eq!(manifest.status, FileStatus::Skipped);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml"))?,
+            "[package]"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_files_skipped_unless_forced() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let pattern = create_test_pattern();
+        let main_rs = temp_dir.path().join("src/main.rs");
+
+        let generator = CodeGenerator::new()?;
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.new_files.len(), 2);
+
+        fs::write(&main_rs, "// hand-written")?;
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert!(summary.new_files.is_empty());
+        assert_eq!(summary.skipped_files.len(), 2);
+        assert_eq!(fs::read_to_string(&main_rs)?, "// hand-written");
+
+        let cargo_toml = temp_dir.path().join("Cargo.toml");
+        fs::write(&cargo_toml, "[dependencies]\nserde = \"1\"\n")?;
+        let summary = CodeGenerator::new()?
+            .with_force(true)
+            .generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.existing_files, vec![main_rs.clone()]);
+        assert_eq!(summary.skipped_files, vec![cargo_toml.clone()]);
+        assert!(fs::read_to_string(&main_rs)?.contains("TestStruct"));
+        // The manifest is never replaced, even when forced
+        assert_eq!(
+            fs::read_to_string(&cargo_toml)?,
+            "[dependencies]\nserde = \"1\"\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_reports_changed_files_without_writing() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let pattern = create_test_pattern();
+        let main_rs = temp_dir.path().join("src/main.rs");
+
+        CodeGenerator::new()?.generate_files(&pattern, temp_dir.path())?;
+        fs::write(&main_rs, "// hand-written")?;
+
+        let summary = CodeGenerator::new()?
+            .with_diff(true)
+            .generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.differing_files, vec![main_rs.clone()]);
+        assert!(summary.unchanged_files.is_empty());
+        assert_eq!(
+            summary.skipped_files,
+            vec![temp_dir.path().join("Cargo.toml")]
+        );
+        assert_eq!(fs::read_to_string(&main_rs)?, "// hand-written");
+
+        Ok(())
+    }
+
     #[test]
     fn test_generate_cargo_toml() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
@@ -609,7 +2025,9 @@ mod tests {
         // Test might fail if generator can't be created due to missing templates
         match CodeGenerator::new() {
             Ok(generator) => {
-                match generator.generate_cargo_toml(&pattern, temp_dir.path()) {
+                match generator
+                    .write_planned(&generator.plan_cargo_toml(&pattern, temp_dir.path())?)
+                {
                     Ok(_) => {
                         let cargo_file = temp_dir.path().join("Cargo.toml");
                         assert!(cargo_file.exists());
@@ -640,7 +2058,7 @@ mod tests {
         let generator = CodeGenerator::new()?;
         let pattern = create_test_js_pattern();
 
-        generator.generate_package_json(&pattern, temp_dir.path())?;
+        generator.write_planned(&generator.plan_package_json(&pattern, temp_dir.path())?)?;
 
         let package_file = temp_dir.path().join("package.json");
         assert!(package_file.exists());
@@ -662,7 +2080,7 @@ mod tests {
         // Test might fail if generator can't be created due to missing templates
         match CodeGenerator::new() {
             Ok(generator) => {
-                let result = generator.generate_rust_files(&pattern, temp_dir.path());
+                let result = generator.generate_files(&pattern, temp_dir.path());
                 // Test might fail due to missing handlebars templates, which is acceptable
                 match result {
                     Ok(_) => {
@@ -692,7 +2110,7 @@ mod tests {
         let generator = CodeGenerator::new()?;
         let pattern = create_test_js_pattern();
 
-        generator.generate_js_files(&pattern, temp_dir.path())?;
+        generator.generate_files(&pattern, temp_dir.path())?;
 
         // Check that the js file was generated
         let generated_file = temp_dir.path().join("src/index.js");
@@ -706,9 +2124,157 @@ mod tests {
     }
 
     #[test]
-    fn test_load_scaff_pattern_missing_file() {
-        let generator = CodeGenerator::new().unwrap();
-        let result = generator.load_scaff_pattern("nonexistent_pattern");
+    fn test_generate_python_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let generator = CodeGenerator::new()?;
+        let mut pattern = create_test_pattern();
+        pattern.language = "Python".to_string();
+        pattern.files = vec![FilePattern {
+            path: "app/models.py".to_string(),
+            extension: "py".to_string(),
+            classes: vec!["User".to_string()],
+            functions: vec!["load_user".to_string()],
+            ..Default::default()
+        }];
+
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.new_files.len(), 2);
+
+        let content = fs::read_to_string(temp_dir.path().join("app/models.py"))?;
+        assert!(content.contains("class User:"));
+        assert!(content.contains("def load_user():"));
+        assert!(content.contains(r#""""TODO: Implement load_user.""""#));
+
+        let pyproject = fs::read_to_string(temp_dir.path().join("pyproject.toml"))?;
+        assert!(pyproject.contains("name = \"test_pattern\""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_only_matching_files() -> Result<(), Box<dyn std::error::Error>> {
+        let rust_file = |path: &str| FilePattern {
+            path: path.to_string(),
+            extension: "rs".to_string(),
+            functions: vec!["run".to_string()],
+            ..Default::default()
+        };
+        let mut pattern = create_test_pattern();
+        pattern.files = vec![
+            rust_file("./src/main.rs"),
+            rust_file("./src/services/user.rs"),
+            rust_file("./src/services/billing/invoice.rs"),
+            rust_file("./src/models/user.rs"),
+        ];
+
+        let temp_dir = TempDir::new()?;
+        let generator = CodeGenerator::new()?.with_only(crate::scanner::build_glob_set(&[
+            "src/services/**".to_string(),
+        ])?);
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+
+        // Both files, the modules declaring them and Cargo.toml
+        assert_eq!(summary.new_files.len(), 6);
+        assert!(temp_dir.path().join("src/services/user.rs").exists());
+        assert!(
+            temp_dir
+                .path()
+                .join("src/services/billing/invoice.rs")
+                .exists()
+        );
+        assert!(!temp_dir.path().join("src/main.rs").exists());
+        assert!(!temp_dir.path().join("src/models/user.rs").exists());
+        assert!(temp_dir.path().join("Cargo.toml").exists());
+
+        // No matching file means no manifest either
+        let temp_dir = TempDir::new()?;
+        let generator = CodeGenerator::new()?
+            .with_only(crate::scanner::build_glob_set(&["docs/**".to_string()])?);
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert!(summary.new_files.is_empty());
+        assert!(!temp_dir.path().join("Cargo.toml").exists());
+
+        Ok(())
+    }
+
+    // Records and marks the .rs files it's run on
+    struct StubFormatter {
+        available: bool,
+        calls: std::rc::Rc<std::cell::RefCell<Vec<PathBuf>>>,
+    }
+
+    impl PostProcessor for StubFormatter {
+        fn name(&self) -> &str {
+            "stubfmt"
+        }
+
+        fn applies_to(&self, path: &Path) -> bool {
+            path.extension().is_some_and(|ext| ext == "rs")
+        }
+
+        fn is_available(&self) -> bool {
+            self.available
+        }
+
+        fn process(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+            // Like rustfmt, which needs the crate's edition, this relies on the manifest
+            if !path.ancestors().any(|dir| dir.join("Cargo.toml").is_file()) {
+                return Err("ran before Cargo.toml was written".into());
+            }
+            let content = fs::read_to_string(path)?;
+            fs::write(path, format!("// formatted\n{}", content))?;
+            self.calls.borrow_mut().push(path.to_path_buf());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_post_processors_run_on_written_files() -> Result<(), Box<dyn std::error::Error>> {
+        let mut pattern = create_test_pattern();
+        pattern.files.retain(|file| file.extension == "rs");
+
+        let temp_dir = TempDir::new()?;
+        let calls = std::rc::Rc::default();
+        let generator = CodeGenerator::new()?
+            .with_module_declarations(false)
+            .with_post_processors(vec![Box::new(StubFormatter {
+                available: true,
+                calls: std::rc::Rc::clone(&calls),
+            })]);
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+
+        let mut expected: Vec<PathBuf> = summary
+            .new_files
+            .iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .cloned()
+            .collect();
+        assert!(!expected.is_empty());
+        let mut formatted = calls.borrow().clone();
+        formatted.sort();
+        expected.sort();
+        assert_eq!(formatted, expected);
+        assert!(fs::read_to_string(&expected[0])?.starts_with("// formatted"));
+        assert!(!fs::read_to_string(temp_dir.path().join("Cargo.toml"))?.contains("formatted"));
+
+        // A formatter that isn't installed is skipped and generation still succeeds
+        let temp_dir = TempDir::new()?;
+        let calls = std::rc::Rc::default();
+        let generator = CodeGenerator::new()?.with_post_processors(vec![Box::new(StubFormatter {
+            available: false,
+            calls: std::rc::Rc::clone(&calls),
+        })]);
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert!(!summary.new_files.is_empty());
+        assert!(calls.borrow().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_scaff_pattern_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = ScaffDirectory::at(temp_dir.path()).find_pattern("nonexistent_pattern");
         assert!(result.is_err());
     }
 
@@ -800,6 +2366,63 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_translate_rust_pattern_to_js() {
+        let mut pattern = create_test_pattern();
+        pattern.files.push(FilePattern {
+            path: "config/settings.json".to_string(),
+            extension: "json".to_string(),
+            ..Default::default()
+        });
+
+        let translated = translate_pattern(&pattern, TargetLanguage::Javascript);
+        assert_eq!(translated.language, "JavaScript");
+        assert_eq!(translated.files.len(), 1);
+
+        let file = &translated.files[0];
+        assert_eq!(file.path, "src/main.js");
+        assert_eq!(file.extension, "js");
+        assert_eq!(file.classes, vec!["TestStruct"]);
+        assert!(file.structs.is_empty());
+        assert_eq!(file.functions, vec!["main", "test_function"]);
+
+        let back = translate_pattern(&translated, TargetLanguage::Rust);
+        assert_eq!(back.files[0].path, "src/main.rs");
+        assert_eq!(back.files[0].structs, vec!["TestStruct"]);
+    }
+
+    #[test]
+    fn test_is_source_language() {
+        let pattern = create_test_pattern();
+        assert!(is_source_language(&pattern, TargetLanguage::Rust));
+        assert!(!is_source_language(&pattern, TargetLanguage::Javascript));
+
+        let combined = CodePattern {
+            language: scanner::JS_TS_DISPLAY_NAME.to_string(),
+            ..pattern
+        };
+        assert!(is_source_language(&combined, TargetLanguage::Javascript));
+        assert!(is_source_language(&combined, TargetLanguage::Typescript));
+        assert!(!is_source_language(&combined, TargetLanguage::Python));
+    }
+
+    #[test]
+    fn test_generate_js_from_rust_scaff() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let generator = CodeGenerator::new()?;
+        let pattern = translate_pattern(&create_test_pattern(), TargetLanguage::Javascript);
+
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.new_files.len(), 2);
+
+        let content = fs::read_to_string(temp_dir.path().join("src/main.js"))?;
+        assert!(content.contains("class TestStruct"));
+        assert!(temp_dir.path().join("package.json").exists());
+        assert!(!temp_dir.path().join("Cargo.toml").exists());
+
+        Ok(())
+    }
+
     #[test]
     fn test_load_templates_from_directory() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
@@ -818,6 +2441,241 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_split_front_matter() {
+        let (front_matter, body) =
+            split_front_matter("---\npath: \"lib/{{file_name}}.rs\"\n---\nfn main() {}\n");
+        assert_eq!(
+            front_matter,
+            Some(TemplateFrontMatter {
+                path: Some("lib/{{file_name}}.rs".to_string())
+            })
+        );
+        assert_eq!(body, "fn main() {}\n");
+
+        // No closing delimiter: not front-matter
+        let content = "---\npath: x\nbody";
+        assert_eq!(split_front_matter(content), (None, content));
+        assert_eq!(split_front_matter("plain"), (None, "plain"));
+    }
+
+    #[test]
+    fn test_planned_paths_match_generated_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let mut pattern = create_test_pattern();
+        pattern.files.push(FilePattern {
+            path: "src/services/user.rs".to_string(),
+            extension: "rs".to_string(),
+            ..Default::default()
+        });
+        let scaffs = ScaffDirectory::at(temp_dir.path().join("scaffs"));
+        scaffs.save_pattern(&pattern)?;
+        let output_dir = temp_dir.path().join("generated");
+        let output = output_dir.to_str().unwrap();
+
+        let generator = CodeGenerator::new()?.with_scaff_directory(scaffs.clone());
+        let mut expected: Vec<PathBuf> = pattern
+            .files
+            .iter()
+            .map(|f| output_dir.join(&f.path))
+            .collect();
+        expected.push(output_dir.join("src/services/mod.rs"));
+        expected.push(output_dir.join("Cargo.toml"));
+        assert_eq!(generator.planned_paths("test_pattern", output)?, expected);
+        assert!(!output_dir.exists());
+
+        let summary = generator.generate_from_scaff("test_pattern", output)?;
+        let generated: Vec<PathBuf> = summary.files.into_iter().map(|f| f.path).collect();
+        assert_eq!(generated, expected);
+
+        // Files generation would skip aren't listed: existing ones unless forced, and
+        // always the kept manifest
+        assert!(generator.planned_paths("test_pattern", output)?.is_empty());
+        let generator = CodeGenerator::new()?
+            .with_scaff_directory(scaffs.clone())
+            .with_force(true);
+        assert_eq!(
+            generator.planned_paths("test_pattern", output)?,
+            expected[..2].to_vec()
+        );
+
+        let output_dir = temp_dir.path().join("only");
+        let generator = CodeGenerator::new()?
+            .with_scaff_directory(scaffs)
+            .with_only(crate::scanner::build_glob_set(&[
+                "src/services/**".to_string()
+            ])?)
+            .with_manifest(false);
+        assert_eq!(
+            generator.planned_paths("test_pattern", output_dir.to_str().unwrap())?,
+            vec![
+                output_dir.join("src/services/user.rs"),
+                output_dir.join("src/lib.rs"),
+                output_dir.join("src/services/mod.rs"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generated_modules_are_declared() -> Result<(), Box<dyn std::error::Error>> {
+        let rust_file = |path: &str| FilePattern {
+            path: path.to_string(),
+            extension: "rs".to_string(),
+            functions: vec!["run".to_string()],
+            ..Default::default()
+        };
+        let mut pattern = create_test_pattern();
+        pattern.files = vec![
+            rust_file("src/services/auth/mod.rs"),
+            rust_file("src/services/payments/mod.rs"),
+            rust_file("src/models/user.rs"),
+            rust_file("build.rs"),
+        ];
+
+        let temp_dir = TempDir::new()?;
+        let src = temp_dir.path().join("src");
+        fs::create_dir_all(&src)?;
+        fs::write(src.join("main.rs"), "mod models;\n\nfn main() {}")?;
+
+        // Dry runs cover the declarations too, without writing them
+        let summary = CodeGenerator::new()?
+            .with_dry_run(true, false)
+            .generate_files(&pattern, temp_dir.path())?;
+        assert!(summary.skipped_files.contains(&src.join("main.rs")));
+        assert!(summary.new_files.contains(&src.join("services/mod.rs")));
+        assert!(!src.join("services").exists());
+
+        // Without --force, the existing crate root is left alone like any existing file
+        let summary = CodeGenerator::new()?.generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.skipped_files, vec![src.join("main.rs")]);
+        assert!(summary.new_files.contains(&src.join("services/mod.rs")));
+        assert_eq!(
+            fs::read_to_string(src.join("main.rs"))?,
+            "mod models;\n\nfn main() {}"
+        );
+
+        let generator = CodeGenerator::new()?.with_force(true);
+        let summary = generator.generate_files(&pattern, temp_dir.path())?;
+        assert!(summary.existing_files.contains(&src.join("main.rs")));
+
+        let read = |path: &str| fs::read_to_string(src.join(path));
+        assert_eq!(
+            read("main.rs")?,
+            "mod models;\n\nfn main() {}\n\npub mod services;\n"
+        );
+        assert_eq!(
+            read("services/mod.rs")?,
+            "pub mod auth;\npub mod payments;\n"
+        );
+        assert_eq!(read("models/mod.rs")?, "pub mod user;\n");
+        assert!(!src.join("lib.rs").exists());
+
+        // Generating again doesn't repeat any declaration
+        generator.generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(
+            read("services/mod.rs")?,
+            "pub mod auth;\npub mod payments;\n"
+        );
+        assert_eq!(read("main.rs")?.matches("mod services;").count(), 1);
+
+        // And so do diffs
+        fs::write(src.join("main.rs"), "fn main() {}")?;
+        let summary = CodeGenerator::new()?
+            .with_diff(true)
+            .generate_files(&pattern, temp_dir.path())?;
+        assert_eq!(summary.differing_files, vec![src.join("main.rs")]);
+        assert_eq!(read("main.rs")?, "fn main() {}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_declares_module() {
+        let source = "// mod commented;\npub(crate) mod api;\nmod inline {\n}\n";
+        assert!(declares_module(source, "api"));
+        assert!(declares_module(source, "inline"));
+        assert!(!declares_module(source, "commented"));
+        assert!(!declares_module(source, "ap"));
+    }
+
+    #[test]
+    fn test_front_matter_relocates_output() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let templates_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&templates_dir)?;
+        fs::write(
+            templates_dir.join("rust_file.hbs"),
+            "---\npath: components/{{snake_case file_name}}/mod.rs\n---\n// {{file_name}}\n",
+        )?;
+
+        let mut pattern = create_test_pattern();
+        pattern.files[0].path = "src/Button.rs".to_string();
+        let output_dir = temp_dir.path().join("out");
+        let generator = CodeGenerator::from_templates_dir(&templates_dir)?;
+        let summary = generator.generate_files(&pattern, &output_dir)?;
+
+        let relocated = output_dir.join("components/button/mod.rs");
+        assert_eq!(fs::read_to_string(&relocated)?, "// Button\n");
+        assert_eq!(generator.plan(&pattern, &output_dir)?[0].path, relocated);
+        assert!(summary.new_files.contains(&relocated));
+        assert!(!output_dir.join("src/Button.rs").exists());
+
+        // Paths may not leave the output directory
+        fs::write(
+            templates_dir.join("rust_file.hbs"),
+            "---\npath: ../{{file_name}}.rs\n---\n",
+        )?;
+        let generator = CodeGenerator::from_templates_dir(&templates_dir)?;
+        let err = generator
+            .generate_files(&pattern, &output_dir)
+            .expect_err("escaping path should be rejected");
+        assert!(matches!(err, ScaffError::InvalidOutputPath { .. }));
+        assert!(err.to_string().contains("inside the output directory"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_vars() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let templates_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&templates_dir)?;
+        fs::write(
+            templates_dir.join("rust_file.hbs"),
+            "// {{vars.author}} ({{vars.license}})\n",
+        )?;
+
+        let mut pattern = create_test_pattern();
+        pattern.variables = HashMap::from([
+            ("author".to_string(), "Scaff Team".to_string()),
+            ("license".to_string(), "MIT".to_string()),
+        ]);
+        let output_dir = temp_dir.path().join("out");
+        let generator = CodeGenerator::from_templates_dir(&templates_dir)?;
+        generator.generate_files(&pattern, &output_dir)?;
+        assert_eq!(
+            fs::read_to_string(output_dir.join("src/main.rs"))?,
+            "// Scaff Team (MIT)\n"
+        );
+
+        // Variables passed to the generator win over the scaff's defaults
+        let generator = CodeGenerator::from_templates_dir(&templates_dir)?
+            .with_force(true)
+            .with_vars(HashMap::from([(
+                "author".to_string(),
+                "Jane Doe".to_string(),
+            )]));
+        generator.generate_files(&pattern, &output_dir)?;
+        assert_eq!(
+            fs::read_to_string(output_dir.join("src/main.rs"))?,
+            "// Jane Doe (MIT)\n"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_load_templates_from_directory_with_invalid_template()
     -> Result<(), Box<dyn std::error::Error>> {
@@ -0,0 +1,55 @@
+use log::info;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// List files under `base_dir` changed relative to `git_ref`, including untracked
+/// files. Paths are joined onto `base_dir` to match the paths produced by a
+/// directory scan.
+pub fn changed_files_since(
+    base_dir: &Path,
+    git_ref: &str,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    info!(
+        "Listing files in {} changed since {}",
+        base_dir.display(),
+        git_ref
+    );
+
+    let mut files = run_git(base_dir, &["diff", "--name-only", "--relative", git_ref])?;
+    files.extend(run_git(
+        base_dir,
+        &["ls-files", "--others", "--exclude-standard"],
+    )?);
+    files.sort();
+    files.dedup();
+
+    Ok(files.into_iter().map(|file| base_dir.join(file)).collect())
+}
+
+/// Whether `dir` is inside a git work tree; false too when git isn't installed
+pub fn is_repository(dir: &Path) -> bool {
+    run_git(dir, &["rev-parse", "--is-inside-work-tree"])
+        .is_ok_and(|output| output.first().is_some_and(|line| line == "true"))
+}
+
+fn run_git(base_dir: &Path, args: &[&str]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(base_dir)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect())
+}
@@ -0,0 +1,43 @@
+//! Capture a codebase's architecture as a reusable pattern (a scaff), generate code
+//! from it and validate code against it. The `scaff` binary is a thin wrapper around
+//! these modules.
+//!
+//! ```
+//! use scaff::pattern::create_pattern_from_scan;
+//! use scaff::scanner;
+//! use scaff::validator::ArchitectureValidator;
+//!
+//! let dir = tempfile::tempdir()?;
+//! std::fs::write(dir.path().join("lib.rs"), "pub struct Config;")?;
+//!
+//! let files = scanner::scan_language_files_in_dir(dir.path().to_str().unwrap(), "rust");
+//! let pattern = create_pattern_from_scan(files, "api".to_string(), "Rust".to_string());
+//! assert_eq!(pattern.files[0].path, "lib.rs");
+//! assert_eq!(pattern.files[0].structs, ["Config"]);
+//!
+//! let result = ArchitectureValidator::new()
+//!     .with_strict(true)
+//!     .validate_pattern(pattern, dir.path())?;
+//! assert!(result.is_valid);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod cache;
+#[doc(hidden)]
+pub mod cli;
+mod config;
+mod diff;
+mod doctor;
+pub mod error;
+pub mod generator;
+mod git;
+pub mod pattern;
+pub mod postprocess;
+mod report;
+pub mod scanner;
+mod stats;
+mod style;
+mod symbols;
+pub mod validator;
+#[cfg(feature = "watch")]
+mod watch;
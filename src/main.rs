@@ -1,10 +1,12 @@
-mod cli;
-mod generator;
-mod pattern;
-mod scanner;
-mod validator;
+use clap::Parser;
+use scaff::cli;
 
 fn main() {
-    env_logger::init();
-    cli::run();
+    let cli = cli::Cli::parse();
+    // RUST_LOG, when set, overrides the level from the command line
+    env_logger::Builder::new()
+        .filter_level(cli.log_level())
+        .parse_default_env()
+        .init();
+    cli::run(cli);
 }
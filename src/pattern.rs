@@ -1,69 +1,601 @@
+use crate::config::{self, ScaffConfig};
+use crate::error::ScaffError;
+use clap::ValueEnum;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use log::{info, warn};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Version of the serialized scaff format written by this build.
+///
+/// - 1: function names only
+/// - 2: adds per-function `signatures`
+/// - 3: Rust items in inline modules are qualified with the module path, e.g. `tests::setup`
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// First schema version qualifying items of inline Rust modules
+const QUALIFIED_MODULES_VERSION: u32 = 3;
+
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// Lockfile held in the scaffs directory while a scaff is written
+const LOCK_FILE: &str = ".scaff.lock";
+/// How long to wait for another `scaff` process to release the lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// A lockfile this old was left behind by a crashed run and is taken over
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+/// Order of `scaff list` output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ListSort {
+    /// Alphabetically by name
+    #[default]
+    Name,
+    /// Oldest first, by `created_at`
+    Created,
+    /// Fewest files first
+    Files,
+}
+
+/// Which saved scaffs `ScaffDirectory::list_patterns` returns, and in what order
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    pub sort: ListSort,
+    /// Only scaffs carrying this tag, ignoring case
+    pub tag: Option<String>,
+    /// Only scaffs of this language, ignoring case
+    pub language: Option<String>,
+}
+
+impl ListOptions {
+    fn apply(&self, patterns: Vec<CodePattern>) -> Vec<CodePattern> {
+        let mut patterns: Vec<CodePattern> = patterns
+            .into_iter()
+            .filter(|pattern| self.tag.as_deref().is_none_or(|tag| pattern.has_tag(tag)))
+            .filter(|pattern| {
+                self.language
+                    .as_deref()
+                    .is_none_or(|language| pattern.language.eq_ignore_ascii_case(language))
+            })
+            .collect();
+
+        match self.sort {
+            ListSort::Name => patterns.sort_by(|a, b| a.name.cmp(&b.name)),
+            // Unparseable timestamps sort last
+            ListSort::Created => patterns.sort_by_key(|pattern| {
+                let created = chrono::DateTime::parse_from_rfc3339(&pattern.created_at).ok();
+                (created.is_none(), created)
+            }),
+            ListSort::Files => patterns.sort_by_key(|pattern| pattern.files.len()),
+        }
+        patterns
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CodePattern {
+    /// Absent in files written before versioning, which are treated as version 1
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub name: String,
     pub description: String,
     pub language: String,
     pub files: Vec<FilePattern>,
+    /// Directories the codebase must contain, e.g. `src/services`
+    #[serde(default)]
+    pub directories: Vec<String>,
+    /// `--include` globs the scaff was saved with, re-applied when validating
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// `--exclude` globs the scaff was saved with, re-applied when validating
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Free-form labels for organizing scaffs, e.g. `backend`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Default values for `{{vars.<key>}}` in templates; `generate --var` overrides them
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
     pub created_at: String,
+    /// When `scaff update` last re-scanned the scaff's files
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// Set when migrating from before schema version 3: items of inline Rust modules
+    /// are recorded by their bare name, so validation compares names unqualified
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unqualified_modules: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Default for CodePattern {
+    fn default() -> Self {
+        CodePattern {
+            schema_version: SCHEMA_VERSION,
+            name: String::new(),
+            description: String::new(),
+            language: String::new(),
+            files: Vec::new(),
+            directories: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            tags: Vec::new(),
+            variables: HashMap::new(),
+            created_at: String::new(),
+            updated_at: None,
+            unqualified_modules: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct FilePattern {
     pub path: String,
+    /// Treat `path` as a glob (e.g. `src/services/*/mod.rs`) that any number of files
+    /// may match during validation, each compared against this file's items
+    #[serde(default)]
+    pub glob: bool,
     pub extension: String,
+    /// Display name of the language the file was scanned as, e.g. `Rust`
+    #[serde(default)]
+    pub language: Option<String>,
     pub classes: Vec<String>,
     pub functions: Vec<String>,
     pub structs: Vec<String>,
     pub implementations: Vec<String>,
+    /// Rust enums; absent in scaffs saved before they were extracted
+    #[serde(default)]
+    pub enums: Vec<String>,
+    /// Rust traits; absent in scaffs saved before they were extracted
+    #[serde(default)]
+    pub traits: Vec<String>,
+    /// Methods defined in Rust `impl` blocks, keyed by the impl's target type
+    #[serde(default)]
+    pub impl_methods: HashMap<String, Vec<String>>,
+    /// Named fields of Rust structs, keyed by struct name; unit and tuple structs map
+    /// to an empty list
+    #[serde(default)]
+    pub struct_fields: HashMap<String, Vec<String>>,
+    /// Hex SHA-256 of the file content at scan time, see `scanner::content_hash`
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Hex SHA-256 of the file's sorted (item type, name) pairs, see `scanner::structure_hash`
+    #[serde(default)]
+    pub structure_hash: Option<String>,
+    /// Parameter lists and return types of the functions above, where the language exposes them
+    #[serde(default)]
+    pub signatures: Vec<FunctionSignature>,
+    /// Modifiers recorded per item name, e.g. `async` or `generator` for JS class methods
+    #[serde(default)]
+    pub metadata: HashMap<String, Vec<String>>,
+    /// Attributes preceding Rust items and Python/TypeScript decorators, keyed by item
+    /// name, e.g. `derive(Debug)` or `dataclass`
+    #[serde(default)]
+    pub attributes: HashMap<String, Vec<String>>,
+    /// The source didn't parse cleanly, so the items above may be incomplete or wrong
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parse_errors: bool,
+    /// Items that are recommended rather than required. Validation only suggests
+    /// adding them when they're missing, and `--count-match` leaves them out.
+    #[serde(default)]
+    pub optional: Vec<OptionalItem>,
+}
+
+/// An item of a file that a scaff recommends rather than requires
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct OptionalItem {
+    /// Category as named by `comparable_items`, e.g. `function`
+    pub item_type: String,
+    /// Name as listed in that category, e.g. `run` or `Config::new`
+    pub name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FunctionSignature {
+    pub name: String,
+    pub params: Vec<String>,
+    pub return_type: Option<String>,
+}
+
+impl FilePattern {
+    /// Whether the `item_type` item `name` is one of the file's optional items
+    pub fn is_optional(&self, item_type: &str, name: &str) -> bool {
+        self.optional
+            .iter()
+            .any(|item| item.item_type == item_type && item.name == name)
+    }
+
+    /// Total number of extracted items across all categories. Impl methods are
+    /// already listed in `functions`, so they aren't counted twice.
+    pub fn item_count(&self) -> usize {
+        self.classes.len()
+            + self.functions.len()
+            + self.structs.len()
+            + self.implementations.len()
+            + self.enums.len()
+            + self.traits.len()
+    }
+
+    /// Impl methods as sorted `Type::method` paths
+    pub fn qualified_impl_methods(&self) -> Vec<String> {
+        let mut methods: Vec<String> = self
+            .impl_methods
+            .iter()
+            .flat_map(|(type_name, methods)| {
+                methods
+                    .iter()
+                    .map(move |method| format!("{}::{}", type_name, method))
+            })
+            .collect();
+        methods.sort();
+        methods
+    }
+
+    /// Struct fields as sorted `Type::field` paths
+    pub fn qualified_struct_fields(&self) -> Vec<String> {
+        let mut fields: Vec<String> = self
+            .struct_fields
+            .iter()
+            .flat_map(|(type_name, fields)| {
+                fields
+                    .iter()
+                    .map(move |field| format!("{}::{}", type_name, field))
+            })
+            .collect();
+        fields.sort();
+        fields
+    }
+
+    /// Reduce Rust names qualified with an inline module path, like `api::Config`, to
+    /// their last segment as schemas before version 3 recorded them, merging the
+    /// duplicates that leaves. The structure hash no longer applies and is dropped.
+    pub fn unqualify_modules(&mut self) {
+        if self.extension != "rs" {
+            return;
+        }
+
+        for names in [
+            &mut self.structs,
+            &mut self.functions,
+            &mut self.implementations,
+            &mut self.enums,
+            &mut self.traits,
+        ] {
+            let mut seen = HashSet::new();
+            names.retain_mut(|name| {
+                *name = unqualified(name).to_string();
+                seen.insert(name.clone())
+            });
+        }
+        for items in [
+            &mut self.impl_methods,
+            &mut self.struct_fields,
+            &mut self.metadata,
+            &mut self.attributes,
+        ] {
+            let mut merged: HashMap<String, Vec<String>> = HashMap::new();
+            for (name, values) in items.drain() {
+                let entry = merged.entry(unqualified(&name).to_string()).or_default();
+                for value in values {
+                    if !entry.contains(&value) {
+                        entry.push(value);
+                    }
+                }
+            }
+            *items = merged;
+        }
+        for signature in &mut self.signatures {
+            signature.name = unqualified(&signature.name).to_string();
+        }
+        self.structure_hash = None;
+    }
+}
+
+/// The last segment of a module-qualified Rust name, e.g. `Config` for `api::Config`.
+/// Paths inside generics, as in `Wrapper<std::io::Error>`, are kept.
+pub fn unqualified(name: &str) -> &str {
+    let generics = name.find('<').unwrap_or(name.len());
+    let start = name[..generics].rfind("::").map_or(0, |i| i + 2);
+    &name[start..]
+}
+
+/// The named item lists compared between two versions of a file, in display order.
+/// Impl methods and struct fields are qualified as `Type::method` and `Type::field`.
+pub fn comparable_items(file: &FilePattern) -> [(&'static str, Vec<String>); 8] {
+    [
+        ("class", file.classes.clone()),
+        ("function", file.functions.clone()),
+        ("struct", file.structs.clone()),
+        ("implementation", file.implementations.clone()),
+        ("enum", file.enums.clone()),
+        ("trait", file.traits.clone()),
+        ("method", file.qualified_impl_methods()),
+        ("field", file.qualified_struct_fields()),
+    ]
+}
+
+impl std::fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.name, self.params.join(", "))?;
+        if let Some(return_type) = &self.return_type {
+            write!(f, " -> {}", return_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl CodePattern {
+    /// Upgrade a pattern loaded from an older schema to [`SCHEMA_VERSION`].
+    ///
+    /// Version 1 files only stored function names; their `signatures` deserialize
+    /// empty and the validator skips signature checks for functions without one.
+    /// Before version 3, items of inline Rust modules weren't qualified with the
+    /// module path, which can't be recovered without the source, so such patterns are
+    /// marked `unqualified_modules` until their files are re-scanned.
+    /// Patterns from a newer schema are left untouched.
+    pub fn migrate(&mut self) {
+        if self.schema_version < QUALIFIED_MODULES_VERSION {
+            self.unqualified_modules = true;
+        }
+        if self.schema_version < SCHEMA_VERSION {
+            self.schema_version = SCHEMA_VERSION;
+        }
+    }
+
+    /// Replace the scaff's files with a fresh scan, keeping its name, description, tags
+    /// and other metadata, and the optional items of files still present, and stamp
+    /// `updated_at`
+    pub fn update_files(&mut self, mut files: Vec<FilePattern>) {
+        for file in &mut files {
+            if let Some(previous) = self.files.iter().find(|f| f.path == file.path) {
+                file.optional = previous.optional.clone();
+            }
+        }
+        self.directories = directories_of(&files);
+        self.files = files;
+        self.unqualified_modules = false;
+        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    }
+
+    /// Whether the scaff carries `tag`, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    // Migrate a pattern just read from disk, logging what happened
+    fn upgrade_loaded(mut self) -> Self {
+        if self.schema_version < SCHEMA_VERSION {
+            info!(
+                "Migrating pattern '{}' from schema version {} to {}",
+                self.name, self.schema_version, SCHEMA_VERSION
+            );
+            self.migrate();
+        } else if self.schema_version > SCHEMA_VERSION {
+            warn!(
+                "Pattern '{}' uses schema version {}, newer than this scaff supports ({}); unknown fields are ignored",
+                self.name, self.schema_version, SCHEMA_VERSION
+            );
+        }
+        self
+    }
+}
+
+/// JSON Schema of a saved scaff file, printed by `scaff schema`
+pub fn pattern_schema() -> serde_json::Value {
+    schemars::schema_for!(CodePattern).to_value()
+}
+
+// Each place `content` breaks the pattern schema, as `/json/pointer: message`. None
+// when it isn't JSON at all or the schema accepts it, leaving serde's error to report.
+fn schema_errors(content: &str) -> Option<String> {
+    let instance: serde_json::Value = serde_json::from_str(content).ok()?;
+    let validator = jsonschema::validator_for(&pattern_schema()).ok()?;
+    let errors: Vec<String> = validator
+        .iter_errors(&instance)
+        .map(|e| {
+            let location = e.instance_path().to_string();
+            let location = if location.is_empty() { "/" } else { &location };
+            format!("{}: {}", location, e)
+        })
+        .collect();
+    (!errors.is_empty()).then(|| errors.join("; "))
+}
+
+/// Scaff files that loaded, and those that didn't with the reason, by path
+pub type PatternFileCheck = (Vec<(PathBuf, CodePattern)>, Vec<(PathBuf, String)>);
+
+/// The directory scaffs are saved to and loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ScaffDirectory {
-    pub patterns: Vec<CodePattern>,
+    dir: PathBuf,
+}
+
+impl Default for ScaffDirectory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ScaffDirectory {
+    /// The default location: `SCAFF_DIR` if set, else the config's `scaff_dir`, else
+    /// `scaffs/`, the latter two under the project root found upward from the current
+    /// directory
     pub fn new() -> Self {
-        ScaffDirectory {
-            patterns: Vec::new(),
+        if let Some(dir) = std::env::var_os("SCAFF_DIR").filter(|dir| !dir.is_empty()) {
+            return Self::at(dir);
+        }
+        let scaff_dir = ScaffConfig::load()
+            .scaff_dir
+            .unwrap_or_else(|| PathBuf::from("scaffs"));
+        Self::at(config::project_root().join(scaff_dir))
+    }
+
+    /// Scaffs stored in `dir`, e.g. one passed with `--scaffs-dir`
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        ScaffDirectory { dir: dir.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn save_pattern(&self, pattern: &CodePattern) -> Result<PathBuf, ScaffError> {
+        self.create()?;
+        ScaffsLock::acquire(&self.dir)?.save_pattern(pattern)
+    }
+
+    fn create(&self) -> Result<(), ScaffError> {
+        if !self.dir.exists() {
+            fs::create_dir_all(&self.dir)?;
+            info!("Created scaffs directory {}", self.dir.display());
         }
+        Ok(())
     }
 
-    pub fn save_pattern(&self, pattern: &CodePattern) -> Result<(), Box<dyn std::error::Error>> {
-        let scaffs_dir = Path::new("scaffs");
-        if !scaffs_dir.exists() {
-            fs::create_dir_all(scaffs_dir)?;
-            info!("Created scaffs directory");
+    /// Load a pattern like [`ScaffDirectory::find_pattern`] and keep the scaffs
+    /// directory locked until the returned lock is dropped, so a load-modify-save like
+    /// `scaff update` can't overwrite a change another run saved in between. Save the
+    /// result with [`ScaffsLock::save_pattern`].
+    pub fn find_pattern_locked(&self, name: &str) -> Result<(CodePattern, ScaffsLock), ScaffError> {
+        if !self.dir.is_dir() {
+            return Err(ScaffError::NotFound(name.to_string()));
         }
+        let lock = ScaffsLock::acquire(&self.dir)?;
+        let pattern = self.find_pattern(name)?;
+        Ok((pattern, lock))
+    }
 
-        let filename = format!("{}.json", pattern.name.replace(" ", "_").to_lowercase());
-        let file_path = scaffs_dir.join(&filename);
+    /// Write a single scaff to a self-contained file that can be shared and imported
+    /// elsewhere. Files ending in `.scaff` are gzip-compressed, anything else is JSON.
+    pub fn export_pattern(&self, name: &str, output: &Path) -> Result<(), ScaffError> {
+        let pattern = self.find_pattern(name)?;
+        let json_content = serde_json::to_string_pretty(&pattern)?;
 
-        let json_content = serde_json::to_string_pretty(pattern)?;
-        fs::write(&file_path, json_content)?;
+        if output.extension().and_then(|s| s.to_str()) == Some("scaff") {
+            let mut encoder = GzEncoder::new(fs::File::create(output)?, Compression::default());
+            encoder.write_all(json_content.as_bytes())?;
+            encoder.finish()?;
+        } else {
+            fs::write(output, json_content)?;
+        }
 
         info!(
-            "Saved pattern '{}' to {}",
+            "Exported pattern '{}' to {}",
             pattern.name,
-            file_path.display()
+            output.display()
         );
         Ok(())
     }
 
-    pub fn load_patterns() -> Result<Vec<CodePattern>, Box<dyn std::error::Error>> {
-        let scaffs_dir = Path::new("scaffs");
-        if !scaffs_dir.exists() {
+    /// Install a scaff from a file written by [`ScaffDirectory::export_pattern`],
+    /// refusing to replace an existing scaff of the same name unless `force` is set.
+    pub fn import_pattern(
+        &self,
+        file: &Path,
+        force: bool,
+    ) -> Result<(CodePattern, PathBuf), ScaffError> {
+        let pattern = read_exported_pattern(file)?;
+
+        if pattern.schema_version > SCHEMA_VERSION {
+            return Err(ScaffError::UnsupportedSchema {
+                path: file.to_path_buf(),
+                version: pattern.schema_version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+
+        self.create()?;
+        let lock = ScaffsLock::acquire(&self.dir)?;
+        let exists = self.find_pattern(&pattern.name).is_ok();
+        if exists && !force {
+            return Err(ScaffError::AlreadyExists(pattern.name));
+        }
+
+        let pattern = pattern.upgrade_loaded();
+        let path = lock.save_pattern(&pattern)?;
+        Ok((pattern, path))
+    }
+
+    pub fn load_patterns(&self) -> Result<Vec<CodePattern>, ScaffError> {
+        let patterns = self
+            .read_pattern_files()?
+            .into_iter()
+            .map(|(_, pattern)| pattern.upgrade_loaded())
+            .collect();
+
+        Ok(patterns)
+    }
+
+    /// Rewrite scaffs saved with an older schema in the current one, returning the
+    /// name and previous schema version of each pattern that was upgraded.
+    /// Only the named scaff is considered when `name` is given.
+    pub fn migrate_patterns(&self, name: Option<&str>) -> Result<Vec<(String, u32)>, ScaffError> {
+        let mut migrated = Vec::new();
+        let mut found = name.is_none();
+
+        let _lock = self
+            .dir
+            .is_dir()
+            .then(|| ScaffsLock::acquire(&self.dir))
+            .transpose()?;
+        for (path, mut pattern) in self.read_pattern_files()? {
+            if name.is_some_and(|name| !refers_to(name, &path, &pattern)) {
+                continue;
+            }
+            found = true;
+            if pattern.schema_version >= SCHEMA_VERSION {
+                continue;
+            }
+
+            let previous_version = pattern.schema_version;
+            pattern.migrate();
+            write_atomic(&path, &serde_json::to_string_pretty(&pattern)?)?;
+            info!(
+                "Migrated pattern '{}' in {} from schema version {} to {}",
+                pattern.name,
+                path.display(),
+                previous_version,
+                SCHEMA_VERSION
+            );
+            migrated.push((pattern.name, previous_version));
+        }
+
+        if !found {
+            return Err(ScaffError::NotFound(name.unwrap_or_default().to_string()));
+        }
+
+        Ok(migrated)
+    }
+
+    // Parse every scaff file as stored, skipping (with a warning) any that fail
+    fn read_pattern_files(&self) -> Result<Vec<(PathBuf, CodePattern)>, ScaffError> {
+        let (patterns, failures) = self.check_pattern_files()?;
+        for (path, reason) in failures {
+            warn!("Skipping {}: {}", path.display(), reason);
+        }
+        Ok(patterns)
+    }
+
+    /// Parse every scaff file as stored, returning the patterns that loaded and,
+    /// separately, each file that couldn't be read or parsed with the reason
+    pub fn check_pattern_files(&self) -> Result<PatternFileCheck, ScaffError> {
+        if !self.dir.exists() {
             info!("Scaffs directory doesn't exist, returning empty list");
-            return Ok(Vec::new());
+            return Ok((Vec::new(), Vec::new()));
         }
 
         let mut patterns = Vec::new();
-        let entries = fs::read_dir(scaffs_dir)?;
+        let mut failures = Vec::new();
+        let entries = fs::read_dir(&self.dir)?;
 
         for entry in entries {
             let entry = entry?;
@@ -74,55 +606,205 @@ impl ScaffDirectory {
                     Ok(content) => match serde_json::from_str::<CodePattern>(&content) {
                         Ok(pattern) => {
                             info!("Loaded pattern '{}' from {}", pattern.name, path.display());
-                            patterns.push(pattern);
+                            patterns.push((path, pattern));
                         }
                         Err(e) => {
-                            warn!("Failed to parse pattern from {}: {}", path.display(), e);
+                            let reason = schema_errors(&content).unwrap_or_else(|| e.to_string());
+                            failures.push((path, format!("failed to parse pattern: {}", reason)));
                         }
                     },
-                    Err(e) => {
-                        warn!("Failed to read file {}: {}", path.display(), e);
-                    }
+                    Err(e) => failures.push((path, format!("failed to read file: {}", e))),
                 }
             }
         }
 
-        Ok(patterns)
+        Ok((patterns, failures))
     }
 
-    pub fn list_patterns() -> Result<(), Box<dyn std::error::Error>> {
-        let patterns = Self::load_patterns()?;
+    /// Load a pattern by its internal name (`My Pattern`) or file name (`my_pattern`)
+    pub fn find_pattern(&self, name: &str) -> Result<CodePattern, ScaffError> {
+        let patterns = self.read_pattern_files()?;
+
+        // An exact internal name wins over a file name that happens to match
+        let position = patterns
+            .iter()
+            .position(|(_, pattern)| pattern.name == name)
+            .or_else(|| {
+                patterns
+                    .iter()
+                    .position(|(path, pattern)| refers_to(name, path, pattern))
+            });
 
-        if patterns.is_empty() {
-            println!("No scaffs found. Use 'scaff save <name>' to save patterns.");
-            return Ok(());
+        match position {
+            Some(index) => {
+                let (_, pattern) = patterns.into_iter().nth(index).unwrap();
+                Ok(pattern.upgrade_loaded())
+            }
+            None => Err(ScaffError::NotFound(name.to_string())),
         }
+    }
 
-        println!("\nAvailable Scaffs:");
-        println!("{:-<50}", "");
+    /// Use the given scaff name, or when omitted the config's `default_scaff`, or the
+    /// only saved scaff
+    pub fn resolve_scaff_name(&self, name: Option<String>) -> Result<String, ScaffError> {
+        if let Some(name) = name.or_else(|| ScaffConfig::load().default_scaff) {
+            return Ok(name);
+        }
 
-        for pattern in patterns {
-            println!("📋 {} ({})", pattern.name, pattern.language);
-            println!("   {}", pattern.description);
-            println!("   Files: {}", pattern.files.len());
+        let mut patterns = self.load_patterns()?;
+        match patterns.len() {
+            1 => Ok(patterns.remove(0).name),
+            0 => Err(ScaffError::NoScaffs),
+            n => Err(ScaffError::AmbiguousScaff(n)),
+        }
+    }
 
-            let total_items = pattern
-                .files
-                .iter()
-                .map(|f| {
-                    f.classes.len() + f.functions.len() + f.structs.len() + f.implementations.len()
-                })
-                .sum::<usize>();
+    /// Saved scaffs matching the filters in `options`, in its sort order
+    pub fn list_patterns(&self, options: &ListOptions) -> Result<Vec<CodePattern>, ScaffError> {
+        Ok(options.apply(self.load_patterns()?))
+    }
+}
 
-            println!("   Items: {}", total_items);
-            println!("   Created: {}", pattern.created_at);
-            println!();
+// Read an exported scaff, transparently decompressing gzip files
+fn read_exported_pattern(file: &Path) -> Result<CodePattern, ScaffError> {
+    let bytes = fs::read(file)?;
+    let content = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut content = String::new();
+        GzDecoder::new(bytes.as_slice()).read_to_string(&mut content)?;
+        content
+    } else {
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+    };
+
+    serde_json::from_str(&content).map_err(|source| ScaffError::InvalidScaff {
+        path: file.to_path_buf(),
+        source,
+    })
+}
+
+/// Held while reading and writing scaff files so concurrent `scaff` runs, e.g. from
+/// parallel scripts, take turns instead of clobbering each other. Released on drop.
+pub struct ScaffsLock {
+    path: PathBuf,
+}
+
+impl ScaffsLock {
+    /// Write `pattern` in the current schema to its file in the locked directory
+    pub fn save_pattern(&self, pattern: &CodePattern) -> Result<PathBuf, ScaffError> {
+        let scaffs_dir = self.path.parent().unwrap_or(Path::new("."));
+        let file_path = scaffs_dir.join(format!("{}.json", scaff_file_stem(&pattern.name)));
+
+        // Always write the current schema
+        let mut pattern = pattern.clone();
+        pattern.migrate();
+        write_atomic(&file_path, &serde_json::to_string_pretty(&pattern)?)?;
+
+        info!(
+            "Saved pattern '{}' to {}",
+            pattern.name,
+            file_path.display()
+        );
+        Ok(file_path)
+    }
+
+    fn acquire(scaffs_dir: &Path) -> Result<Self, ScaffError> {
+        let path = scaffs_dir.join(LOCK_FILE);
+        let started = Instant::now();
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    // Only informational, for whoever finds a leftover lock
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(ScaffsLock { path });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if is_stale_lock(&path) {
+                        Self::take_over_stale(&path);
+                    } else if started.elapsed() > LOCK_TIMEOUT {
+                        return Err(ScaffError::Locked(path));
+                    } else {
+                        thread::sleep(Duration::from_millis(20));
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
         }
+    }
 
-        Ok(())
+    // Move a stale lock aside before removing it, so of several runs that found it
+    // stale only the one whose rename wins removes it; the others retry creating the
+    // lock. A fresh lock moved by mistake, because a winner replaced the stale one in
+    // between, is linked back, which fails rather than replace yet another lock.
+    fn take_over_stale(path: &Path) {
+        static TAKEOVERS: AtomicUsize = AtomicUsize::new(0);
+        let aside = path.with_file_name(format!(
+            "{}.{}.{}.stale",
+            LOCK_FILE,
+            std::process::id(),
+            TAKEOVERS.fetch_add(1, Ordering::Relaxed)
+        ));
+        if fs::rename(path, &aside).is_err() {
+            return;
+        }
+
+        if is_stale_lock(&aside) {
+            warn!("Removing stale lock {}", path.display());
+        } else {
+            let _ = fs::hard_link(&aside, path);
+        }
+        let _ = fs::remove_file(&aside);
     }
 }
 
+fn is_stale_lock(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK_AGE)
+}
+
+impl Drop for ScaffsLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Write to a temporary file next to `path` and rename it into place, so readers see
+// the old content or the new, never a truncated file
+fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// File name (without `.json`) a pattern is saved under, e.g. `My Pattern` -> `my_pattern`
+pub fn scaff_file_stem(name: &str) -> String {
+    name.replace(" ", "_").to_lowercase()
+}
+
+// Whether `name` refers to the pattern stored at `path`, by internal name or file name
+fn refers_to(name: &str, path: &Path, pattern: &CodePattern) -> bool {
+    let stem = scaff_file_stem(name);
+    pattern.name == name
+        || scaff_file_stem(&pattern.name) == stem
+        || path
+            .file_stem()
+            .is_some_and(|s| s.to_string_lossy() == stem)
+}
+
 pub fn create_pattern_from_scan(
     files: Vec<FilePattern>,
     name: String,
@@ -131,52 +813,129 @@ pub fn create_pattern_from_scan(
     let description = format!(
         "Pattern with {} files containing {} total items",
         files.len(),
-        files
-            .iter()
-            .map(|f| f.classes.len()
-                + f.functions.len()
-                + f.structs.len()
-                + f.implementations.len())
-            .sum::<usize>()
+        files.iter().map(FilePattern::item_count).sum::<usize>()
     );
 
     CodePattern {
+        schema_version: SCHEMA_VERSION,
         name,
         description,
         language,
+        directories: directories_of(&files),
+        include: Vec::new(),
+        exclude: Vec::new(),
+        tags: Vec::new(),
+        variables: HashMap::new(),
         files,
         created_at: chrono::Utc::now().to_rfc3339(),
+        updated_at: None,
+        unqualified_modules: false,
     }
 }
 
-pub fn display_pattern_summary(pattern: &CodePattern) {
-    println!("\n🔍 Pattern: {}", pattern.name);
-    println!("📝 Description: {}", pattern.description);
-    println!("🔤 Language: {}", pattern.language);
-    println!("📁 Files: {}", pattern.files.len());
-    println!("📅 Created: {}", pattern.created_at);
-    println!("{:-<50}", "");
+/// Keep only the files at the given indices, in scan order. Indices past the end are ignored.
+pub fn select_files(files: Vec<FilePattern>, selections: &[usize]) -> Vec<FilePattern> {
+    files
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| selections.contains(index))
+        .map(|(_, file)| file)
+        .collect()
+}
+
+/// Sorted, deduplicated parent directories of the given files, without a leading `./`
+pub fn directories_of(files: &[FilePattern]) -> Vec<String> {
+    let directories: BTreeSet<String> = files
+        .iter()
+        .filter_map(|f| Path::new(&f.path).parent())
+        .map(|parent| {
+            parent
+                .components()
+                .filter(|c| !matches!(c, Component::CurDir))
+                .collect::<PathBuf>()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .filter(|dir| !dir.is_empty())
+        .collect();
+    directories.into_iter().collect()
+}
 
+/// Render a scaff as a Mermaid `classDiagram`: one class per file, labelled with its
+/// path and grouped into a namespace per directory, listing the file's classes, structs,
+/// enums and traits as attributes and its functions as methods
+pub fn to_mermaid(pattern: &CodePattern) -> String {
+    let mut by_directory: BTreeMap<String, Vec<(String, String, &FilePattern)>> = BTreeMap::new();
+    let mut ids = BTreeSet::new();
     for file in &pattern.files {
-        println!("📄 {}", file.path);
+        let path = file.path.replace('\\', "/");
+        let path = path.trim_start_matches("./");
+        let directory = path.rsplit_once('/').map_or("", |(dir, _)| dir);
 
-        if !file.classes.is_empty() {
-            println!("  Classes: {}", file.classes.join(", "));
+        // Mermaid ids are identifiers, so paths that sanitize alike get a suffix
+        let base = mermaid_id(path);
+        let mut id = base.clone();
+        let mut suffix = 1;
+        while !ids.insert(id.clone()) {
+            suffix += 1;
+            id = format!("{}_{}", base, suffix);
         }
-        if !file.structs.is_empty() {
-            println!("  Structs: {}", file.structs.join(", "));
+        by_directory
+            .entry(directory.to_string())
+            .or_default()
+            .push((id, path.to_string(), file));
+    }
+
+    let mut lines = vec!["classDiagram".to_string()];
+    for (directory, files) in &by_directory {
+        let indent = if directory.is_empty() {
+            "    "
+        } else {
+            lines.push(format!("    namespace {} {{", mermaid_id(directory)));
+            "        "
+        };
+        for (id, path, _) in files {
+            lines.push(format!("{}class {}[\"{}\"]", indent, id, path));
         }
-        if !file.functions.is_empty() {
-            println!("  Functions: {}", file.functions.join(", "));
+        if !directory.is_empty() {
+            lines.push("    }".to_string());
         }
-        if !file.implementations.is_empty() {
-            println!("  Implementations: {}", file.implementations.join(", "));
+    }
+
+    for (id, _, file) in by_directory.values().flatten() {
+        let members = [
+            ("class", &file.classes),
+            ("struct", &file.structs),
+            ("enum", &file.enums),
+            ("trait", &file.traits),
+        ];
+        for (kind, names) in members {
+            for name in names {
+                lines.push(format!("    {} : {} {}", id, kind, mermaid_text(name)));
+            }
+        }
+        for function in &file.functions {
+            lines.push(format!("    {} : {}()", id, mermaid_text(function)));
         }
-        println!();
     }
+
+    lines.join("\n") + "\n"
+}
+
+// `src/models/user.rs` -> `src_models_user_rs`
+fn mermaid_id(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// Mermaid writes generics as `~T~`; angle brackets would end the member
+fn mermaid_text(name: &str) -> String {
+    name.replace(['<', '>'], "~")
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
     use std::fs;
@@ -190,16 +949,19 @@ mod tests {
             functions: vec!["test_function".to_string()],
             structs: vec!["TestStruct".to_string()],
             implementations: vec!["TestImpl".to_string()],
+            ..Default::default()
         }
     }
 
     fn create_test_pattern() -> CodePattern {
         CodePattern {
+            schema_version: SCHEMA_VERSION,
             name: "test_pattern".to_string(),
             description: "A test pattern".to_string(),
             language: "Rust".to_string(),
             files: vec![create_test_file_pattern()],
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
         }
     }
 
@@ -214,6 +976,18 @@ mod tests {
         assert_eq!(file_pattern.implementations.len(), 1);
     }
 
+    #[test]
+    fn test_parse_errors_only_serialized_when_set() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file_pattern = create_test_file_pattern();
+        let json = serde_json::to_value(&file_pattern)?;
+        assert!(json.get("parse_errors").is_none());
+
+        file_pattern.parse_errors = true;
+        let json = serde_json::to_value(&file_pattern)?;
+        assert_eq!(json["parse_errors"], true);
+        Ok(())
+    }
+
     #[test]
     fn test_code_pattern_creation() {
         let pattern = create_test_pattern();
@@ -235,9 +1009,430 @@ mod tests {
     }
 
     #[test]
-    fn test_scaff_directory_new() {
-        let scaff_dir = ScaffDirectory::new();
-        assert!(scaff_dir.patterns.is_empty());
+    fn test_function_signature_display() {
+        let signature = FunctionSignature {
+            name: "add".to_string(),
+            params: vec!["a: i32".to_string(), "b: i32".to_string()],
+            return_type: Some("i32".to_string()),
+        };
+        assert_eq!(signature.to_string(), "add(a: i32, b: i32) -> i32");
+    }
+
+    #[test]
+    fn test_load_migrates_legacy_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let legacy = r#"{
+            "name": "legacy",
+            "description": "Written before signatures",
+            "language": "Rust",
+            "files": [{
+                "path": "src/lib.rs",
+                "extension": "rs",
+                "classes": [],
+                "functions": ["run"],
+                "structs": [],
+                "implementations": []
+            }],
+            "created_at": "2024-01-01T00:00:00Z"
+        }"#;
+
+        let mut pattern: CodePattern = serde_json::from_str(legacy)?;
+        assert_eq!(pattern.schema_version, 1);
+        assert!(pattern.files[0].signatures.is_empty());
+
+        pattern.migrate();
+        assert_eq!(pattern.schema_version, SCHEMA_VERSION);
+        assert_eq!(pattern.files[0].functions, vec!["run"]);
+        assert!(pattern.files[0].enums.is_empty());
+        assert!(pattern.files[0].traits.is_empty());
+        assert!(pattern.unqualified_modules);
+
+        // A fresh scan records module paths again
+        pattern.update_files(vec![create_test_file_pattern()]);
+        assert!(!pattern.unqualified_modules);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unqualify_modules() {
+        let mut file = FilePattern {
+            path: "src/lib.rs".to_string(),
+            extension: "rs".to_string(),
+            functions: vec!["run".to_string(), "tests::run".to_string()],
+            structs: vec![
+                "api::Config".to_string(),
+                "Wrapper<std::io::Error>".to_string(),
+            ],
+            impl_methods: HashMap::from([
+                ("api::Config".to_string(), vec!["new".to_string()]),
+                ("db::Config".to_string(), vec!["open".to_string()]),
+            ]),
+            structure_hash: Some("abc".to_string()),
+            ..Default::default()
+        };
+        file.unqualify_modules();
+
+        assert_eq!(file.functions, vec!["run"]);
+        assert_eq!(file.structs, vec!["Config", "Wrapper<std::io::Error>"]);
+        let mut methods = file.impl_methods["Config"].clone();
+        methods.sort();
+        assert_eq!(methods, vec!["new", "open"]);
+        assert!(file.structure_hash.is_none());
+    }
+
+    #[test]
+    fn test_load_patterns_upgrades_v1_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        // Hand-written v1 file: no schema_version, file language, enums, traits,
+        // impl_methods, content_hash or signatures
+        fs::write(
+            temp_dir.path().join("v1.json"),
+            r#"{
+                "name": "v1",
+                "description": "Saved by an early scaff",
+                "language": "Rust",
+                "files": [{
+                    "path": "src/lib.rs",
+                    "extension": "rs",
+                    "classes": [],
+                    "functions": ["run", "stop"],
+                    "structs": ["Engine"],
+                    "implementations": ["Engine"],
+                    "metadata": {},
+                    "attributes": {}
+                }],
+                "created_at": "2024-01-01T00:00:00Z"
+            }"#,
+        )?;
+
+        let patterns = ScaffDirectory::at(temp_dir.path()).load_patterns()?;
+        assert_eq!(patterns.len(), 1);
+        let pattern = &patterns[0];
+        assert_eq!(pattern.schema_version, SCHEMA_VERSION);
+
+        let file = &pattern.files[0];
+        assert_eq!(file.functions, vec!["run", "stop"]);
+        assert_eq!(file.structs, vec!["Engine"]);
+        assert!(file.language.is_none());
+        assert!(file.enums.is_empty() && file.traits.is_empty());
+        assert!(file.impl_methods.is_empty());
+        assert!(file.struct_fields.is_empty());
+        assert!(file.content_hash.is_none());
+        assert!(file.signatures.is_empty());
+        assert!(pattern.tags.is_empty());
+
+        // Serializing the upgraded pattern writes the current version
+        let saved: serde_json::Value = serde_json::to_value(pattern)?;
+        assert_eq!(saved["schema_version"], SCHEMA_VERSION);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let schema = pattern_schema();
+        let mut pattern = serde_json::to_value(create_test_pattern())?;
+        assert!(jsonschema::is_valid(&schema, &pattern));
+
+        pattern.as_object_mut().unwrap().remove("language");
+        assert!(!jsonschema::is_valid(&schema, &pattern));
+
+        // The failure names the missing field rather than serde's position
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("broken.json"), pattern.to_string())?;
+        let (patterns, failures) = ScaffDirectory::at(temp_dir.path()).check_pattern_files()?;
+        assert!(patterns.is_empty());
+        assert_eq!(failures.len(), 1);
+        assert!(
+            failures[0]
+                .1
+                .contains("\"language\" is a required property")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_pattern() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let scaffs_dir = temp_dir.path().join("scaffs");
+        let pattern = create_test_pattern();
+
+        // Compressed and plain exports read back the same
+        let compressed = temp_dir.path().join("shared.scaff");
+        let mut encoder = GzEncoder::new(fs::File::create(&compressed)?, Compression::default());
+        encoder.write_all(serde_json::to_string(&pattern)?.as_bytes())?;
+        encoder.finish()?;
+
+        let (imported, path) =
+            ScaffDirectory::at(&scaffs_dir).import_pattern(&compressed, false)?;
+        assert_eq!(imported.name, pattern.name);
+        assert_eq!(path, scaffs_dir.join("test_pattern.json"));
+
+        let err = ScaffDirectory::at(&scaffs_dir)
+            .import_pattern(&compressed, false)
+            .unwrap_err();
+        assert!(matches!(err, ScaffError::AlreadyExists(_)));
+        assert!(err.to_string().contains("--force"));
+        ScaffDirectory::at(&scaffs_dir).import_pattern(&compressed, true)?;
+
+        let mut future = pattern.clone();
+        future.schema_version = SCHEMA_VERSION + 1;
+        let future_file = temp_dir.path().join("future.json");
+        fs::write(&future_file, serde_json::to_string(&future)?)?;
+        let err = ScaffDirectory::at(&scaffs_dir)
+            .import_pattern(&future_file, true)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ScaffError::UnsupportedSchema { version, supported, .. }
+                if version == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+        ));
+        assert!(err.to_string().contains("Upgrade scaff"));
+
+        let invalid = temp_dir.path().join("invalid.json");
+        fs::write(&invalid, "{}")?;
+        let err = ScaffDirectory::at(&scaffs_dir)
+            .import_pattern(&invalid, false)
+            .unwrap_err();
+        assert!(matches!(err, ScaffError::InvalidScaff { .. }));
+        assert!(err.to_string().contains("not a valid scaff"));
+
+        Ok(())
+    }
+
+    fn listed_names(patterns: Vec<CodePattern>) -> Vec<String> {
+        patterns.into_iter().map(|p| p.name).collect()
+    }
+
+    fn write_list_fixtures(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let file = || FilePattern {
+            path: "src/lib.rs".to_string(),
+            ..Default::default()
+        };
+        for (name, language, created_at, files) in [
+            ("beta", "Rust", "2024-03-01T00:00:00+00:00", 3),
+            ("alpha", "JavaScript", "2024-05-01T00:00:00Z", 1),
+            ("gamma", "rust", "2023-12-31T23:00:00-02:00", 2),
+        ] {
+            let mut pattern =
+                create_pattern_from_scan(vec![file(); files], name.into(), language.into());
+            pattern.created_at = created_at.to_string();
+            ScaffDirectory::at(dir).save_pattern(&pattern)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_patterns_sorted() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        write_list_fixtures(temp_dir.path())?;
+
+        let list = |sort| {
+            let options = ListOptions {
+                sort,
+                ..ListOptions::default()
+            };
+            ScaffDirectory::at(temp_dir.path())
+                .list_patterns(&options)
+                .map(listed_names)
+        };
+
+        assert_eq!(list(ListSort::Name)?, vec!["alpha", "beta", "gamma"]);
+        // gamma's offset puts it on 2024-01-01 UTC, still the oldest
+        assert_eq!(list(ListSort::Created)?, vec!["gamma", "beta", "alpha"]);
+        assert_eq!(list(ListSort::Files)?, vec!["alpha", "gamma", "beta"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_patterns_by_language() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        write_list_fixtures(temp_dir.path())?;
+
+        let options = ListOptions {
+            language: Some("RUST".to_string()),
+            ..ListOptions::default()
+        };
+        let listed = ScaffDirectory::at(temp_dir.path()).list_patterns(&options)?;
+        assert_eq!(listed_names(listed), vec!["beta", "gamma"]);
+
+        let options = ListOptions {
+            language: Some("go".to_string()),
+            ..ListOptions::default()
+        };
+        assert!(
+            ScaffDirectory::at(temp_dir.path())
+                .list_patterns(&options)?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_tag() {
+        let mut pattern = create_pattern_from_scan(Vec::new(), "api".to_string(), "Rust".into());
+        assert!(!pattern.has_tag("backend"));
+
+        pattern.tags = vec!["Backend".to_string(), "http".to_string()];
+        assert!(pattern.has_tag("backend"));
+        assert!(pattern.has_tag("http"));
+        assert!(!pattern.has_tag("frontend"));
+    }
+
+    #[test]
+    fn test_update_files_keeps_metadata() {
+        let file = |path: &str| FilePattern {
+            path: path.to_string(),
+            ..Default::default()
+        };
+        let mut pattern = create_pattern_from_scan(
+            vec![file("src/old.rs"), file("src/lib.rs")],
+            "api".to_string(),
+            "Rust".into(),
+        );
+        let health_check = OptionalItem {
+            item_type: "function".to_string(),
+            name: "health_check".to_string(),
+        };
+        pattern.files[1].optional = vec![health_check.clone()];
+        pattern.description = "Our API".to_string();
+        pattern.tags = vec!["backend".to_string()];
+        pattern.created_at = "2024-01-01T00:00:00Z".to_string();
+
+        pattern.update_files(vec![file("src/lib.rs"), file("src/models/user.rs")]);
+
+        assert_eq!(pattern.name, "api");
+        assert_eq!(pattern.description, "Our API");
+        assert_eq!(pattern.tags, vec!["backend"]);
+        assert_eq!(pattern.created_at, "2024-01-01T00:00:00Z");
+        assert!(pattern.updated_at.is_some());
+        assert_eq!(pattern.files.len(), 2);
+        assert_eq!(pattern.files[0].optional, vec![health_check]);
+        assert_eq!(pattern.directories, vec!["src", "src/models"]);
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let file = |path: &str, structs: &[&str], functions: &[&str]| FilePattern {
+            path: path.to_string(),
+            structs: structs.iter().map(|s| s.to_string()).collect(),
+            functions: functions.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+        let pattern = create_pattern_from_scan(
+            vec![
+                file("./src/main.rs", &[], &["main"]),
+                file("./src/models/user.rs", &["User", "Page<T>"], &[]),
+                file("./src/models/post.rs", &["Post"], &[]),
+                file("build.rs", &[], &[]),
+            ],
+            "api".to_string(),
+            "Rust".into(),
+        );
+
+        let mermaid = to_mermaid(&pattern);
+        assert!(mermaid.starts_with("classDiagram\n"));
+        assert_eq!(mermaid.matches("class ").count(), 4);
+        assert!(mermaid.contains("    class build_rs[\"build.rs\"]\n"));
+        assert!(mermaid.contains(
+            "    namespace src_models {\n        class src_models_user_rs[\"src/models/user.rs\"]\n        class src_models_post_rs[\"src/models/post.rs\"]\n    }"
+        ));
+        assert!(
+            mermaid
+                .contains("    namespace src {\n        class src_main_rs[\"src/main.rs\"]\n    }")
+        );
+        assert!(mermaid.contains("    src_models_user_rs : struct Page~T~\n"));
+        assert!(mermaid.contains("    src_main_rs : main()\n"));
+    }
+
+    #[test]
+    fn test_select_files() {
+        let files: Vec<FilePattern> = ["a.rs", "b.rs", "c.rs"]
+            .iter()
+            .map(|path| FilePattern {
+                path: path.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let paths = |files: Vec<FilePattern>| -> Vec<String> {
+            files.into_iter().map(|f| f.path).collect()
+        };
+
+        assert_eq!(
+            paths(select_files(files.clone(), &[2, 0])),
+            vec!["a.rs", "c.rs"]
+        );
+        assert_eq!(paths(select_files(files.clone(), &[1, 7])), vec!["b.rs"]);
+        assert!(select_files(files, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_directories_of() {
+        let files: Vec<FilePattern> = [
+            "./src/main.rs",
+            "src/services/user.rs",
+            "build.rs",
+            "src/lib.rs",
+        ]
+        .iter()
+        .map(|path| FilePattern {
+            path: path.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+        assert_eq!(directories_of(&files), vec!["src", "src/services"]);
+
+        let pattern = create_pattern_from_scan(files, "dirs".to_string(), "Rust".to_string());
+        assert_eq!(pattern.directories, vec!["src", "src/services"]);
+    }
+
+    #[test]
+    fn test_migrate_leaves_newer_schema_alone() {
+        let mut pattern = create_test_pattern();
+        pattern.schema_version = SCHEMA_VERSION + 1;
+        pattern.migrate();
+        assert_eq!(pattern.schema_version, SCHEMA_VERSION + 1);
+    }
+
+    #[test]
+    fn test_find_pattern_by_name_or_file_name() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let mut pattern = create_test_pattern();
+        pattern.name = "My API Pattern".to_string();
+        fs::write(
+            temp_dir
+                .path()
+                .join(format!("{}.json", scaff_file_stem(&pattern.name))),
+            serde_json::to_string(&pattern)?,
+        )?;
+        assert!(temp_dir.path().join("my_api_pattern.json").exists());
+
+        for name in ["My API Pattern", "my_api_pattern", "my api pattern"] {
+            let found = ScaffDirectory::at(temp_dir.path()).find_pattern(name)?;
+            assert_eq!(found.name, "My API Pattern");
+        }
+
+        let err = ScaffDirectory::at(temp_dir.path())
+            .find_pattern("Other Pattern")
+            .unwrap_err();
+        assert!(matches!(&err, ScaffError::NotFound(name) if name == "Other Pattern"));
+        assert!(err.to_string().contains("scaff list"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scaff_directory_at() {
+        let scaff_dir = ScaffDirectory::at("custom/scaffs");
+        assert_eq!(scaff_dir.path(), Path::new("custom/scaffs"));
+        assert_eq!(
+            scaff_dir,
+            ScaffDirectory::at(PathBuf::from("custom/scaffs"))
+        );
     }
 
     #[test]
@@ -260,7 +1455,7 @@ mod tests {
                     && current_scaffs_dir.join("test_pattern.json").exists()
                 {
                     // Test loading
-                    let loaded_patterns = ScaffDirectory::load_patterns()?;
+                    let loaded_patterns = scaff_dir.load_patterns()?;
                     assert_eq!(loaded_patterns.len(), 1);
                     assert_eq!(loaded_patterns[0].name, "test_pattern");
                     assert_eq!(loaded_patterns[0].language, "Rust");
@@ -281,15 +1476,170 @@ mod tests {
     }
 
     #[test]
-    fn test_load_patterns_empty_directory() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_overwriting_pattern_is_never_partial() -> Result<(), Box<dyn std::error::Error>> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
         let temp_dir = TempDir::new()?;
-        let original_dir = std::env::current_dir()?;
-        std::env::set_current_dir(temp_dir.path())?;
+        let scaffs_dir = temp_dir.path().to_path_buf();
+        // Large enough that a plain write would take several syscalls
+        let mut pattern = create_test_pattern();
+        pattern.files = (0..500)
+            .map(|i| FilePattern {
+                path: format!("src/module_{}.rs", i),
+                ..create_test_file_pattern()
+            })
+            .collect();
+        let file_path = ScaffDirectory::at(&scaffs_dir).save_pattern(&pattern)?;
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let done = Arc::clone(&done);
+            let file_path = file_path.clone();
+            thread::spawn(move || {
+                let mut reads = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let content = fs::read_to_string(&file_path).unwrap();
+                    let loaded: CodePattern = serde_json::from_str(&content).unwrap_or_else(|e| {
+                        panic!("read a partial scaff after {reads} reads: {e}")
+                    });
+                    assert_eq!(loaded.name, "test_pattern");
+                    reads += 1;
+                }
+            })
+        };
+
+        for i in 0..50 {
+            pattern.description = format!("revision {}", i);
+            pattern.files.truncate(500 - i);
+            ScaffDirectory::at(&scaffs_dir).save_pattern(&pattern)?;
+        }
+        done.store(true, Ordering::Relaxed);
+        reader.join().expect("reader saw a partially written scaff");
+
+        // Neither the lock nor temporary files are left behind
+        let leftovers: Vec<_> = fs::read_dir(&scaffs_dir)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(leftovers, vec!["test_pattern.json"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_saves_take_turns() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let scaffs_dir = temp_dir.path().to_path_buf();
+
+        let savers: Vec<_> = (0..8)
+            .map(|i| {
+                let scaffs_dir = scaffs_dir.clone();
+                thread::spawn(move || {
+                    let pattern = CodePattern {
+                        name: format!("pattern_{}", i % 4),
+                        description: format!("saved by {}", i),
+                        ..create_test_pattern()
+                    };
+                    ScaffDirectory::at(&scaffs_dir)
+                        .save_pattern(&pattern)
+                        .map(|_| ())
+                })
+            })
+            .collect();
+        for saver in savers {
+            saver.join().unwrap()?;
+        }
 
-        let patterns = ScaffDirectory::load_patterns()?;
+        let (patterns, failures) = ScaffDirectory::at(&scaffs_dir).check_pattern_files()?;
+        assert_eq!(patterns.len(), 4);
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert!(!scaffs_dir.join(LOCK_FILE).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let lock = fs::File::create(temp_dir.path().join(LOCK_FILE))?;
+        lock.set_modified(std::time::SystemTime::now() - STALE_LOCK_AGE * 2)?;
+
+        let savers: Vec<_> = (0..8)
+            .map(|_| {
+                let scaffs_dir = temp_dir.path().to_path_buf();
+                thread::spawn(move || {
+                    ScaffDirectory::at(&scaffs_dir)
+                        .save_pattern(&create_test_pattern())
+                        .map(|_| ())
+                })
+            })
+            .collect();
+        for saver in savers {
+            saver.join().unwrap()?;
+        }
+        assert!(temp_dir.path().join("test_pattern.json").exists());
+        let leftovers: Vec<_> = fs::read_dir(temp_dir.path())?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(leftovers, vec!["test_pattern.json"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_pattern_locked_holds_lock_until_saved() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let scaffs_dir = temp_dir.path().to_path_buf();
+        ScaffDirectory::at(&scaffs_dir).save_pattern(&create_test_pattern())?;
+
+        let (mut pattern, lock) =
+            ScaffDirectory::at(&scaffs_dir).find_pattern_locked("test_pattern")?;
+        let saver = {
+            let scaffs_dir = scaffs_dir.clone();
+            thread::spawn(move || {
+                let pattern = CodePattern {
+                    description: "saved concurrently".to_string(),
+                    ..create_test_pattern()
+                };
+                ScaffDirectory::at(&scaffs_dir)
+                    .save_pattern(&pattern)
+                    .map(|_| ())
+            })
+        };
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(
+            ScaffDirectory::at(&scaffs_dir)
+                .find_pattern("test_pattern")?
+                .description,
+            create_test_pattern().description
+        );
+
+        pattern.description = "updated".to_string();
+        lock.save_pattern(&pattern)?;
+        drop(lock);
+        saver.join().unwrap()?;
+        // The concurrent save waited for the update instead of being lost under it
+        assert_eq!(
+            ScaffDirectory::at(&scaffs_dir)
+                .find_pattern("test_pattern")?
+                .description,
+            "saved concurrently"
+        );
+
+        assert!(matches!(
+            ScaffDirectory::at(temp_dir.path().join("missing")).find_pattern_locked("x"),
+            Err(ScaffError::NotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_patterns_empty_directory() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let patterns = ScaffDirectory::at(temp_dir.path().join("scaffs")).load_patterns()?;
         assert!(patterns.is_empty());
 
-        std::env::set_current_dir(original_dir)?;
         Ok(())
     }
 
@@ -302,13 +1652,9 @@ mod tests {
         // Create invalid JSON file
         fs::write(scaffs_dir.join("invalid.json"), "{ invalid json }")?;
 
-        let original_dir = std::env::current_dir()?;
-        std::env::set_current_dir(temp_dir.path())?;
-
-        let patterns = ScaffDirectory::load_patterns()?;
+        let patterns = ScaffDirectory::at(&scaffs_dir).load_patterns()?;
         assert!(patterns.is_empty()); // Should skip invalid files
 
-        std::env::set_current_dir(original_dir)?;
         Ok(())
     }
 }
@@ -0,0 +1,213 @@
+use log::{debug, info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A step run on each file `CodeGenerator` wrote, once all are written, e.g. a formatter
+pub trait PostProcessor {
+    /// Shown in logs, e.g. `rustfmt`
+    fn name(&self) -> &str;
+
+    /// Whether this processor handles `path`, usually by its extension
+    fn applies_to(&self, path: &Path) -> bool;
+
+    /// Whether the processor can run here; unavailable processors are skipped
+    fn is_available(&self) -> bool;
+
+    /// Process the file in place
+    fn process(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Runs an external formatter on files with matching extensions, appending the file's
+/// path to `args`
+#[derive(Debug, Clone)]
+pub struct CommandFormatter {
+    program: String,
+    args: Vec<String>,
+    extensions: Vec<String>,
+}
+
+impl CommandFormatter {
+    pub fn new(program: &str, args: &[&str], extensions: &[&str]) -> Self {
+        CommandFormatter {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+        }
+    }
+
+    /// `prettier --write` for JavaScript and TypeScript files
+    pub fn prettier() -> Self {
+        Self::new("prettier", &["--write"], &["js", "jsx", "ts", "tsx"])
+    }
+}
+
+impl PostProcessor for CommandFormatter {
+    fn name(&self) -> &str {
+        &self.program
+    }
+
+    fn applies_to(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|e| e == ext))
+    }
+
+    fn is_available(&self) -> bool {
+        on_path(&self.program)
+    }
+
+    fn process(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        run(Command::new(&self.program).args(&self.args).arg(path))
+    }
+}
+
+/// `rustfmt` for `.rs` files, with the edition of the crate each file belongs to, read
+/// from the nearest Cargo.toml above it
+#[derive(Debug, Clone, Default)]
+pub struct Rustfmt;
+
+impl PostProcessor for Rustfmt {
+    fn name(&self) -> &str {
+        "rustfmt"
+    }
+
+    fn applies_to(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "rs")
+    }
+
+    fn is_available(&self) -> bool {
+        on_path("rustfmt")
+    }
+
+    fn process(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut command = Command::new("rustfmt");
+        if let Some(edition) = crate_edition(path) {
+            command.args(["--edition", &edition]);
+        }
+        run(command.arg(path))
+    }
+}
+
+/// The formatters `--format-output` runs: rustfmt and prettier
+pub fn default_formatters() -> Vec<Box<dyn PostProcessor>> {
+    vec![Box::new(Rustfmt), Box::new(CommandFormatter::prettier())]
+}
+
+/// Run every applicable processor on the written files. Unavailable processors are
+/// skipped and failures only logged, since the files themselves were generated fine.
+pub fn run_post_processors(processors: &[Box<dyn PostProcessor>], paths: &[PathBuf]) {
+    for path in paths {
+        for processor in processors.iter().filter(|p| p.applies_to(path)) {
+            if !processor.is_available() {
+                info!(
+                    "{} isn't available, leaving {} as generated",
+                    processor.name(),
+                    path.display()
+                );
+                continue;
+            }
+
+            match processor.process(path) {
+                Ok(()) => debug!("Ran {} on {}", processor.name(), path.display()),
+                Err(e) => warn!("{} failed on {}: {}", processor.name(), path.display(), e),
+            }
+        }
+    }
+}
+
+fn run(command: &mut Command) -> Result<(), Box<dyn std::error::Error>> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} exited with {}: {}",
+            command.get_program().to_string_lossy(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// The edition of the crate `path` is in, from the nearest Cargo.toml above it, or None
+// outside a crate
+fn crate_edition(path: &Path) -> Option<String> {
+    path.ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+        .and_then(|manifest| fs::read_to_string(manifest).ok())
+        .map(|manifest| manifest_edition(&manifest))
+}
+
+// The `edition` of a Cargo.toml's [package] table. Without one (or with one inherited
+// from a workspace, which isn't resolved) it's Cargo's default, 2015.
+fn manifest_edition(manifest: &str) -> String {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+            continue;
+        }
+        if in_package
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "edition"
+        {
+            let value = value.split('#').next().unwrap_or_default().trim();
+            if let Some(edition) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                return edition.to_string();
+            }
+        }
+    }
+    "2015".to_string()
+}
+
+// Whether `program` is an executable file in a PATH directory
+fn on_path(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(program);
+        candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_formatter_extensions_and_availability() {
+        assert!(Rustfmt.applies_to(Path::new("src/lib.rs")));
+        assert!(!Rustfmt.applies_to(Path::new("Cargo.toml")));
+        assert!(CommandFormatter::prettier().applies_to(Path::new("src/app.tsx")));
+
+        let missing = CommandFormatter::new("scaff-no-such-formatter", &[], &["rs"]);
+        assert!(!missing.is_available());
+        // Skipped without an error
+        run_post_processors(&[Box::new(missing)], &[PathBuf::from("src/lib.rs")]);
+    }
+
+    #[test]
+    fn test_crate_edition() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let file = temp_dir.path().join("src/services/mod.rs");
+        assert_eq!(crate_edition(&file), None);
+
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nedition = \"2024\" # latest\n\n[dependencies]\nedition = \"1\"\n",
+        )?;
+        assert_eq!(crate_edition(&file).as_deref(), Some("2024"));
+
+        assert_eq!(manifest_edition("[package]\nname = \"app\"\n"), "2015");
+        assert_eq!(
+            manifest_edition("[dependencies]\nedition = \"1\"\n[package]\nedition = \"2018\"\n"),
+            "2018"
+        );
+
+        Ok(())
+    }
+}
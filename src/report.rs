@@ -0,0 +1,662 @@
+use crate::error::ScaffError;
+use crate::generator::{FileStatus, GenerationSummary};
+use crate::pattern::{CodePattern, FilePattern};
+use crate::scanner;
+use crate::style;
+use crate::symbols;
+use crate::validator::ValidationResult;
+use std::path::Path;
+
+pub fn display_scan_results(files: &[FilePattern], language_type: &str, max_name_length: usize) {
+    println!(
+        "\n{} {}",
+        symbols::SEARCH,
+        style::bold(format!("Scan Results ({})", language_type))
+    );
+    println!("{:-<50}", "");
+
+    for file in files {
+        println!("\nFile: {}", style::bold(&file.path));
+
+        if !file.classes.is_empty() {
+            println!("  Classes:");
+            for class in &file.classes {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(class, max_name_length)
+                );
+            }
+        }
+        if !file.structs.is_empty() {
+            println!("  Structs:");
+            for struct_name in &file.structs {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(struct_name, max_name_length)
+                );
+            }
+        }
+        if !file.implementations.is_empty() {
+            println!("  Implementations:");
+            for impl_name in &file.implementations {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(impl_name, max_name_length)
+                );
+            }
+        }
+        if !file.enums.is_empty() {
+            println!("  Enums:");
+            for enum_name in &file.enums {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(enum_name, max_name_length)
+                );
+            }
+        }
+        if !file.traits.is_empty() {
+            println!("  Traits:");
+            for trait_name in &file.traits {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(trait_name, max_name_length)
+                );
+            }
+        }
+        if !file.impl_methods.is_empty() {
+            println!("  Methods:");
+            for method in file.qualified_impl_methods() {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(&method, max_name_length)
+                );
+            }
+        }
+        if file.struct_fields.values().any(|fields| !fields.is_empty()) {
+            println!("  Fields:");
+            for field in file.qualified_struct_fields() {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(&field, max_name_length)
+                );
+            }
+        }
+        if !file.functions.is_empty() {
+            println!("  Functions:");
+            for function in &file.functions {
+                println!(
+                    "    - {}",
+                    scanner::truncate_item_name(function, max_name_length)
+                );
+            }
+        }
+
+        if file.item_count() == 0 {
+            println!("  (No extractable items found)");
+        }
+    }
+
+    display_parse_errors(files);
+}
+
+fn display_parse_errors(files: &[FilePattern]) {
+    let failed = scanner::files_with_parse_errors(files);
+    if failed.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{} Files with parse errors ({}):",
+        symbols::WARN,
+        failed.len()
+    );
+    for file in failed {
+        println!("  - {}", style::yellow(&file.path));
+    }
+}
+
+pub fn display_all_scan_results(results: &[(String, Vec<FilePattern>)], max_name_length: usize) {
+    if results.is_empty() {
+        println!("No supported files found in the directory.");
+        return;
+    }
+
+    println!(
+        "\n{} {}",
+        symbols::SEARCH,
+        style::bold("Multi-Language Scan Results")
+    );
+    println!("{:=<60}", "");
+
+    for (language, files) in results {
+        if !files.is_empty() {
+            display_scan_results(files, language, max_name_length);
+        }
+    }
+
+    // Summary
+    let total_files: usize = results.iter().map(|(_, files)| files.len()).sum();
+    let total_items: usize = results
+        .iter()
+        .map(|(_, files)| files.iter().map(FilePattern::item_count).sum::<usize>())
+        .sum();
+
+    println!("\n{} Summary:", symbols::STATS);
+    println!("  Languages found: {}", results.len());
+    println!("  Total files: {}", total_files);
+    println!("  Total items: {}", total_items);
+
+    let parse_errors: usize = results
+        .iter()
+        .map(|(_, files)| scanner::files_with_parse_errors(files).len())
+        .sum();
+    if parse_errors > 0 {
+        println!("  Files with parse errors: {}", parse_errors);
+    }
+}
+
+pub fn display_pattern_list(patterns: &[CodePattern], detailed: bool) {
+    println!("\nAvailable Scaffs:");
+    println!("{:-<50}", "");
+
+    for pattern in patterns {
+        println!(
+            "{} {} ({})",
+            symbols::LIST_ITEM,
+            pattern.name,
+            pattern.language
+        );
+        println!("   {}", pattern.description);
+        if !pattern.tags.is_empty() {
+            println!("   Tags: {}", pattern.tags.join(", "));
+        }
+        println!("   Files: {}", pattern.files.len());
+
+        let total_items = pattern
+            .files
+            .iter()
+            .map(FilePattern::item_count)
+            .sum::<usize>();
+
+        println!("   Items: {}", total_items);
+        if detailed {
+            let count = |f: fn(&FilePattern) -> usize| pattern.files.iter().map(f).sum::<usize>();
+            println!("     Classes: {}", count(|f| f.classes.len()));
+            println!("     Functions: {}", count(|f| f.functions.len()));
+            println!("     Structs: {}", count(|f| f.structs.len()));
+            println!(
+                "     Implementations: {}",
+                count(|f| f.implementations.len())
+            );
+            println!("     Enums: {}", count(|f| f.enums.len()));
+            println!("     Traits: {}", count(|f| f.traits.len()));
+            println!(
+                "     Methods: {}",
+                count(|f| f.impl_methods.values().map(Vec::len).sum())
+            );
+        }
+        println!("   Created: {}", pattern.created_at);
+        if let Some(updated_at) = &pattern.updated_at {
+            println!("   Updated: {}", updated_at);
+        }
+        println!();
+    }
+}
+
+pub fn display_pattern_summary(pattern: &CodePattern) {
+    println!("\n{} Pattern: {}", symbols::SEARCH, pattern.name);
+    println!("{} Description: {}", symbols::NOTE, pattern.description);
+    println!("{} Language: {}", symbols::LANGUAGE, pattern.language);
+    println!("{} Files: {}", symbols::FOLDER, pattern.files.len());
+    if !pattern.directories.is_empty() {
+        println!(
+            "{} Directories: {}",
+            symbols::OPEN_FOLDER,
+            pattern.directories.join(", ")
+        );
+    }
+    if !pattern.tags.is_empty() {
+        println!("{} Tags: {}", symbols::TAG, pattern.tags.join(", "));
+    }
+    if !pattern.include.is_empty() {
+        println!(
+            "{} Include: {}",
+            symbols::INCLUDE,
+            pattern.include.join(", ")
+        );
+    }
+    if !pattern.exclude.is_empty() {
+        println!(
+            "{} Exclude: {}",
+            symbols::EXCLUDE,
+            pattern.exclude.join(", ")
+        );
+    }
+    println!("{} Created: {}", symbols::DATE, pattern.created_at);
+    if let Some(updated_at) = &pattern.updated_at {
+        println!("{} Updated: {}", symbols::DATE, updated_at);
+    }
+    println!("{:-<50}", "");
+
+    for file in &pattern.files {
+        println!("{} {}", symbols::FILE, file.path);
+
+        if !file.classes.is_empty() {
+            println!("  Classes: {}", file.classes.join(", "));
+        }
+        if !file.structs.is_empty() {
+            println!("  Structs: {}", file.structs.join(", "));
+        }
+        if !file.functions.is_empty() {
+            println!("  Functions: {}", file.functions.join(", "));
+        }
+        if !file.implementations.is_empty() {
+            println!("  Implementations: {}", file.implementations.join(", "));
+        }
+        if !file.enums.is_empty() {
+            println!("  Enums: {}", file.enums.join(", "));
+        }
+        if !file.traits.is_empty() {
+            println!("  Traits: {}", file.traits.join(", "));
+        }
+        if !file.impl_methods.is_empty() {
+            println!("  Methods: {}", file.qualified_impl_methods().join(", "));
+        }
+        if file.struct_fields.values().any(|fields| !fields.is_empty()) {
+            println!("  Fields: {}", file.qualified_struct_fields().join(", "));
+        }
+        println!();
+    }
+}
+
+/// A validation's findings, then its summary. Count mismatches are tallied when the
+/// validator compared counts (`count_match`) and unmet expectations when it had any.
+pub fn display_validation_results(
+    result: &ValidationResult,
+    count_match: bool,
+    count_expectations: bool,
+) {
+    println!(
+        "\n{} {}",
+        symbols::SEARCH,
+        style::bold("Architecture Validation Results")
+    );
+    println!("Scaff: {}", result.scaff_name);
+    println!("{:-<60}", "");
+
+    if result.is_valid {
+        println!(
+            "{} {}",
+            symbols::OK,
+            style::green("Architecture is VALID - matches scaff pattern!")
+        );
+    } else {
+        println!(
+            "{} {}",
+            symbols::FAIL,
+            style::red("Architecture DEVIATES from scaff pattern")
+        );
+    }
+    println!(
+        "{} Conformance: {:.0}%",
+        symbols::SCORE,
+        result.conformance_score * 100.0
+    );
+
+    // Show missing files
+    if !result.missing_files.is_empty() {
+        println!(
+            "\n{} Missing Files ({}):",
+            symbols::FOLDER,
+            result.missing_files.len()
+        );
+        for file in &result.missing_files {
+            println!("  {} {}", symbols::FAIL, style::red(file));
+        }
+    }
+
+    // Show missing directories
+    if !result.missing_directories.is_empty() {
+        println!(
+            "\n{} Missing Directories ({}):",
+            symbols::OPEN_FOLDER,
+            result.missing_directories.len()
+        );
+        for directory in &result.missing_directories {
+            println!(
+                "  {} {}",
+                symbols::FAIL,
+                style::red(format!("{}/", directory))
+            );
+        }
+    }
+
+    // Show extra files
+    if !result.extra_files.is_empty() {
+        println!(
+            "\n{} Extra Files ({}):",
+            symbols::FOLDER,
+            result.extra_files.len()
+        );
+        for file in &result.extra_files {
+            println!("  {} {}", symbols::ADDED, style::yellow(file));
+        }
+    }
+
+    // Show files whose items changed since the scaff was saved
+    if !result.changed_files.is_empty() {
+        println!(
+            "\n{} Changed Files ({}):",
+            symbols::HASH,
+            result.changed_files.len()
+        );
+        for file in &result.changed_files {
+            println!("  {} {}", symbols::CHANGED, style::yellow(file));
+        }
+    }
+
+    // Show missing items
+    if !result.missing_items.is_empty() {
+        println!(
+            "\n{} Missing Items ({}):",
+            symbols::FIX,
+            result.missing_items.len()
+        );
+        for issue in &result.missing_items {
+            println!(
+                "  {} {}",
+                symbols::FAIL,
+                style::red(format!(
+                    "{} '{}' in {}",
+                    issue.item_type, issue.item_name, issue.file_path
+                ))
+            );
+        }
+    }
+
+    // Show likely renames
+    if !result.renamed_items.is_empty() {
+        println!(
+            "\n{} Renamed Items ({}):",
+            symbols::DIFF,
+            result.renamed_items.len()
+        );
+        for renamed in &result.renamed_items {
+            println!(
+                "  {} {}",
+                symbols::DIFF,
+                style::yellow(format!(
+                    "{} '{}' -> '{}' in {} ({:.0}% similar)",
+                    renamed.item_type,
+                    renamed.expected,
+                    renamed.found,
+                    renamed.file_path,
+                    renamed.similarity * 100.0
+                ))
+            );
+        }
+    }
+
+    // Show signature mismatches
+    if !result.signature_mismatches.is_empty() {
+        println!(
+            "\n{} Signature Mismatches ({}):",
+            symbols::SIGNATURE,
+            result.signature_mismatches.len()
+        );
+        for mismatch in &result.signature_mismatches {
+            println!(
+                "  {} {}",
+                symbols::FAIL,
+                style::red(format!(
+                    "{} in {}",
+                    mismatch.expected.name, mismatch.file_path
+                ))
+            );
+            println!("     expected: {}", style::green(&mismatch.expected));
+            println!("     found:    {}", style::red(&mismatch.found));
+        }
+    }
+
+    // Show per-file count mismatches
+    if !result.count_mismatches.is_empty() {
+        println!(
+            "\n{} Count Mismatches ({}):",
+            symbols::COUNT,
+            result.count_mismatches.len()
+        );
+        for mismatch in &result.count_mismatches {
+            println!(
+                "  {} {}",
+                symbols::FAIL,
+                style::red(format!(
+                    "{} count in {}: expected {}, found {}",
+                    mismatch.item_type, mismatch.file_path, mismatch.expected, mismatch.found
+                ))
+            );
+        }
+    }
+
+    // Show count expectations that weren't met
+    if !result.count_shortfalls.is_empty() {
+        println!(
+            "\n{} Unmet Count Expectations ({}):",
+            symbols::COUNT,
+            result.count_shortfalls.len()
+        );
+        for shortfall in &result.count_shortfalls {
+            println!(
+                "  {} {}",
+                symbols::FAIL,
+                style::red(format!(
+                    "{} (found {})",
+                    shortfall.expectation, shortfall.found
+                ))
+            );
+        }
+    }
+
+    // Show extra items
+    if !result.extra_items.is_empty() && result.extra_items.len() <= 10 {
+        println!(
+            "\n{} Extra Items ({}):",
+            symbols::FIX,
+            result.extra_items.len()
+        );
+        for issue in &result.extra_items {
+            println!(
+                "  {} {}",
+                symbols::ADDED,
+                style::yellow(format!(
+                    "{} '{}' in {}",
+                    issue.item_type, issue.item_name, issue.file_path
+                ))
+            );
+        }
+    } else if result.extra_items.len() > 10 {
+        println!(
+            "\n{} Extra Items ({}) - showing first 10:",
+            symbols::FIX,
+            result.extra_items.len()
+        );
+        for issue in result.extra_items.iter().take(10) {
+            println!(
+                "  {} {}",
+                symbols::ADDED,
+                style::yellow(format!(
+                    "{} '{}' in {}",
+                    issue.item_type, issue.item_name, issue.file_path
+                ))
+            );
+        }
+        println!("  ... and {} more", result.extra_items.len() - 10);
+    }
+
+    // Show suggestions
+    if !result.suggestions.is_empty() {
+        println!("\n{} Suggestions:", symbols::TIP);
+        for suggestion in &result.suggestions {
+            println!("  {} {}", symbols::BULLET, suggestion);
+        }
+    }
+
+    // Summary
+    println!("\n{} Summary:", symbols::STATS);
+    println!("  Missing files: {}", result.missing_files.len());
+    if !result.missing_directories.is_empty() {
+        println!(
+            "  Missing directories: {}",
+            result.missing_directories.len()
+        );
+    }
+    println!("  Extra files: {}", result.extra_files.len());
+    println!("  Missing items: {}", result.missing_items.len());
+    println!("  Extra items: {}", result.extra_items.len());
+    println!(
+        "  Signature mismatches: {}",
+        result.signature_mismatches.len()
+    );
+    if count_match {
+        println!("  Count mismatches: {}", result.count_mismatches.len());
+    }
+    if count_expectations {
+        println!(
+            "  Unmet count expectations: {}",
+            result.count_shortfalls.len()
+        );
+    }
+    if !result.category_counts.is_empty() {
+        println!(
+            "\n  {:<16}{:>8}{:>8}{:>8}",
+            "Category", "Matched", "Missing", "Extra"
+        );
+        for count in &result.category_counts {
+            println!(
+                "  {:<16}{:>8}{:>8}{:>8}",
+                count.item_type, count.matched, count.missing, count.extra
+            );
+        }
+        println!();
+    }
+
+    if result.is_valid {
+        println!(
+            "  {} {}",
+            symbols::SUCCESS,
+            style::green("Your codebase follows the scaff architecture!")
+        );
+    } else {
+        println!(
+            "  {} Consider addressing the missing files and items above.",
+            symbols::FIX
+        );
+    }
+}
+
+/// One line per scaff of a multi-scaff validation, then whether they all passed
+pub fn display_validation_summary(results: &[(String, Result<ValidationResult, ScaffError>)]) {
+    println!(
+        "\n{} Validation Summary ({} scaffs)",
+        symbols::STATS,
+        results.len()
+    );
+    println!("{:-<60}", "");
+
+    for (name, result) in results {
+        match result {
+            Ok(result) if result.is_valid => println!(
+                "{} {} ({:.0}% conformance)",
+                symbols::PASSED,
+                style::green(name),
+                result.conformance_score * 100.0
+            ),
+            Ok(result) => println!(
+                "{} {} ({:.0}% conformance)",
+                symbols::FAILED,
+                style::red(name),
+                result.conformance_score * 100.0
+            ),
+            Err(e) => println!("{} {}: {}", symbols::FAILED, style::red(name), e),
+        }
+    }
+
+    let failed = results
+        .iter()
+        .filter(|(_, result)| !result.as_ref().is_ok_and(|result| result.is_valid))
+        .count();
+    if failed == 0 {
+        println!("\n{} All {} scaffs passed", symbols::OK, results.len());
+    } else {
+        println!(
+            "\n{} {} of {} scaffs failed",
+            symbols::FAIL,
+            failed,
+            results.len()
+        );
+    }
+}
+
+/// What a generation wrote, or in a dry run would write: each file in a dry run, the
+/// diff of each differing file, then the totals
+pub fn display_generation(
+    summary: &GenerationSummary,
+    scaff_name: &str,
+    output_dir: &str,
+    dry_run: bool,
+    show_diff: bool,
+) {
+    for file in &summary.files {
+        if let Some(diff) = &file.diff {
+            print!("{}", diff);
+            continue;
+        }
+        let label = match file.status {
+            FileStatus::New => "new",
+            FileStatus::Existing => "overwrite",
+            FileStatus::Skipped => "exists, skip",
+            FileStatus::Differs | FileStatus::Unchanged => continue,
+        };
+        if dry_run {
+            println!("  {} {} ({})", symbols::FILE, file.path.display(), label);
+            if let Some(content) = &file.content {
+                println!("{}", content);
+            }
+        }
+    }
+
+    if dry_run {
+        println!(
+            "{} Dry run: would write {} new and overwrite {} existing file(s), skipping {}, from scaff '{}' to '{}'",
+            symbols::SEARCH,
+            summary.new_files.len(),
+            summary.existing_files.len(),
+            summary.skipped_files.len(),
+            scaff_name,
+            output_dir
+        );
+    } else {
+        println!(
+            "{} Successfully generated code from scaff '{}' to '{}' ({} new, {} overwritten, {} skipped)",
+            symbols::OK,
+            scaff_name,
+            output_dir,
+            summary.new_files.len(),
+            summary.existing_files.len(),
+            summary.skipped_files.len()
+        );
+    }
+    if show_diff {
+        println!(
+            "{} {} existing file(s) differ from the generated output, {} unchanged",
+            symbols::SEARCH,
+            summary.differing_files.len(),
+            summary.unchanged_files.len()
+        );
+    }
+    if summary.skipped_without_force(Path::new(output_dir)) {
+        println!(
+            "{} Use --force to overwrite files that already exist",
+            symbols::TIP
+        );
+    }
+}
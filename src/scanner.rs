@@ -1,14 +1,27 @@
-use crate::pattern::FilePattern;
+use crate::cache::ScanCache;
+use crate::pattern::{FilePattern, FunctionSignature, comparable_items};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use ignore::gitignore::GitignoreBuilder;
 use log::{debug, error, info, warn};
-use tree_sitter::{Node, Parser};
+use sha2::{Digest, Sha256};
+use tree_sitter::{Language, Node, Parser};
 
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub struct LanguageConfig {
+    /// Identifier used by `--language` and throughout the scanner, e.g. `javascript`
     pub name: &'static str,
+    /// Short forms `--language` also accepts, e.g. `js`
+    pub aliases: &'static [&'static str],
     pub extensions: &'static [&'static str],
+    /// Label stored as a scaff's `language`, e.g. `JavaScript`
     pub display_name: &'static str,
 }
 
@@ -16,52 +29,178 @@ pub struct LanguageConfig {
 pub const SUPPORTED_LANGUAGES: &[LanguageConfig] = &[
     LanguageConfig {
         name: "rust",
+        aliases: &[],
         extensions: &["rs"],
         display_name: "Rust",
     },
     LanguageConfig {
         name: "javascript",
+        aliases: &["js"],
         extensions: &["js", "jsx"],
         display_name: "JavaScript",
     },
     LanguageConfig {
         name: "typescript",
+        aliases: &["ts"],
         extensions: &["ts", "tsx"],
         display_name: "TypeScript",
     },
     LanguageConfig {
         name: "python",
+        aliases: &["py"],
         extensions: &["py", "pyi"],
         display_name: "Python",
     },
     LanguageConfig {
         name: "java",
+        aliases: &[],
         extensions: &["java"],
         display_name: "Java",
     },
     LanguageConfig {
         name: "go",
+        aliases: &[],
         extensions: &["go"],
         display_name: "Go",
     },
     LanguageConfig {
         name: "json",
+        aliases: &[],
         extensions: &["json"],
         display_name: "JSON",
     },
     LanguageConfig {
         name: "html",
+        aliases: &[],
         extensions: &["html", "htm"],
         display_name: "HTML",
     },
     LanguageConfig {
         name: "css",
+        aliases: &[],
         extensions: &["css"],
         display_name: "CSS",
     },
+    LanguageConfig {
+        name: "ruby",
+        aliases: &["rb"],
+        extensions: &["rb"],
+        display_name: "Ruby",
+    },
+    LanguageConfig {
+        name: "toml",
+        aliases: &[],
+        extensions: &["toml"],
+        display_name: "TOML",
+    },
+    LanguageConfig {
+        name: "yaml",
+        aliases: &["yml"],
+        extensions: &["yml", "yaml"],
+        display_name: "YAML",
+    },
+    LanguageConfig {
+        name: "markdown",
+        aliases: &["md"],
+        extensions: &["md", "markdown"],
+        display_name: "Markdown",
+    },
+    LanguageConfig {
+        name: "graphql",
+        aliases: &["gql"],
+        extensions: &["graphql", "gql"],
+        display_name: "GraphQL",
+    },
 ];
 
-// Legacy functions for backward compatibility
+// Data, markup and schema languages that accompany most codebases (manifests, docs,
+// config), left out when detecting the primary language
+const NON_PRIMARY_LANGUAGES: &[&str] =
+    &["json", "html", "css", "toml", "yaml", "markdown", "graphql"];
+
+/// Scaff-specific ignore file, in gitignore syntax, for paths that stay tracked in git
+/// but shouldn't be scaffed (e.g. generated protobuf code)
+pub const SCAFFIGNORE_FILE: &str = ".scaffignore";
+
+// Directories skipped by default even without a .gitignore entry
+pub const DEFAULT_IGNORED_DIRS: &[&str] = &["target", "node_modules", ".git", "dist", ".scaff"];
+
+/// Files larger than this are skipped unless configured otherwise (2 MiB)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Skip paths listed in the scan root's `.gitignore` and `.scaffignore` and
+    /// `DEFAULT_IGNORED_DIRS`
+    pub respect_ignore: bool,
+    /// Collapse whitespace before hashing file content, so reformatting keeps the hash
+    pub normalize_whitespace: bool,
+    /// Skip files larger than this many bytes, e.g. minified bundles or generated JSON
+    pub max_file_size: u64,
+    /// When set, only files whose path relative to the scan root matches are scanned
+    pub include: Option<GlobSet>,
+    /// Files whose path relative to the scan root matches are skipped
+    pub exclude: Option<GlobSet>,
+    /// How many directory levels to descend; 1 scans only the root's own files
+    pub max_depth: Option<usize>,
+    /// Descend into symlinked directories and scan symlinked files. The walker skips
+    /// links back to a directory it's already inside, so loops still terminate.
+    pub follow_symlinks: bool,
+    /// Reuse patterns of files unchanged since an earlier scan instead of parsing them
+    pub cache: Option<Rc<RefCell<ScanCache>>>,
+    /// Directory stored file paths are relative to, instead of the scan root (or the
+    /// current directory for explicit file lists, which are also filtered with its
+    /// ignore rules and globs)
+    pub base_path: Option<PathBuf>,
+    /// When set, directory scans only visit these files, given as the scan root joined
+    /// with their relative path (see `git::changed_files_since`)
+    pub restrict_to: Option<HashSet<PathBuf>>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            respect_ignore: true,
+            normalize_whitespace: false,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            include: None,
+            exclude: None,
+            max_depth: None,
+            follow_symlinks: false,
+            cache: None,
+            base_path: None,
+            restrict_to: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Write back the scan cache, if any. Failing to is logged, not fatal, since the
+    /// scan itself succeeded.
+    pub fn save_cache(&self) {
+        if let Some(cache) = &self.cache
+            && let Err(e) = cache.borrow_mut().save()
+        {
+            warn!("Could not save scan cache: {}", e);
+        }
+    }
+}
+
+/// Compile glob patterns once for `ScanOptions::include`/`exclude`; `None` when empty
+pub fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+// Legacy functions for backward compatibility; callers now pass `ScanOptions`
+#[cfg(test)]
 pub fn scan_js_ts_files_in_dir(dir: &str) -> Vec<FilePattern> {
     let mut results = Vec::new();
     results.extend(scan_language_files_in_dir(dir, "javascript"));
@@ -69,49 +208,131 @@ pub fn scan_js_ts_files_in_dir(dir: &str) -> Vec<FilePattern> {
     results
 }
 
+#[cfg(test)]
 pub fn scan_rust_files_in_dir(dir: &str) -> Vec<FilePattern> {
     scan_language_files_in_dir(dir, "rust")
 }
 
-// New unified language scanning function
+/// Scan `dir` for files of `language`, e.g. `rust`, with the default `ScanOptions`
 pub fn scan_language_files_in_dir(dir: &str, language: &str) -> Vec<FilePattern> {
+    scan_language_files_in_dir_with_options(dir, language, &ScanOptions::default())
+}
+
+pub fn scan_language_files_in_dir_with_options(
+    dir: &str,
+    language: &str,
+    options: &ScanOptions,
+) -> Vec<FilePattern> {
+    let mut file_patterns = Vec::new();
+    visit_language_files_in_dir(dir, language, options, &mut |file_pattern| {
+        file_patterns.push(file_pattern)
+    });
+    file_patterns
+}
+
+// Hand each file pattern to `visit` as soon as it's extracted, without collecting them
+pub fn visit_language_files_in_dir(
+    dir: &str,
+    language: &str,
+    options: &ScanOptions,
+    visit: &mut dyn FnMut(FilePattern),
+) {
     info!("Starting {} scan of directory: {}", language, dir);
 
-    let mut parser = Parser::new();
+    let Some(mut parser) = create_parser(language) else {
+        return;
+    };
 
-    let language_obj = match language {
-        "rust" => tree_sitter_rust::LANGUAGE.into(),
-        "javascript" => tree_sitter_javascript::LANGUAGE.into(),
-        "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
-        "python" => tree_sitter_python::LANGUAGE.into(),
-        "java" => tree_sitter_java::LANGUAGE.into(),
-        "go" => tree_sitter_go::LANGUAGE.into(),
-        "json" => tree_sitter_json::LANGUAGE.into(),
-        "html" => tree_sitter_html::LANGUAGE.into(),
-        "css" => tree_sitter_css::LANGUAGE.into(),
-        _ => {
-            error!("Unsupported language: {}", language);
-            return Vec::new();
-        }
+    scan_dir_recursive(Path::new(dir), &mut parser, language, options, visit);
+}
+
+// Stream scan results as a JSON array, serializing each file pattern as it's produced
+pub fn write_scan_json<W: Write>(
+    dir: &str,
+    languages: &[&str],
+    options: &ScanOptions,
+    writer: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut count = 0;
+    let mut write_error: Option<Box<dyn std::error::Error>> = None;
+
+    writer.write_all(b"[")?;
+    for language in languages {
+        visit_language_files_in_dir(dir, language, options, &mut |file_pattern| {
+            if write_error.is_some() {
+                return;
+            }
+            match write_json_array_element(writer, count, &file_pattern) {
+                Ok(()) => count += 1,
+                Err(e) => write_error = Some(e),
+            }
+        });
+    }
+    if let Some(e) = write_error {
+        return Err(e);
+    }
+    writer.write_all(b"]\n")?;
+    writer.flush()?;
+
+    Ok(count)
+}
+
+// Scan an explicit set of files instead of walking a directory
+pub fn scan_language_files(
+    paths: &[PathBuf],
+    language: &str,
+    options: &ScanOptions,
+) -> Vec<FilePattern> {
+    info!("Starting {} scan of {} files", language, paths.len());
+
+    let Some(mut parser) = create_parser(language) else {
+        return Vec::new();
     };
+    let base = options.base_path.as_deref().unwrap_or(Path::new("."));
 
-    match parser.set_language(&language_obj) {
-        Ok(_) => info!("Successfully loaded {} grammar", language),
-        Err(e) => {
-            error!("Failed to load {} grammar: {}", language, e);
-            return Vec::new();
+    paths
+        .iter()
+        .filter(|path| {
+            path.is_file()
+                && has_language_extension(path, language)
+                && passes_filters(path, base, options)
+        })
+        .filter_map(|path| {
+            let mut file_pattern = parse_file_cached(path, &mut parser, language, options)?;
+            file_pattern.path = stored_path(path, base);
+            Some(file_pattern)
+        })
+        .collect()
+}
+
+// Scan all supported languages, only loading grammars for languages that have files
+pub fn scan_all_languages_in_dir(
+    dir: &str,
+    options: &ScanOptions,
+) -> Vec<(String, Vec<FilePattern>)> {
+    let mut results = Vec::new();
+
+    for config in languages_in_dir(dir, options) {
+        let files = scan_language_files_in_dir_with_options(dir, config.name, options);
+        if !files.is_empty() {
+            results.push((config.display_name.to_string(), files));
         }
     }
 
-    scan_dir_recursive(Path::new(dir), &mut parser, language)
+    results
 }
 
-// Scan all supported languages
-pub fn scan_all_languages_in_dir(dir: &str) -> Vec<(String, Vec<FilePattern>)> {
+pub fn scan_all_languages_in_files(
+    paths: &[PathBuf],
+    options: &ScanOptions,
+) -> Vec<(String, Vec<FilePattern>)> {
     let mut results = Vec::new();
 
-    for config in SUPPORTED_LANGUAGES {
-        let files = scan_language_files_in_dir(dir, config.name);
+    let present = SUPPORTED_LANGUAGES
+        .iter()
+        .filter(|config| paths.iter().any(|p| has_language_extension(p, config.name)));
+    for config in present {
+        let files = scan_language_files(paths, config.name, options);
         if !files.is_empty() {
             results.push((config.display_name.to_string(), files));
         }
@@ -120,288 +341,949 @@ pub fn scan_all_languages_in_dir(dir: &str) -> Vec<(String, Vec<FilePattern>)> {
     results
 }
 
-fn scan_dir_recursive(path: &Path, parser: &mut Parser, language: &str) -> Vec<FilePattern> {
-    let mut file_patterns = Vec::new();
+// Languages with at least one matching file under `dir`, found by extension alone in a
+// single walk, in `SUPPORTED_LANGUAGES` order
+pub fn languages_in_dir(dir: &str, options: &ScanOptions) -> Vec<&'static LanguageConfig> {
+    let mut present = vec![false; SUPPORTED_LANGUAGES.len()];
+    let has_any_extension = |path: &Path| language_index(path).is_some();
+    walk_files(Path::new(dir), options, &has_any_extension, &mut |path| {
+        if let Some(index) = language_index(path) {
+            present[index] = true;
+        }
+    });
 
-    if path.is_dir() {
-        debug!("Scanning directory: {}", path.display());
-        let entries = match fs::read_dir(path) {
-            Ok(entries) => entries,
-            Err(e) => {
-                warn!("Could not read directory {}: {}", path.display(), e);
-                return file_patterns;
-            }
-        };
+    SUPPORTED_LANGUAGES
+        .iter()
+        .zip(present)
+        .filter_map(|(config, present)| present.then_some(config))
+        .collect()
+}
 
-        for entry in entries {
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(e) => {
-                    warn!("Could not get directory entry: {}", e);
-                    continue;
-                }
-            };
+/// The programming language with the most files under `dir` that pass `options`, e.g.
+/// `rust`, or None when no source files match or two languages tie for the most files
+pub fn detect_primary_language(dir: &str, options: &ScanOptions) -> Option<&'static str> {
+    let mut counts = vec![0usize; SUPPORTED_LANGUAGES.len()];
+    let is_candidate = |path: &Path| {
+        language_index(path)
+            .is_some_and(|index| !NON_PRIMARY_LANGUAGES.contains(&SUPPORTED_LANGUAGES[index].name))
+    };
+    walk_files(Path::new(dir), options, &is_candidate, &mut |path| {
+        if let Some(index) = language_index(path) {
+            counts[index] += 1;
+        }
+    });
 
-            let entry_path = entry.path();
-            if entry_path.is_dir() {
-                let mut sub_patterns = scan_dir_recursive(&entry_path, parser, language);
-                file_patterns.append(&mut sub_patterns);
-            } else if let Some(ext) = entry_path.extension() {
-                let ext_str = ext.to_string_lossy().to_string();
+    let most = *counts.iter().max()?;
+    let mut leaders = (0..counts.len()).filter(|&index| counts[index] == most);
+    match (leaders.next(), leaders.next()) {
+        (Some(index), None) if most > 0 => Some(SUPPORTED_LANGUAGES[index].name),
+        _ => None,
+    }
+}
 
-                let should_parse = SUPPORTED_LANGUAGES
-                    .iter()
-                    .find(|config| config.name == language)
-                    .map(|config| config.extensions.contains(&ext_str.as_str()))
-                    .unwrap_or(false);
-
-                if should_parse {
-                    debug!("Found {} file: {}", language, entry_path.display());
-                    let content = match fs::read_to_string(&entry_path) {
-                        Ok(content) => content,
-                        Err(e) => {
-                            error!("Could not read file {}: {}", entry_path.display(), e);
-                            continue;
-                        }
-                    };
-
-                    match parser.parse(&content, None) {
-                        Some(tree) => {
-                            info!("Successfully parsed: {}", entry_path.display());
-                            let file_pattern = extract_file_pattern(
-                                tree.root_node(),
-                                &content,
-                                &entry_path,
-                                language,
-                            );
-                            file_patterns.push(file_pattern);
-                        }
-                        None => {
-                            error!("Failed to parse {}", entry_path.display());
-                        }
-                    }
-                }
+fn language_index(path: &Path) -> Option<usize> {
+    let ext = path.extension()?.to_string_lossy();
+    SUPPORTED_LANGUAGES
+        .iter()
+        .position(|config| config.extensions.contains(&ext.as_ref()))
+}
+
+pub fn grammar_for(language: &str) -> Option<Language> {
+    let language_obj = match language {
+        "rust" => tree_sitter_rust::LANGUAGE.into(),
+        "javascript" => tree_sitter_javascript::LANGUAGE.into(),
+        "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        "python" => tree_sitter_python::LANGUAGE.into(),
+        "java" => tree_sitter_java::LANGUAGE.into(),
+        "go" => tree_sitter_go::LANGUAGE.into(),
+        "json" => tree_sitter_json::LANGUAGE.into(),
+        "html" => tree_sitter_html::LANGUAGE.into(),
+        "css" => tree_sitter_css::LANGUAGE.into(),
+        "ruby" => tree_sitter_ruby::LANGUAGE.into(),
+        "toml" => tree_sitter_toml_ng::LANGUAGE.into(),
+        "yaml" => tree_sitter_yaml::LANGUAGE.into(),
+        "markdown" => tree_sitter_md::LANGUAGE.into(),
+        "graphql" => tree_sitter_graphql::LANGUAGE.into(),
+        _ => return None,
+    };
+    Some(language_obj)
+}
+
+fn write_json_array_element<W: Write>(
+    writer: &mut W,
+    index: usize,
+    file_pattern: &FilePattern,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if index > 0 {
+        writer.write_all(b",")?;
+    }
+    serde_json::to_writer(writer, file_pattern)?;
+    Ok(())
+}
+
+// Collect the entry file plus every file reachable from it through Rust `mod`/`use crate::`
+// declarations or relative JS/TS imports
+pub fn reachable_files(entry: &Path) -> Vec<PathBuf> {
+    let entry = if entry.is_relative() && !entry.starts_with(".") {
+        Path::new(".").join(entry)
+    } else {
+        entry.to_path_buf()
+    };
+    let crate_root = entry.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+    let mut visited: Vec<PathBuf> = Vec::new();
+    let mut queue = vec![entry];
+
+    while let Some(file) = queue.pop() {
+        if visited.contains(&file) || !file.is_file() {
+            continue;
+        }
+        debug!("Following imports from {}", file.display());
+
+        for import in file_imports(&file, &crate_root) {
+            if !visited.contains(&import) {
+                queue.push(import);
             }
         }
+        visited.push(file);
     }
 
-    file_patterns
+    info!("Found {} files reachable from the entry", visited.len());
+    visited
 }
 
-fn extract_file_pattern(root: Node, source: &str, file_path: &Path, language: &str) -> FilePattern {
-    let mut cursor = root.walk();
-    let mut classes = Vec::new();
-    let mut functions = Vec::new();
-    let mut structs = Vec::new();
-    let mut implementations = Vec::new();
+fn file_imports(file: &Path, crate_root: &Path) -> Vec<PathBuf> {
+    let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let language = match ext {
+        "rs" => "rust",
+        "js" | "jsx" => "javascript",
+        "ts" | "tsx" => "typescript",
+        _ => return Vec::new(),
+    };
 
-    for child in root.children(&mut cursor) {
-        extract_from_node(
-            child,
-            source,
-            language,
-            &mut classes,
-            &mut functions,
-            &mut structs,
-            &mut implementations,
-        );
-    }
+    let Some(mut parser) = create_parser(language) else {
+        return Vec::new();
+    };
+    let Ok(content) = fs::read_to_string(file) else {
+        warn!("Could not read file {}", file.display());
+        return Vec::new();
+    };
+    let Some(tree) = parser.parse(&content, None) else {
+        return Vec::new();
+    };
 
-    FilePattern {
-        path: file_path.to_string_lossy().to_string(),
-        extension: file_path
-            .extension()
-            .and_then(|s| s.to_str())
-            .unwrap_or("")
-            .to_string(),
-        classes,
-        functions,
-        structs,
-        implementations,
-    }
+    let mut imports = Vec::new();
+    collect_imports(tree.root_node(), &content, file, crate_root, &mut imports);
+    imports
 }
 
-fn extract_from_node(
+fn collect_imports(
     node: Node,
     source: &str,
-    language: &str,
-    classes: &mut Vec<String>,
-    functions: &mut Vec<String>,
-    structs: &mut Vec<String>,
-    implementations: &mut Vec<String>,
+    file: &Path,
+    crate_root: &Path,
+    imports: &mut Vec<PathBuf>,
 ) {
-    match (node.kind(), language) {
-        // Rust
-        ("struct_item", "rust") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    structs.push(name_str.to_string());
-                    debug!("Found Rust struct: {}", name_str);
-                }
+    match node.kind() {
+        // `mod foo;` without a body lives in a sibling file
+        "mod_item" if node.child_by_field_name("body").is_none() => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                imports.extend(resolve_rust_module(&rust_module_dir(file), &[name_str]));
             }
         }
-        ("fn_item", "rust") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found Rust function: {}", name_str);
-                }
+        "use_declaration" => {
+            if let Some(argument) = node.child_by_field_name("argument")
+                && let Ok(text) = argument.utf8_text(source.as_bytes())
+                && let Some(path) = text.strip_prefix("crate::")
+            {
+                let segments: Vec<&str> = path
+                    .split("::")
+                    .take_while(|segment| !segment.starts_with('{') && *segment != "*")
+                    .collect();
+                imports.extend(resolve_rust_module(crate_root, &segments));
             }
         }
-        ("impl_item", "rust") => {
-            if let Some(type_node) = node.child_by_field_name("type") {
-                if let Ok(name_str) = type_node.utf8_text(source.as_bytes()) {
-                    implementations.push(name_str.to_string());
-                    debug!("Found Rust impl: {}", name_str);
+        "import_statement" | "export_statement" => {
+            if let Some(source_node) = node.child_by_field_name("source")
+                && let Ok(text) = source_node.utf8_text(source.as_bytes())
+            {
+                let specifier = text.trim_matches(|c| c == '"' || c == '\'' || c == '`');
+                if specifier.starts_with('.') {
+                    let base = file.parent().unwrap_or(Path::new("."));
+                    imports.extend(resolve_js_module(&base.join(specifier)));
                 }
             }
         }
+        _ => {}
+    }
 
-        // JavaScript
-        ("class_declaration", "javascript") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    classes.push(name_str.to_string());
-                    debug!("Found JavaScript class: {}", name_str);
-                }
-            }
-        }
-        ("function_declaration", "javascript") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found JavaScript function: {}", name_str);
-                }
-            }
-        }
-        ("method_definition", "javascript") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found JavaScript method: {}", name_str);
-                }
-            }
+    for child in node.children(&mut node.walk()) {
+        collect_imports(child, source, file, crate_root, imports);
+    }
+}
+
+// Directory that holds the child modules declared in `file`
+fn rust_module_dir(file: &Path) -> PathBuf {
+    let parent = file.parent().unwrap_or(Path::new("."));
+    match file.file_name().and_then(|n| n.to_str()) {
+        Some("main.rs") | Some("lib.rs") | Some("mod.rs") => parent.to_path_buf(),
+        _ => parent.join(file.file_stem().unwrap_or_default()),
+    }
+}
+
+// Resolve the longest prefix of `segments` that maps to a module file
+fn resolve_rust_module(base: &Path, segments: &[&str]) -> Option<PathBuf> {
+    for len in (1..=segments.len()).rev() {
+        let module_path: PathBuf = segments[..len].iter().collect();
+        let candidates = [
+            base.join(&module_path).with_extension("rs"),
+            base.join(&module_path).join("mod.rs"),
+        ];
+        if let Some(found) = candidates.into_iter().find(|c| c.is_file()) {
+            return Some(found);
         }
+    }
+    None
+}
 
-        // TypeScript (similar to JavaScript with additional constructs)
-        ("class_declaration", "typescript") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    classes.push(name_str.to_string());
-                    debug!("Found TypeScript class: {}", name_str);
-                }
-            }
+fn resolve_js_module(target: &Path) -> Option<PathBuf> {
+    if target.is_file() {
+        return Some(target.to_path_buf());
+    }
+    for ext in ["js", "jsx", "ts", "tsx"] {
+        let with_ext = PathBuf::from(format!("{}.{}", target.display(), ext));
+        if with_ext.is_file() {
+            return Some(with_ext);
         }
-        ("function_declaration", "typescript") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found TypeScript function: {}", name_str);
-                }
-            }
+        let index = target.join(format!("index.{}", ext));
+        if index.is_file() {
+            return Some(index);
         }
-        ("method_definition", "typescript") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found TypeScript method: {}", name_str);
-                }
-            }
+    }
+    None
+}
+
+fn create_parser(language: &str) -> Option<Parser> {
+    let mut parser = Parser::new();
+
+    let Some(language_obj) = grammar_for(language) else {
+        error!("Unsupported language: {}", language);
+        return None;
+    };
+
+    match parser.set_language(&language_obj) {
+        Ok(_) => info!("Successfully loaded {} grammar", language),
+        Err(e) => {
+            error!("Failed to load {} grammar: {}", language, e);
+            return None;
         }
-        ("interface_declaration", "typescript") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    classes.push(format!("interface {}", name_str));
-                    debug!("Found TypeScript interface: {}", name_str);
-                }
-            }
+    }
+
+    Some(parser)
+}
+
+/// Whether a file found outside a directory walk, e.g. named with `--file` or reached
+/// with `--follow-imports`, passes the filters a walk of `root` applies: the ignore
+/// rules, the include/exclude globs (matched against the path relative to `root`) and
+/// the size limit
+pub fn passes_filters(path: &Path, root: &Path, options: &ScanOptions) -> bool {
+    let relative_path = path.strip_prefix(root).unwrap_or(path);
+    !(options.respect_ignore && is_ignored(relative_path, root))
+        && matches_globs(relative_path, options)
+        && !exceeds_max_file_size(path, options)
+}
+
+// Whether a walk of `root` would leave out the file: it's under one of
+// `DEFAULT_IGNORED_DIRS`, or `root`'s `.gitignore` lists it and its `.scaffignore`
+// doesn't re-include it (the later file's matches win, as in a walk)
+fn is_ignored(relative_path: &Path, root: &Path) -> bool {
+    if relative_path.has_root() {
+        return false;
+    }
+    let in_ignored_dir = relative_path.parent().is_some_and(|dir| {
+        dir.components()
+            .any(|c| DEFAULT_IGNORED_DIRS.contains(&c.as_os_str().to_string_lossy().as_ref()))
+    });
+    if in_ignored_dir {
+        debug!(
+            "Skipping {} in an ignored directory",
+            relative_path.display()
+        );
+        return true;
+    }
+
+    let mut builder = GitignoreBuilder::new(root);
+    for file in [".gitignore", SCAFFIGNORE_FILE] {
+        let path = root.join(file);
+        if path.is_file()
+            && let Some(e) = builder.add(&path)
+        {
+            warn!("Could not read {}: {}", path.display(), e);
         }
+    }
+    let ignored = builder.build().is_ok_and(|ignore| {
+        ignore
+            .matched_path_or_any_parents(relative_path, false)
+            .is_ignore()
+    });
+    if ignored {
+        debug!("Skipping ignored file {}", relative_path.display());
+    }
+    ignored
+}
 
-        // Python
-        ("class_definition", "python") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    classes.push(name_str.to_string());
-                    debug!("Found Python class: {}", name_str);
-                }
-            }
+// A file must match at least one include (if any are given) and no exclude
+fn matches_globs(relative_path: &Path, options: &ScanOptions) -> bool {
+    let included = options
+        .include
+        .as_ref()
+        .is_none_or(|include| include.is_match(relative_path));
+    let excluded = options
+        .exclude
+        .as_ref()
+        .is_some_and(|exclude| exclude.is_match(relative_path));
+    included && !excluded
+}
+
+fn exceeds_max_file_size(path: &Path, options: &ScanOptions) -> bool {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.len() > options.max_file_size => {
+            warn!(
+                "Skipping {} ({} bytes exceeds the {} byte limit)",
+                path.display(),
+                metadata.len(),
+                options.max_file_size
+            );
+            true
         }
-        ("function_definition", "python") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found Python function: {}", name_str);
-                }
+        _ => false,
+    }
+}
+
+fn has_language_extension(path: &Path, language: &str) -> bool {
+    let Some(ext) = path.extension() else {
+        return false;
+    };
+    let ext_str = ext.to_string_lossy().to_string();
+
+    SUPPORTED_LANGUAGES
+        .iter()
+        .find(|config| config.name == language)
+        .map(|config| config.extensions.contains(&ext_str.as_str()))
+        .unwrap_or(false)
+}
+
+/// Parse one file as `language`, whatever its extension, without walking a directory,
+/// with the same `options` a directory scan would use. Filters aren't applied; check
+/// [`passes_filters`] first. None when the language is unsupported or the file can't
+/// be read.
+pub fn scan_file(path: &Path, language: &str, options: &ScanOptions) -> Option<FilePattern> {
+    let mut parser = create_parser(language)?;
+    let mut file_pattern = parse_file_cached(path, &mut parser, language, options)?;
+    let base = options.base_path.as_deref().unwrap_or(Path::new("."));
+    file_pattern.path = stored_path(path, base);
+    Some(file_pattern)
+}
+
+/// Parse `content` as `language`, e.g. piped from an editor, reporting it under `path`
+pub fn scan_source(
+    content: &str,
+    path: &Path,
+    language: &str,
+    options: &ScanOptions,
+) -> Option<FilePattern> {
+    let mut parser = create_parser(language)?;
+    Some(parse_source(content, path, &mut parser, language, options))
+}
+
+/// The supported language a file's extension belongs to, e.g. `rust` for `lib.rs`
+pub fn language_for_path(path: &Path) -> Option<&'static str> {
+    language_index(path).map(|index| SUPPORTED_LANGUAGES[index].name)
+}
+
+// Read and parse one file; every scan, directory walks included, goes through here
+fn parse_file(
+    path: &Path,
+    parser: &mut Parser,
+    language: &str,
+    options: &ScanOptions,
+) -> Option<FilePattern> {
+    debug!("Found {} file: {}", language, path.display());
+    let content = match fs::read(path) {
+        Ok(bytes) => match String::from_utf8(bytes) {
+            Ok(content) => content,
+            // Legacy encodings like Latin-1 still parse on a best-effort basis. The
+            // lossy text is what gets parsed, so node byte ranges index into it.
+            Err(e) => {
+                warn!(
+                    "{} is not valid UTF-8, replacing invalid bytes",
+                    path.display()
+                );
+                String::from_utf8_lossy(e.as_bytes()).into_owned()
             }
+        },
+        Err(e) => {
+            error!("Could not read file {}: {}", path.display(), e);
+            return None;
         }
+    };
 
-        // Java
-        ("class_declaration", "java") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    classes.push(name_str.to_string());
-                    debug!("Found Java class: {}", name_str);
-                }
+    Some(parse_source(&content, path, parser, language, options))
+}
+
+fn parse_source(
+    content: &str,
+    path: &Path,
+    parser: &mut Parser,
+    language: &str,
+    options: &ScanOptions,
+) -> FilePattern {
+    match parser.parse(content, None) {
+        Some(tree) => {
+            info!("Successfully parsed: {}", path.display());
+            let mut file_pattern = extract_file_pattern(tree.root_node(), content, path, language);
+            file_pattern.content_hash = Some(content_hash(content, options.normalize_whitespace));
+            file_pattern.structure_hash = Some(structure_hash(&file_pattern));
+            if tree.root_node().has_error() {
+                warn!("Parse errors in {}", path.display());
+                file_pattern.parse_errors = true;
             }
+            file_pattern
         }
-        ("method_declaration", "java") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found Java method: {}", name_str);
-                }
+        None => {
+            // Keep the file so the failure shows up in the scan results
+            error!("Failed to parse {}", path.display());
+            FilePattern {
+                parse_errors: true,
+                ..empty_file_pattern(path, language)
             }
         }
-        ("interface_declaration", "java") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    classes.push(format!("interface {}", name_str));
-                    debug!("Found Java interface: {}", name_str);
+    }
+}
+
+// List the files a directory scan would parse, without parsing them
+pub fn matching_files_in_dir(dir: &str, language: &str, options: &ScanOptions) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    walk_language_files(Path::new(dir), language, options, &mut |path| {
+        files.push(path.to_path_buf())
+    });
+    files
+}
+
+fn scan_dir_recursive(
+    path: &Path,
+    parser: &mut Parser,
+    language: &str,
+    options: &ScanOptions,
+    visit: &mut dyn FnMut(FilePattern),
+) {
+    let base = options.base_path.as_deref().unwrap_or(path);
+    walk_language_files(path, language, options, &mut |entry_path| {
+        if let Some(mut file_pattern) = parse_file_cached(entry_path, parser, language, options) {
+            file_pattern.path = stored_path(entry_path, base);
+            visit(file_pattern);
+        }
+    });
+}
+
+/// `path` as stored in a scaff: relative to `base` without a leading `./`, so scaffs
+/// saved from an absolute or another directory match on any machine. Paths outside
+/// `base` are kept as they are.
+pub fn stored_path(path: &Path, base: &Path) -> String {
+    let relative = path
+        .strip_prefix(base)
+        .ok()
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            // e.g. `src/lib.rs` under `.`, or an absolute path under a relative base
+            let path = std::path::absolute(path).ok()?;
+            let base = std::path::absolute(base).ok()?;
+            path.strip_prefix(base).ok().map(Path::to_path_buf)
+        });
+
+    match relative {
+        Some(relative) if !relative.as_os_str().is_empty() => {
+            relative.to_string_lossy().into_owned()
+        }
+        _ => {
+            let components = path.components().skip_while(|c| *c == Component::CurDir);
+            components
+                .collect::<PathBuf>()
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+}
+
+// Parse a file unless the scan cache has it from an earlier, unchanged version
+fn parse_file_cached(
+    path: &Path,
+    parser: &mut Parser,
+    language: &str,
+    options: &ScanOptions,
+) -> Option<FilePattern> {
+    let Some(cache) = &options.cache else {
+        return parse_file(path, parser, language, options);
+    };
+
+    if let Some(file_pattern) = cache
+        .borrow()
+        .get(path, language, options.normalize_whitespace)
+    {
+        debug!("Using cached scan of {}", path.display());
+        return Some(file_pattern);
+    }
+
+    let file_pattern = parse_file(path, parser, language, options)?;
+    cache
+        .borrow_mut()
+        .insert(path, language, options.normalize_whitespace, &file_pattern);
+    Some(file_pattern)
+}
+
+fn walk_language_files(
+    path: &Path,
+    language: &str,
+    options: &ScanOptions,
+    visit: &mut dyn FnMut(&Path),
+) {
+    let has_extension = |entry_path: &Path| has_language_extension(entry_path, language);
+    walk_files(path, options, &has_extension, visit);
+}
+
+// Visit files accepted by `matches` (checked first, so it should be cheap) that also
+// pass the glob and size filters
+fn walk_files(
+    path: &Path,
+    options: &ScanOptions,
+    matches: &dyn Fn(&Path) -> bool,
+    visit: &mut dyn FnMut(&Path),
+) {
+    if !path.is_dir() {
+        return;
+    }
+    debug!("Scanning directory: {}", path.display());
+
+    let mut walker = WalkBuilder::new(path);
+    walker
+        .standard_filters(false)
+        .hidden(false)
+        .follow_links(options.follow_symlinks)
+        .max_depth(options.max_depth);
+
+    if options.respect_ignore {
+        // `.scaffignore` takes precedence, so its `!pattern`s re-include gitignored files
+        walker
+            .git_ignore(true)
+            .require_git(false)
+            .add_custom_ignore_filename(SCAFFIGNORE_FILE);
+        walker.filter_entry(|entry| {
+            let is_ignored_dir = entry.depth() > 0
+                && entry.file_type().is_some_and(|t| t.is_dir())
+                && DEFAULT_IGNORED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref());
+            if is_ignored_dir {
+                debug!("Skipping ignored directory: {}", entry.path().display());
+            }
+            !is_ignored_dir
+        });
+    }
+
+    for entry in walker.build() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("Could not get directory entry: {}", e);
+                continue;
+            }
+        };
+
+        let entry_path = entry.path();
+        if entry.file_type().is_some_and(|t| t.is_file())
+            && matches(entry_path)
+            && options
+                .restrict_to
+                .as_ref()
+                .is_none_or(|files| files.contains(entry_path))
+            && matches_globs(entry_path.strip_prefix(path).unwrap_or(entry_path), options)
+            && !exceeds_max_file_size(entry_path, options)
+        {
+            visit(entry_path);
+        }
+    }
+}
+
+/// Hex SHA-256 of a file's content. With `normalize_whitespace`, runs of whitespace
+/// (including line breaks) collapse to a single space and leading/trailing
+/// whitespace is dropped first, so only non-whitespace changes alter the hash.
+pub fn content_hash(content: &str, normalize_whitespace: bool) -> String {
+    let digest = if normalize_whitespace {
+        let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ");
+        Sha256::digest(normalized.as_bytes())
+    } else {
+        Sha256::digest(content.as_bytes())
+    };
+    format!("{:x}", digest)
+}
+
+/// Hex SHA-256 of a file's extracted items as sorted `type:name` lines, over the
+/// categories validation compares. Order in the source doesn't matter, so it only
+/// changes when items are added, removed or renamed.
+pub fn structure_hash(file: &FilePattern) -> String {
+    let items: BTreeSet<String> = comparable_items(file)
+        .into_iter()
+        .flat_map(|(item_type, names)| {
+            names
+                .into_iter()
+                .map(move |name| format!("{}:{}", item_type, name))
+        })
+        .collect();
+
+    let mut hasher = Sha256::new();
+    for item in items {
+        hasher.update(item.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn empty_file_pattern(file_path: &Path, language: &str) -> FilePattern {
+    FilePattern {
+        path: file_path.to_string_lossy().to_string(),
+        extension: file_path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string(),
+        language: Some(get_language_display_name(language)),
+        ..Default::default()
+    }
+}
+
+fn extract_file_pattern(root: Node, source: &str, file_path: &Path, language: &str) -> FilePattern {
+    let mut cursor = root.walk();
+    let mut pattern = empty_file_pattern(file_path, language);
+
+    for child in root.children(&mut cursor) {
+        extract_from_node(child, source, language, "", &mut pattern);
+    }
+
+    for items in [
+        &mut pattern.classes,
+        &mut pattern.functions,
+        &mut pattern.structs,
+        &mut pattern.implementations,
+        &mut pattern.enums,
+        &mut pattern.traits,
+    ] {
+        dedup_items(items);
+    }
+
+    pattern
+}
+
+// Drop repeated names, e.g. Go methods of the same name on different receivers,
+// keeping the first occurrence of each
+fn dedup_items(items: &mut Vec<String>) {
+    let mut seen = HashSet::new();
+    items.retain(|item| seen.insert(item.clone()));
+}
+
+fn extract_from_node(
+    node: Node,
+    source: &str,
+    language: &str,
+    module: &str,
+    pattern: &mut FilePattern,
+) {
+    match (node.kind(), language) {
+        // Rust
+        ("struct_item", "rust") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                let name_str = &qualify(module, name_str);
+                pattern.structs.push(name_str.to_string());
+                record_rust_attributes(node, source, name_str, pattern);
+                debug!("Found Rust struct: {}", name_str);
+
+                // Tuple structs have an ordered_field_declaration_list without names
+                let fields: Vec<String> = node
+                    .child_by_field_name("body")
+                    .filter(|body| body.kind() == "field_declaration_list")
+                    .map(|body| {
+                        body.named_children(&mut body.walk())
+                            .filter(|child| child.kind() == "field_declaration")
+                            .filter_map(|child| child.child_by_field_name("name"))
+                            .filter_map(|name| name.utf8_text(source.as_bytes()).ok())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                pattern.struct_fields.insert(name_str.to_string(), fields);
+            }
+        }
+        ("function_item", "rust") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                let name_str = &qualify(module, name_str);
+                pattern.functions.push(name_str.to_string());
+                pattern
+                    .signatures
+                    .push(function_signature(node, source, name_str));
+                record_rust_attributes(node, source, name_str, pattern);
+                debug!("Found Rust function: {}", name_str);
+            }
+        }
+        ("enum_item", "rust") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                let name_str = &qualify(module, name_str);
+                pattern.enums.push(name_str.to_string());
+                record_rust_attributes(node, source, name_str, pattern);
+                debug!("Found Rust enum: {}", name_str);
+            }
+        }
+        ("trait_item", "rust") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                let name_str = &qualify(module, name_str);
+                pattern.traits.push(name_str.to_string());
+                record_rust_attributes(node, source, name_str, pattern);
+                debug!("Found Rust trait: {}", name_str);
+            }
+        }
+        // Items of inline `mod name { ... }` blocks are qualified with the module path,
+        // so `a::Config` and `b::Config` stay distinct
+        ("mod_item", "rust") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+                && let Some(body) = node.child_by_field_name("body")
+            {
+                let module = qualify(module, name_str);
+                debug!("Found Rust module: {}", module);
+                for child in body.children(&mut body.walk()) {
+                    extract_from_node(child, source, language, &module, pattern);
+                }
+            }
+            return;
+        }
+        ("impl_item", "rust") => {
+            if let Some(type_node) = node.child_by_field_name("type")
+                && let Ok(name_str) = type_node.utf8_text(source.as_bytes())
+            {
+                let name_str = &qualify(module, name_str);
+                pattern.implementations.push(name_str.to_string());
+                record_rust_attributes(node, source, name_str, pattern);
+                debug!("Found Rust impl: {}", name_str);
+
+                if let Some(body) = node.child_by_field_name("body") {
+                    let methods: Vec<String> = body
+                        .named_children(&mut body.walk())
+                        .filter(|child| child.kind() == "function_item")
+                        .filter_map(|child| child.child_by_field_name("name"))
+                        .filter_map(|name| name.utf8_text(source.as_bytes()).ok())
+                        .map(str::to_string)
+                        .collect();
+                    pattern
+                        .impl_methods
+                        .entry(name_str.to_string())
+                        .or_default()
+                        .extend(methods);
+                }
+            }
+        }
+
+        // JavaScript
+        ("class_declaration", "javascript") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(name_str.to_string());
+                debug!("Found JavaScript class: {}", name_str);
+            }
+        }
+        ("function_declaration", "javascript") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                debug!("Found JavaScript function: {}", name_str);
+            }
+        }
+        ("method_definition", "javascript") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                debug!("Found JavaScript method: {}", name_str);
+
+                let modifiers = method_modifiers(node);
+                if !modifiers.is_empty() {
+                    pattern.metadata.insert(name_str.to_string(), modifiers);
+                }
+            }
+        }
+
+        // TypeScript (similar to JavaScript with additional constructs)
+        ("class_declaration", "typescript") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(name_str.to_string());
+                record_decorators(node, source, name_str, pattern);
+                debug!("Found TypeScript class: {}", name_str);
+            }
+        }
+        ("function_declaration", "typescript") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                pattern
+                    .signatures
+                    .push(function_signature(node, source, name_str));
+                debug!("Found TypeScript function: {}", name_str);
+            }
+        }
+        ("method_definition", "typescript") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                pattern
+                    .signatures
+                    .push(function_signature(node, source, name_str));
+                debug!("Found TypeScript method: {}", name_str);
+
+                let modifiers = method_modifiers(node);
+                if !modifiers.is_empty() {
+                    pattern.metadata.insert(name_str.to_string(), modifiers);
                 }
             }
         }
+        ("interface_declaration", "typescript") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(format!("interface {}", name_str));
+                debug!("Found TypeScript interface: {}", name_str);
+            }
+        }
+
+        // Python
+        ("class_definition", "python") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(name_str.to_string());
+                record_decorators(node, source, name_str, pattern);
+                debug!("Found Python class: {}", name_str);
+            }
+        }
+        ("function_definition", "python") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                record_decorators(node, source, name_str, pattern);
+                debug!("Found Python function: {}", name_str);
+            }
+        }
+
+        // Java
+        ("class_declaration", "java") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(name_str.to_string());
+                debug!("Found Java class: {}", name_str);
+            }
+        }
+        ("method_declaration", "java") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                debug!("Found Java method: {}", name_str);
+            }
+        }
+        ("interface_declaration", "java") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(format!("interface {}", name_str));
+                debug!("Found Java interface: {}", name_str);
+            }
+        }
 
         // Go
         ("type_declaration", "go") => {
             for child in node.children(&mut node.walk()) {
-                if child.kind() == "type_spec" {
-                    if let Some(name) = child.child_by_field_name("name") {
-                        if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                            structs.push(name_str.to_string());
-                            debug!("Found Go type: {}", name_str);
-                        }
-                    }
+                if child.kind() == "type_spec"
+                    && let Some(name) = child.child_by_field_name("name")
+                    && let Ok(name_str) = name.utf8_text(source.as_bytes())
+                {
+                    pattern.structs.push(name_str.to_string());
+                    debug!("Found Go type: {}", name_str);
                 }
             }
         }
         ("function_declaration", "go") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found Go function: {}", name_str);
-                }
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                debug!("Found Go function: {}", name_str);
             }
         }
         ("method_declaration", "go") => {
-            if let Some(name) = node.child_by_field_name("name") {
-                if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                    functions.push(name_str.to_string());
-                    debug!("Found Go method: {}", name_str);
-                }
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                debug!("Found Go method: {}", name_str);
+            }
+        }
+
+        // Ruby (modules are prefixed like TypeScript interfaces)
+        ("class", "ruby") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(name_str.to_string());
+                debug!("Found Ruby class: {}", name_str);
+            }
+        }
+        ("module", "ruby") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(format!("module {}", name_str));
+                debug!("Found Ruby module: {}", name_str);
+            }
+        }
+        ("method", "ruby") | ("singleton_method", "ruby") => {
+            if let Some(name) = node.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.functions.push(name_str.to_string());
+                debug!("Found Ruby method: {}", name_str);
             }
         }
 
         // HTML (extract element types as "classes" for structural analysis)
         ("element", "html") => {
-            if let Some(start_tag) = node.child_by_field_name("start_tag") {
-                if let Some(name) = start_tag.child_by_field_name("name") {
-                    if let Ok(name_str) = name.utf8_text(source.as_bytes()) {
-                        if !classes.contains(&name_str.to_string()) {
-                            classes.push(name_str.to_string());
-                            debug!("Found HTML element: {}", name_str);
-                        }
-                    }
-                }
+            if let Some(start_tag) = node.child_by_field_name("start_tag")
+                && let Some(name) = start_tag.child_by_field_name("name")
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.classes.push(name_str.to_string());
+                debug!("Found HTML element: {}", name_str);
             }
         }
 
@@ -410,13 +1292,11 @@ fn extract_from_node(
             for child in node.children(&mut node.walk()) {
                 if child.kind() == "selectors" {
                     for selector_child in child.children(&mut child.walk()) {
-                        if let Ok(selector_text) = selector_child.utf8_text(source.as_bytes()) {
-                            if !selector_text.trim().is_empty()
-                                && !classes.contains(&selector_text.trim().to_string())
-                            {
-                                classes.push(selector_text.trim().to_string());
-                                debug!("Found CSS selector: {}", selector_text.trim());
-                            }
+                        if let Ok(selector_text) = selector_child.utf8_text(source.as_bytes())
+                            && !selector_text.trim().is_empty()
+                        {
+                            pattern.classes.push(selector_text.trim().to_string());
+                            debug!("Found CSS selector: {}", selector_text.trim());
                         }
                     }
                 }
@@ -425,108 +1305,268 @@ fn extract_from_node(
 
         // JSON (for structural analysis, we could extract top-level keys)
         ("pair", "json") => {
-            if let Some(key) = node.child_by_field_name("key") {
-                if let Ok(key_str) = key.utf8_text(source.as_bytes()) {
-                    if !structs.contains(&key_str.to_string()) {
-                        structs.push(key_str.to_string());
-                        debug!("Found JSON key: {}", key_str);
+            if let Some(key) = node.child_by_field_name("key")
+                && let Ok(key_str) = key.utf8_text(source.as_bytes())
+            {
+                pattern.structs.push(key_str.to_string());
+                debug!("Found JSON key: {}", key_str);
+            }
+        }
+
+        // TOML: tables like `[dependencies]` or `[tool.poetry]`, and top-level keys
+        ("table" | "table_array_element", "toml") => {
+            if let Some(name) = toml_key(node)
+                && let Ok(name_str) = name.utf8_text(source.as_bytes())
+            {
+                pattern.structs.push(name_str.to_string());
+                debug!("Found TOML table: {}", name_str);
+            }
+        }
+        ("pair", "toml") if node.parent().is_some_and(|p| p.kind() == "document") => {
+            if let Some(key) = toml_key(node)
+                && let Ok(key_str) = key.utf8_text(source.as_bytes())
+            {
+                pattern.structs.push(key_str.to_string());
+                debug!("Found TOML key: {}", key_str);
+            }
+        }
+
+        // YAML: keys of each document's top-level mapping, e.g. `services`
+        ("block_mapping_pair", "yaml") if is_top_level_yaml_pair(node) => {
+            if let Some(key) = node.child_by_field_name("key")
+                && let Ok(key_str) = key.utf8_text(source.as_bytes())
+            {
+                let key_str = key_str.trim_matches(|c| c == '"' || c == '\'');
+                pattern.structs.push(key_str.to_string());
+                debug!("Found YAML key: {}", key_str);
+            }
+        }
+
+        // Markdown: ATX (`## Installation`) and setext headings, both recorded in ATX
+        // form so the heading level is part of the name
+        ("atx_heading" | "setext_heading", "markdown") => {
+            if let Some(level) = markdown_heading_level(node)
+                && let Some(content) = node.child_by_field_name("heading_content")
+                && let Ok(text) = content.utf8_text(source.as_bytes())
+            {
+                let heading = format!(
+                    "{} {}",
+                    "#".repeat(level),
+                    text.split_whitespace().collect::<Vec<_>>().join(" ")
+                );
+                debug!("Found Markdown heading: {}", heading);
+                pattern.structs.push(heading);
+            }
+        }
+
+        // GraphQL: object, interface, input, enum, union and scalar types, with the
+        // fields of each recorded as `Type.field`
+        (kind, "graphql") if kind.ends_with("_type_definition") => {
+            if let Some(type_name) = graphql_name(node, source) {
+                debug!("Found GraphQL type: {}", type_name);
+                pattern.structs.push(type_name.to_string());
+
+                for fields in node.named_children(&mut node.walk()) {
+                    if fields.kind() != "fields_definition" {
+                        continue;
+                    }
+                    for field in fields.named_children(&mut fields.walk()) {
+                        if let Some(field_name) = graphql_name(field, source) {
+                            debug!("Found GraphQL field: {}.{}", type_name, field_name);
+                            pattern
+                                .functions
+                                .push(format!("{}.{}", type_name, field_name));
+                        }
                     }
                 }
             }
         }
 
+        // GraphQL: named operations in query documents, e.g. `query GetUser { ... }`
+        ("operation_definition", "graphql") => {
+            if let Some(operation_name) = graphql_name(node, source) {
+                debug!("Found GraphQL operation: {}", operation_name);
+                pattern.functions.push(operation_name.to_string());
+            }
+        }
+
         _ => {}
     }
 
     // Recursively process child nodes
     for child in node.children(&mut node.walk()) {
-        extract_from_node(
-            child,
-            source,
-            language,
-            classes,
-            functions,
-            structs,
-            implementations,
-        );
+        extract_from_node(child, source, language, module, pattern);
+    }
+}
+
+// `name` inside the inline module path `module`, e.g. `models::User`
+fn qualify(module: &str, name: &str) -> String {
+    if module.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}::{}", module, name)
     }
 }
 
-pub fn display_scan_results(files: &[FilePattern], language_type: &str) {
-    println!("\n🔍 Scan Results ({})", language_type);
-    println!("{:-<50}", "");
+// The bare, quoted or dotted key naming a TOML table or pair
+fn toml_key(node: Node) -> Option<Node> {
+    node.named_children(&mut node.walk())
+        .find(|child| child.kind().ends_with("_key"))
+}
 
-    for file in files {
-        println!("\nFile: {}", file.path);
+// The `name` child of a GraphQL definition; GraphQL nodes have no named fields
+fn graphql_name<'a>(node: Node, source: &'a str) -> Option<&'a str> {
+    node.named_children(&mut node.walk())
+        .find(|child| child.kind() == "name")
+        .and_then(|name| name.utf8_text(source.as_bytes()).ok())
+}
 
-        if !file.classes.is_empty() {
-            println!("  Classes:");
-            for class in &file.classes {
-                println!("    - {}", class);
-            }
-        }
-        if !file.structs.is_empty() {
-            println!("  Structs:");
-            for struct_name in &file.structs {
-                println!("    - {}", struct_name);
-            }
-        }
-        if !file.implementations.is_empty() {
-            println!("  Implementations:");
-            for impl_name in &file.implementations {
-                println!("    - {}", impl_name);
-            }
-        }
-        if !file.functions.is_empty() {
-            println!("  Functions:");
-            for function in &file.functions {
-                println!("    - {}", function);
+// From the `atx_h2_marker` or `setext_h2_underline` child of a heading
+fn markdown_heading_level(node: Node) -> Option<usize> {
+    node.children(&mut node.walk()).find_map(|child| {
+        let kind = child.kind();
+        let level = kind
+            .strip_prefix("atx_h")
+            .and_then(|rest| rest.strip_suffix("_marker"))
+            .or_else(|| {
+                kind.strip_prefix("setext_h")
+                    .and_then(|rest| rest.strip_suffix("_underline"))
+            })?;
+        level.parse().ok()
+    })
+}
+
+// block_mapping_pair -> block_mapping -> block_node -> document
+fn is_top_level_yaml_pair(node: Node) -> bool {
+    node.parent()
+        .and_then(|mapping| mapping.parent())
+        .and_then(|block| block.parent())
+        .is_some_and(|document| document.kind() == "document")
+}
+
+// Attributes are `attribute_item` siblings preceding the item, possibly
+// interleaved with doc comments
+fn record_rust_attributes(node: Node, source: &str, item_name: &str, pattern: &mut FilePattern) {
+    let mut attributes = Vec::new();
+    let mut sibling = node.prev_sibling();
+
+    while let Some(prev) = sibling {
+        match prev.kind() {
+            "attribute_item" => {
+                if let Some(attribute) = prev.named_child(0)
+                    && let Ok(text) = attribute.utf8_text(source.as_bytes())
+                {
+                    attributes.push(text.to_string());
+                }
             }
+            "line_comment" | "block_comment" => {}
+            _ => break,
         }
+        sibling = prev.prev_sibling();
+    }
 
-        if file.classes.is_empty()
-            && file.functions.is_empty()
-            && file.structs.is_empty()
-            && file.implementations.is_empty()
-        {
-            println!("  (No extractable items found)");
-        }
+    if !attributes.is_empty() {
+        attributes.reverse();
+        debug!("Found Rust attributes on {}: {:?}", item_name, attributes);
+        pattern
+            .attributes
+            .entry(item_name.to_string())
+            .or_default()
+            .extend(attributes);
     }
 }
 
-pub fn display_all_scan_results(results: &[(String, Vec<FilePattern>)]) {
-    if results.is_empty() {
-        println!("No supported files found in the directory.");
-        return;
+// Python decorators are children of a `decorated_definition` wrapping the item, while
+// TypeScript class decorators are children of the class, or of its `export_statement`
+fn record_decorators(node: Node, source: &str, item_name: &str, pattern: &mut FilePattern) {
+    let owner = match node.parent() {
+        Some(parent) if matches!(parent.kind(), "decorated_definition" | "export_statement") => {
+            parent
+        }
+        _ => node,
+    };
+
+    let decorators: Vec<String> = owner
+        .children(&mut owner.walk())
+        .filter(|child| child.kind() == "decorator")
+        .filter_map(|decorator| decorator.utf8_text(source.as_bytes()).ok())
+        .map(|text| text.trim_start_matches('@').trim().to_string())
+        .collect();
+
+    if !decorators.is_empty() {
+        debug!("Found decorators on {}: {:?}", item_name, decorators);
+        pattern
+            .attributes
+            .entry(item_name.to_string())
+            .or_default()
+            .extend(decorators);
     }
+}
 
-    println!("\n🔍 Multi-Language Scan Results");
-    println!("{:=<60}", "");
+// Rust and TypeScript both expose `parameters` and `return_type` fields; TypeScript's
+// return type is a `type_annotation` whose text keeps the leading `:`
+fn function_signature(node: Node, source: &str, name: &str) -> FunctionSignature {
+    let text = |n: Node| {
+        n.utf8_text(source.as_bytes())
+            .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+            .ok()
+    };
 
-    for (language, files) in results {
-        if !files.is_empty() {
-            display_scan_results(files, language);
+    let params = node
+        .child_by_field_name("parameters")
+        .map(|params| {
+            params
+                .named_children(&mut params.walk())
+                .filter(|p| !p.kind().ends_with("comment"))
+                .filter_map(text)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let return_type = node
+        .child_by_field_name("return_type")
+        .and_then(text)
+        .map(|t| t.trim_start_matches(':').trim().to_string());
+
+    FunctionSignature {
+        name: name.to_string(),
+        params,
+        return_type,
+    }
+}
+
+// `async` and `*` are anonymous tokens on the method node itself
+fn method_modifiers(node: Node) -> Vec<String> {
+    let mut modifiers = Vec::new();
+    for child in node.children(&mut node.walk()) {
+        match child.kind() {
+            "async" => modifiers.push("async".to_string()),
+            "*" => modifiers.push("generator".to_string()),
+            _ => {}
         }
     }
+    modifiers
+}
 
-    // Summary
-    let total_files: usize = results.iter().map(|(_, files)| files.len()).sum();
-    let total_items: usize = results
-        .iter()
-        .map(|(_, files)| {
-            files
-                .iter()
-                .map(|f| {
-                    f.classes.len() + f.functions.len() + f.structs.len() + f.implementations.len()
-                })
-                .sum::<usize>()
-        })
-        .sum();
+/// Item names longer than this are truncated in scan output (storage keeps the full name)
+pub const DEFAULT_MAX_ITEM_NAME_LENGTH: usize = 80;
+
+/// `name` cut to at most `max_length` characters, ending in "..." when there's room
+/// for it and at least one character of the name
+pub fn truncate_item_name(name: &str, max_length: usize) -> String {
+    if name.chars().count() <= max_length {
+        return name.to_string();
+    }
+    if max_length <= 3 {
+        return name.chars().take(max_length).collect();
+    }
+    let truncated: String = name.chars().take(max_length - 3).collect();
+    format!("{}...", truncated)
+}
 
-    println!("\n📊 Summary:");
-    println!("  Languages found: {}", results.len());
-    println!("  Total files: {}", total_files);
-    println!("  Total items: {}", total_items);
+/// Files that didn't parse cleanly, whose items may be incomplete
+pub fn files_with_parse_errors(files: &[FilePattern]) -> Vec<&FilePattern> {
+    files.iter().filter(|file| file.parse_errors).collect()
 }
 
 pub fn get_supported_languages() -> Vec<&'static str> {
@@ -536,6 +1576,39 @@ pub fn get_supported_languages() -> Vec<&'static str> {
         .collect()
 }
 
+/// Label of scaffs covering both JavaScript and TypeScript files, written by older
+/// versions
+pub const JS_TS_DISPLAY_NAME: &str = "JavaScript/TypeScript";
+
+/// Joins the display names in the label of a scaff saved from several languages, e.g.
+/// `Rust/TypeScript`
+pub const LANGUAGE_SEPARATOR: &str = "/";
+
+/// The configuration of a language by its identifier or one of its aliases
+pub fn language_config(language: &str) -> Option<&'static LanguageConfig> {
+    SUPPORTED_LANGUAGES
+        .iter()
+        .find(|config| config.name == language || config.aliases.contains(&language))
+}
+
+/// The language identifier for a display name, e.g. `javascript` for `JavaScript`
+pub fn language_from_display(display_name: &str) -> Option<&'static str> {
+    SUPPORTED_LANGUAGES
+        .iter()
+        .find(|config| config.display_name == display_name)
+        .map(|config| config.name)
+}
+
+/// The languages a scaff's `language` label covers: its own, or each of a combined
+/// label's like `Rust/TypeScript`. Empty when any of them isn't supported.
+pub fn languages_for_scaff(display_name: &str) -> Vec<&'static str> {
+    display_name
+        .split(LANGUAGE_SEPARATOR)
+        .map(language_from_display)
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default()
+}
+
 pub fn get_language_display_name(language: &str) -> String {
     SUPPORTED_LANGUAGES
         .iter()
@@ -552,7 +1625,7 @@ mod tests {
 
     #[test]
     fn test_supported_languages_config() {
-        assert_eq!(SUPPORTED_LANGUAGES.len(), 9);
+        assert_eq!(SUPPORTED_LANGUAGES.len(), 14);
 
         let rust_config = &SUPPORTED_LANGUAGES[0];
         assert_eq!(rust_config.name, "rust");
@@ -563,11 +1636,12 @@ mod tests {
     #[test]
     fn test_get_supported_languages() {
         let languages = get_supported_languages();
-        assert_eq!(languages.len(), 9);
+        assert_eq!(languages.len(), 14);
         assert!(languages.contains(&"rust"));
         assert!(languages.contains(&"javascript"));
         assert!(languages.contains(&"typescript"));
         assert!(languages.contains(&"python"));
+        assert!(languages.contains(&"ruby"));
     }
 
     #[test]
@@ -578,6 +1652,32 @@ mod tests {
         assert_eq!(get_language_display_name("unknown"), "unknown");
     }
 
+    #[test]
+    fn test_language_display_name_round_trip() {
+        for config in SUPPORTED_LANGUAGES {
+            let display_name = get_language_display_name(config.name);
+            assert_eq!(language_from_display(&display_name), Some(config.name));
+            assert_eq!(languages_for_scaff(&display_name), vec![config.name]);
+            assert_eq!(
+                language_config(config.name).map(|c| c.name),
+                Some(config.name)
+            );
+            for alias in config.aliases {
+                assert_eq!(language_config(alias).map(|c| c.name), Some(config.name));
+            }
+        }
+
+        assert_eq!(language_from_display("rust"), None);
+        assert_eq!(language_config("Rust").map(|c| c.name), None);
+        assert_eq!(
+            languages_for_scaff(JS_TS_DISPLAY_NAME),
+            vec!["javascript", "typescript"]
+        );
+        assert_eq!(languages_for_scaff("Rust/Python"), vec!["rust", "python"]);
+        assert!(languages_for_scaff("Cobol").is_empty());
+        assert!(languages_for_scaff("Rust/Cobol").is_empty());
+    }
+
     #[test]
     fn test_scan_empty_directory() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
@@ -615,153 +1715,1076 @@ fn main() {
         )?;
 
         let temp_path = temp_dir.path().to_str().unwrap();
-        let results = scan_language_files_in_dir(temp_path, "rust");
+        let results = scan_language_files_in_dir(temp_path, "rust");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert!(file_pattern.path.ends_with("test.rs"));
+        assert_eq!(file_pattern.extension, "rs");
+        assert_eq!(file_pattern.language.as_deref(), Some("Rust"));
+        // Just verify file was found - tree-sitter parsing can be complex
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_rust_attributes() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("model.rs"),
+            r#"
+#[derive(Debug)]
+/// A user record
+#[serde(rename_all = "camelCase")]
+struct User {
+    name: String,
+}
+
+#[get("/")]
+fn index() {}
+
+fn plain() {}
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert_eq!(
+            file_pattern.attributes.get("User"),
+            Some(&vec![
+                "derive(Debug)".to_string(),
+                "serde(rename_all = \"camelCase\")".to_string()
+            ])
+        );
+        assert_eq!(
+            file_pattern.attributes.get("index"),
+            Some(&vec!["get(\"/\")".to_string()])
+        );
+        assert!(!file_pattern.attributes.contains_key("plain"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_rust_derive_on_struct() {
+        let file = scan_source(
+            "#[derive(Debug, Clone)]\npub struct Service {\n    name: String,\n}\n",
+            Path::new("service.rs"),
+            "rust",
+            &ScanOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            file.attributes.get("Service"),
+            Some(&vec!["derive(Debug, Clone)".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_scan_decorators() {
+        let file = scan_source(
+            "@dataclass\nclass User:\n    name: str\n\n@app.route(\"/\")\ndef index():\n    pass\n\ndef plain():\n    pass\n",
+            Path::new("app.py"),
+            "python",
+            &ScanOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            file.attributes.get("User"),
+            Some(&vec!["dataclass".to_string()])
+        );
+        assert_eq!(
+            file.attributes.get("index"),
+            Some(&vec!["app.route(\"/\")".to_string()])
+        );
+        assert!(!file.attributes.contains_key("plain"));
+
+        let file = scan_source(
+            "@Component({ selector: 'app' })\nexport class AppComponent {}\n",
+            Path::new("app.ts"),
+            "typescript",
+            &ScanOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            file.attributes.get("AppComponent"),
+            Some(&vec!["Component({ selector: 'app' })".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_scan_rust_enums_and_traits() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("shape.rs"),
+            "enum Shape { Circle, Square }\ntrait Area { fn area(&self) -> f64; }\nstruct Unit;",
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].enums, vec!["Shape"]);
+        assert_eq!(results[0].traits, vec!["Area"]);
+        assert_eq!(results[0].structs, vec!["Unit"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_hash_normalize_whitespace() -> Result<(), Box<dyn std::error::Error>> {
+        let original = "fn main() {\n    run(1, 2);\n}\n";
+        let reformatted = "fn main() {\n\trun(1,  2);   \n\n}";
+        let changed = "fn main() {\n    run(1, 3);\n}\n";
+
+        assert_eq!(
+            content_hash(original, true),
+            content_hash(reformatted, true)
+        );
+        assert_ne!(
+            content_hash(original, false),
+            content_hash(reformatted, false)
+        );
+        assert_ne!(content_hash(original, true), content_hash(changed, true));
+
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("main.rs"), reformatted)?;
+        let options = ScanOptions {
+            normalize_whitespace: true,
+            ..ScanOptions::default()
+        };
+        let results = scan_language_files_in_dir_with_options(
+            temp_dir.path().to_str().unwrap(),
+            "rust",
+            &options,
+        );
+        assert_eq!(results[0].content_hash, Some(content_hash(original, true)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_rust_struct_fields() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("auth.rs"),
+            r#"
+pub struct AuthService {
+    pub id: u64,
+    // Display name
+    name: String,
+    created_at: String,
+}
+
+struct Token(String);
+
+struct Marker;
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+
+        let file_pattern = &results[0];
+        assert_eq!(
+            file_pattern.struct_fields.get("AuthService"),
+            Some(&vec![
+                "id".to_string(),
+                "name".to_string(),
+                "created_at".to_string()
+            ])
+        );
+        assert_eq!(file_pattern.struct_fields.get("Token"), Some(&Vec::new()));
+        assert_eq!(file_pattern.struct_fields.get("Marker"), Some(&Vec::new()));
+        assert_eq!(
+            file_pattern.qualified_struct_fields(),
+            vec![
+                "AuthService::created_at",
+                "AuthService::id",
+                "AuthService::name"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_rust_impl_methods() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("auth.rs"),
+            r#"
+struct AuthService {
+    name: String,
+}
+
+impl AuthService {
+    pub fn new(name: String) -> Self {
+        AuthService { name }
+    }
+
+    // Renames the service
+    pub fn update_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn display_name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn helper() {}
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert_eq!(file_pattern.implementations, vec!["AuthService"]);
+        assert_eq!(
+            file_pattern.impl_methods.get("AuthService"),
+            Some(&vec![
+                "new".to_string(),
+                "update_name".to_string(),
+                "display_name".to_string()
+            ])
+        );
+        assert!(file_pattern.functions.contains(&"helper".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_function_signatures() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "fn add(a: i32, b: i32) -> i32 { a + b }\nfn reset(&mut self) {}",
+        )?;
+        fs::write(
+            temp_dir.path().join("api.ts"),
+            "function greet(name: string, loud?: boolean): string { return name; }",
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let rust = scan_language_files_in_dir(temp_path, "rust");
+        assert_eq!(
+            rust[0].signatures,
+            vec![
+                FunctionSignature {
+                    name: "add".to_string(),
+                    params: vec!["a: i32".to_string(), "b: i32".to_string()],
+                    return_type: Some("i32".to_string()),
+                },
+                FunctionSignature {
+                    name: "reset".to_string(),
+                    params: vec!["&mut self".to_string()],
+                    return_type: None,
+                },
+            ]
+        );
+        assert_eq!(rust[0].functions, vec!["add", "reset"]);
+
+        let typescript = scan_language_files_in_dir(temp_path, "typescript");
+        assert_eq!(
+            typescript[0].signatures,
+            vec![FunctionSignature {
+                name: "greet".to_string(),
+                params: vec!["name: string".to_string(), "loud?: boolean".to_string()],
+                return_type: Some("string".to_string()),
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reachable_files_follows_imports() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let src = temp_dir.path().join("src");
+        fs::create_dir_all(&src)?;
+        fs::write(
+            src.join("main.rs"),
+            "mod used;\nuse crate::used::helper;\nfn main() { helper(); }",
+        )?;
+        fs::write(src.join("used.rs"), "pub fn helper() {}")?;
+        fs::write(src.join("unused.rs"), "pub fn orphan() {}")?;
+
+        let files = reachable_files(&src.join("main.rs"));
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().any(|f| f.ends_with("used.rs")));
+        assert!(!files.iter().any(|f| f.ends_with("unused.rs")));
+
+        let results = scan_language_files(&files, "rust", &ScanOptions::default());
+        assert_eq!(results.len(), 2);
+        assert!(!results.iter().any(|f| f.path.ends_with("unused.rs")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_javascript_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("test.js");
+
+        fs::write(
+            &test_file,
+            r#"
+class TestClass {
+    constructor(name) {
+        this.name = name;
+    }
+    
+    getName() {
+        return this.name;
+    }
+}
+
+function testFunction() {
+    return "test";
+}
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "javascript");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert!(file_pattern.path.ends_with("test.js"));
+        assert_eq!(file_pattern.extension, "js");
+        // Just verify file was processed - parsing results may vary
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_javascript_async_and_generator_methods() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("api.js"),
+            r#"
+class ApiClient {
+    async getData() {
+        return fetch("/data");
+    }
+
+    *pages() {
+        yield 1;
+    }
+
+    reset() {}
+}
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "javascript");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert_eq!(
+            file_pattern.metadata.get("getData"),
+            Some(&vec!["async".to_string()])
+        );
+        assert_eq!(
+            file_pattern.metadata.get("pages"),
+            Some(&vec!["generator".to_string()])
+        );
+        assert!(!file_pattern.metadata.contains_key("reset"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_item_names_truncated_for_display() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let long_name = "a".repeat(200);
+        fs::write(
+            temp_dir.path().join("long.js"),
+            format!("function {}() {{}}", long_name),
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "javascript");
+        assert_eq!(results[0].functions, vec![long_name.clone()]);
+
+        let displayed = truncate_item_name(&long_name, DEFAULT_MAX_ITEM_NAME_LENGTH);
+        assert_eq!(displayed.chars().count(), DEFAULT_MAX_ITEM_NAME_LENGTH);
+        assert!(displayed.ends_with("..."));
+        assert_eq!(
+            truncate_item_name("short", DEFAULT_MAX_ITEM_NAME_LENGTH),
+            "short"
+        );
+
+        // Limits too small for the ellipsis still hold
+        assert_eq!(truncate_item_name("handler", 4), "h...");
+        assert_eq!(truncate_item_name("handler", 2), "ha");
+        assert_eq!(truncate_item_name("handler", 0), "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_python_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("test.py");
+
+        fs::write(
+            &test_file,
+            r#"
+class TestClass:
+    def __init__(self, name):
+        self.name = name
+    
+    def get_name(self):
+        return self.name
+
+def test_function():
+    return "test"
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "python");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert!(file_pattern.path.ends_with("test.py"));
+        assert_eq!(file_pattern.extension, "py");
+        // Just verify file was processed - parsing results may vary
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_ruby_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("user.rb"),
+            r#"
+module Accounts
+  class User < ApplicationRecord
+    def full_name
+      [first, last].join(" ")
+    end
+
+    def self.active
+      where(active: true)
+    end
+  end
+end
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "ruby");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert_eq!(file_pattern.extension, "rb");
+        assert_eq!(file_pattern.classes, vec!["module Accounts", "User"]);
+        assert_eq!(file_pattern.functions, vec!["full_name", "active"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_html_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("test.html");
+
+        fs::write(
+            &test_file,
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <title>Test</title>
+</head>
+<body>
+    <div class="container">
+        <h1>Test Header</h1>
+        <p>Test paragraph</p>
+    </div>
+</body>
+</html>
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "html");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert!(file_pattern.path.ends_with("test.html"));
+        assert_eq!(file_pattern.extension, "html");
+
+        // HTML parsing with tree-sitter might not capture all elements as expected
+        // Let's just verify the file was processed correctly
+        assert_eq!(file_pattern.classes.len(), 0); // tree-sitter might not parse HTML elements as we expect
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_json_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("test.json");
+
+        fs::write(
+            &test_file,
+            r#"
+{
+    "name": "test-project",
+    "version": "1.0.0",
+    "dependencies": {
+        "express": "^4.18.0"
+    },
+    "scripts": {
+        "start": "node index.js"
+    }
+}
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "json");
+
+        assert_eq!(results.len(), 1);
+        let file_pattern = &results[0];
+        assert!(file_pattern.path.ends_with("test.json"));
+        assert_eq!(file_pattern.extension, "json");
+
+        // Just verify file was processed - JSON parsing results may vary
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_structure_hash_is_order_independent() {
+        let file = FilePattern {
+            path: "src/lib.rs".to_string(),
+            functions: vec!["run".to_string(), "stop".to_string()],
+            structs: vec!["Config".to_string()],
+            ..Default::default()
+        };
+        let reordered = FilePattern {
+            path: "src/other.rs".to_string(),
+            functions: vec!["stop".to_string(), "run".to_string()],
+            structs: vec!["Config".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(structure_hash(&file), structure_hash(&reordered));
+
+        // The same name as another item type is a different structure
+        let moved = FilePattern {
+            functions: vec!["run".to_string(), "stop".to_string()],
+            enums: vec!["Config".to_string()],
+            ..Default::default()
+        };
+        assert_ne!(structure_hash(&file), structure_hash(&moved));
+    }
+
+    #[test]
+    fn test_scan_non_utf8_file() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        // Latin-1 "é" (0xE9) on its own is invalid UTF-8
+        fs::write(
+            temp_dir.path().join("legacy.rs"),
+            b"// caf\xe9 \xe9t\xe9\npub fn run() {}\npub const NAME: &str = \"Andr\xe9\";\npub fn stop() {}\n",
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].functions, vec!["run", "stop"]);
+        assert!(!results[0].parse_errors);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_rust_inline_modules() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            r#"
+pub struct Config;
+
+mod api {
+    pub struct Config { pub port: u16 }
+    impl Config {
+        pub fn new() -> Self { Config { port: 80 } }
+    }
+
+    mod v2 {
+        pub enum Route {}
+    }
+}
+
+mod db {
+    pub struct Config;
+}
+
+mod external;
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+
+        assert_eq!(results.len(), 1);
+        let file = &results[0];
+        assert_eq!(file.structs, vec!["Config", "api::Config", "db::Config"]);
+        assert_eq!(file.enums, vec!["api::v2::Route"]);
+        assert_eq!(file.implementations, vec!["api::Config"]);
+        assert_eq!(file.functions, vec!["api::new"]);
+        assert_eq!(file.struct_fields["api::Config"], vec!["port"]);
+        assert_eq!(file.impl_methods["api::Config"], vec!["new"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_dedups_repeated_items() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("shapes.go"),
+            r#"package shapes
+
+type Circle struct{}
+type Square struct{}
+
+func (c Circle) Area() float64 { return 0 }
+func (s Square) Area() float64 { return 0 }
+func Describe() {}
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "go");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].functions, vec!["Area", "Describe"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_toml_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            r#"
+workspace = false
+
+[package]
+name = "app"
+
+[dependencies]
+serde = "1"
+
+[tool.poetry]
+name = "app"
+
+[[bin]]
+name = "one"
+
+[[bin]]
+name = "two"
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "toml");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].structs,
+            vec!["workspace", "package", "dependencies", "tool.poetry", "bin"]
+        );
+        assert!(!results[0].parse_errors);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_yaml_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("docker-compose.yml"),
+            r#"
+version: "3"
+services:
+  web:
+    image: nginx
+  db:
+    image: postgres
+volumes: {}
+"#,
+        )?;
+        fs::write(temp_dir.path().join("ci.yaml"), "jobs:\n  test: {}\n")?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let mut results = scan_language_files_in_dir(temp_path, "yaml");
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].structs, vec!["jobs"]);
+        assert_eq!(results[1].structs, vec!["version", "services", "volumes"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_graphql_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("schema.graphql"),
+            r#""""A registered user"""
+type User {
+  id: ID!
+  name: String
+}
+
+type Query {
+  user(id: ID!): User
+}
+"#,
+        )?;
+        fs::write(
+            temp_dir.path().join("queries.gql"),
+            "query GetUser($id: ID!) { user(id: $id) { name } }",
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let mut results = scan_language_files_in_dir(temp_path, "graphql");
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].functions, vec!["GetUser"]);
+        assert!(results[0].structs.is_empty());
+        assert_eq!(results[1].structs, vec!["User", "Query"]);
+        assert_eq!(
+            results[1].functions,
+            vec!["User.id", "User.name", "Query.user"]
+        );
+        assert!(!results[1].parse_errors);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_markdown_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("README.md"),
+            r#"# My   Project
+
+Intro text.
+
+## Installation
+
+```sh
+# not a heading
+cargo install my-project
+```
+
+### From source
+
+Usage
+-----
+"#,
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "markdown");
 
         assert_eq!(results.len(), 1);
-        let file_pattern = &results[0];
-        assert!(file_pattern.path.ends_with("test.rs"));
-        assert_eq!(file_pattern.extension, "rs");
-        // Just verify file was found - tree-sitter parsing can be complex
+        assert_eq!(
+            results[0].structs,
+            vec![
+                "# My Project",
+                "## Installation",
+                "### From source",
+                "## Usage"
+            ]
+        );
+        assert!(!results[0].parse_errors);
 
         Ok(())
     }
 
     #[test]
-    fn test_scan_javascript_files() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_write_scan_json_matches_buffered_scan() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("test.js");
+        fs::write(temp_dir.path().join("a.rs"), "struct A {}\nfn a() {}")?;
+        fs::write(temp_dir.path().join("b.rs"), "fn b() {}")?;
+        fs::write(temp_dir.path().join("c.js"), "function c() {}")?;
 
-        fs::write(
-            &test_file,
-            r#"
-class TestClass {
-    constructor(name) {
-        this.name = name;
-    }
-    
-    getName() {
-        return this.name;
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let mut streamed = Vec::new();
+        let count = write_scan_json(
+            temp_path,
+            &["rust", "javascript"],
+            &ScanOptions::default(),
+            &mut streamed,
+        )?;
+
+        let mut buffered = scan_language_files_in_dir(temp_path, "rust");
+        buffered.extend(scan_language_files_in_dir(temp_path, "javascript"));
+
+        let streamed: Vec<FilePattern> = serde_json::from_slice(&streamed)?;
+        assert_eq!(count, 3);
+        assert_eq!(
+            serde_json::to_value(&streamed)?,
+            serde_json::to_value(&buffered)?
+        );
+
+        Ok(())
     }
-}
 
-function testFunction() {
-    return "test";
-}
-"#,
+    #[test]
+    fn test_scan_respects_ignore() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("node_modules"))?;
+        fs::create_dir_all(temp_dir.path().join("build"))?;
+        fs::write(
+            temp_dir.path().join("node_modules/foo.js"),
+            "function foo() {}",
+        )?;
+        fs::write(
+            temp_dir.path().join("build/bundle.js"),
+            "function bundle() {}",
         )?;
+        fs::write(temp_dir.path().join("app.js"), "function app() {}")?;
+        fs::write(temp_dir.path().join(".gitignore"), "build/\n")?;
 
         let temp_path = temp_dir.path().to_str().unwrap();
         let results = scan_language_files_in_dir(temp_path, "javascript");
-
         assert_eq!(results.len(), 1);
-        let file_pattern = &results[0];
-        assert!(file_pattern.path.ends_with("test.js"));
-        assert_eq!(file_pattern.extension, "js");
-        // Just verify file was processed - parsing results may vary
+        assert!(results[0].path.ends_with("app.js"));
+
+        let options = ScanOptions {
+            respect_ignore: false,
+            ..ScanOptions::default()
+        };
+        let results = scan_language_files_in_dir_with_options(temp_path, "javascript", &options);
+        assert_eq!(results.len(), 3);
+        assert!(
+            results
+                .iter()
+                .any(|f| f.path.ends_with("node_modules/foo.js"))
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test_scan_python_files() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_scan_restricted_to_files() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("test.py");
-
-        fs::write(
-            &test_file,
-            r#"
-class TestClass:
-    def __init__(self, name):
-        self.name = name
-    
-    def get_name(self):
-        return self.name
-
-def test_function():
-    return "test"
-"#,
-        )?;
+        fs::create_dir_all(temp_dir.path().join("src"))?;
+        fs::write(temp_dir.path().join("src/lib.rs"), "pub fn run() {}")?;
+        fs::write(temp_dir.path().join("src/config.rs"), "pub struct Config;")?;
 
+        let options = ScanOptions {
+            restrict_to: Some(HashSet::from([temp_dir.path().join("src/config.rs")])),
+            ..ScanOptions::default()
+        };
         let temp_path = temp_dir.path().to_str().unwrap();
-        let results = scan_language_files_in_dir(temp_path, "python");
-
+        let results = scan_language_files_in_dir_with_options(temp_path, "rust", &options);
         assert_eq!(results.len(), 1);
-        let file_pattern = &results[0];
-        assert!(file_pattern.path.ends_with("test.py"));
-        assert_eq!(file_pattern.extension, "py");
-        // Just verify file was processed - parsing results may vary
+        assert_eq!(results[0].path, "src/config.rs");
+        assert_eq!(languages_in_dir(temp_path, &options).len(), 1);
 
         Ok(())
     }
 
     #[test]
-    fn test_scan_html_files() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_scan_respects_scaffignore() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("test.html");
-
+        fs::create_dir_all(temp_dir.path().join("src/proto"))?;
+        fs::write(temp_dir.path().join("src/lib.rs"), "pub fn run() {}")?;
         fs::write(
-            &test_file,
-            r#"
-<!DOCTYPE html>
-<html>
-<head>
-    <title>Test</title>
-</head>
-<body>
-    <div class="container">
-        <h1>Test Header</h1>
-        <p>Test paragraph</p>
-    </div>
-</body>
-</html>
-"#,
+            temp_dir.path().join("src/proto/user.pb.rs"),
+            "pub struct User;",
+        )?;
+        fs::write(temp_dir.path().join("src/order.pb.rs"), "pub struct Order;")?;
+        fs::write(temp_dir.path().join("src/schema.rs"), "pub struct Schema;")?;
+        fs::write(temp_dir.path().join(".gitignore"), "schema.rs\n")?;
+        fs::write(
+            temp_dir.path().join(SCAFFIGNORE_FILE),
+            "*.pb.rs\n!schema.rs\n",
         )?;
 
         let temp_path = temp_dir.path().to_str().unwrap();
-        let results = scan_language_files_in_dir(temp_path, "html");
+        let mut paths: Vec<String> = scan_language_files_in_dir(temp_path, "rust")
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["src/lib.rs", "src/schema.rs"]);
+
+        let options = ScanOptions {
+            respect_ignore: false,
+            ..ScanOptions::default()
+        };
+        let results = scan_language_files_in_dir_with_options(temp_path, "rust", &options);
+        assert_eq!(results.len(), 4);
+
+        // Changed-file restrictions still walk, so the ignore rules apply
+        let options = ScanOptions {
+            restrict_to: Some(HashSet::from([
+                temp_dir.path().join("src/lib.rs"),
+                temp_dir.path().join("src/order.pb.rs"),
+            ])),
+            ..ScanOptions::default()
+        };
+        let results = scan_language_files_in_dir_with_options(temp_path, "rust", &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "src/lib.rs");
 
+        Ok(())
+    }
+
+    #[test]
+    fn test_passes_filters_applies_ignore_rules() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src"))?;
+        fs::create_dir_all(root.join("target"))?;
+        for file in [
+            "src/lib.rs",
+            "src/user.pb.rs",
+            "src/schema.rs",
+            "target/out.rs",
+        ] {
+            fs::write(root.join(file), "pub struct Item;")?;
+        }
+        fs::write(root.join(".gitignore"), "schema.rs\n")?;
+        fs::write(root.join(SCAFFIGNORE_FILE), "*.pb.rs\n!schema.rs\n")?;
+
+        let options = ScanOptions::default();
+        let passes = |file: &str| passes_filters(&root.join(file), root, &options);
+        assert!(passes("src/lib.rs"));
+        assert!(!passes("src/user.pb.rs"));
+        // Re-included by .scaffignore
+        assert!(passes("src/schema.rs"));
+        assert!(!passes("target/out.rs"));
+
+        let options = ScanOptions {
+            respect_ignore: false,
+            ..ScanOptions::default()
+        };
+        assert!(passes_filters(&root.join("src/user.pb.rs"), root, &options));
+
+        // Explicit file lists, e.g. from --follow-imports
+        let paths: Vec<PathBuf> = ["src/lib.rs", "src/user.pb.rs", "target/out.rs"]
+            .iter()
+            .map(|file| root.join(file))
+            .collect();
+        let options = ScanOptions {
+            base_path: Some(root.to_path_buf()),
+            ..ScanOptions::default()
+        };
+        let results = scan_language_files(&paths, "rust", &options);
         assert_eq!(results.len(), 1);
-        let file_pattern = &results[0];
-        assert!(file_pattern.path.ends_with("test.html"));
-        assert_eq!(file_pattern.extension, "html");
+        assert_eq!(results[0].path, "src/lib.rs");
 
-        // HTML parsing with tree-sitter might not capture all elements as expected
-        // Let's just verify the file was processed correctly
-        assert_eq!(file_pattern.classes.len(), 0); // tree-sitter might not parse HTML elements as we expect
+        Ok(())
+    }
+
+    #[test]
+    fn test_matching_files_in_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("src"))?;
+        fs::write(temp_dir.path().join("src/lib.rs"), "fn a() {}")?;
+        fs::write(temp_dir.path().join("build.rs"), "fn main() {}")?;
+        fs::write(temp_dir.path().join("app.js"), "function b() {}")?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let mut files = matching_files_in_dir(temp_path, "rust", &ScanOptions::default());
+        files.sort();
+
+        assert_eq!(
+            files,
+            vec![
+                temp_dir.path().join("build.rs"),
+                temp_dir.path().join("src/lib.rs")
+            ]
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test_scan_json_files() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_scan_skips_files_over_max_size() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("test.json");
+        let limit = 64;
+        let under = "fn under() {}";
+        let over = format!("fn over() {{}}\n{}", "//".repeat(limit));
+        assert!(under.len() <= limit && over.len() > limit);
+        fs::write(temp_dir.path().join("under.rs"), under)?;
+        fs::write(temp_dir.path().join("over.rs"), &over)?;
+
+        let options = ScanOptions {
+            max_file_size: limit as u64,
+            ..ScanOptions::default()
+        };
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir_with_options(temp_path, "rust", &options);
 
-        fs::write(
-            &test_file,
-            r#"
-{
-    "name": "test-project",
-    "version": "1.0.0",
-    "dependencies": {
-        "express": "^4.18.0"
-    },
-    "scripts": {
-        "start": "node index.js"
+        assert_eq!(results.len(), 1);
+        assert!(results[0].path.ends_with("under.rs"));
+
+        let results = scan_language_files_in_dir(temp_path, "rust");
+        assert_eq!(results.len(), 2);
+
+        Ok(())
     }
-}
-"#,
-        )?;
 
+    #[test]
+    fn test_scan_include_exclude_globs() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("src/tests"))?;
+        fs::write(temp_dir.path().join("main.rs"), "fn main() {}")?;
+        fs::write(temp_dir.path().join("src/lib.rs"), "fn lib() {}")?;
+        fs::write(temp_dir.path().join("src/tests/it.rs"), "fn it() {}")?;
         let temp_path = temp_dir.path().to_str().unwrap();
-        let results = scan_language_files_in_dir(temp_path, "json");
 
+        let options = ScanOptions {
+            include: build_glob_set(&["src/**".to_string()])?,
+            ..ScanOptions::default()
+        };
+        let mut paths: Vec<String> =
+            scan_language_files_in_dir_with_options(temp_path, "rust", &options)
+                .into_iter()
+                .map(|f| f.path)
+                .collect();
+        paths.sort();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with("lib.rs"));
+        assert!(paths[1].ends_with("it.rs"));
+
+        let options = ScanOptions {
+            include: build_glob_set(&["src/**".to_string()])?,
+            exclude: build_glob_set(&["**/tests/**".to_string()])?,
+            ..ScanOptions::default()
+        };
+        let results = scan_language_files_in_dir_with_options(temp_path, "rust", &options);
         assert_eq!(results.len(), 1);
-        let file_pattern = &results[0];
-        assert!(file_pattern.path.ends_with("test.json"));
-        assert_eq!(file_pattern.extension, "json");
+        assert!(results[0].path.ends_with("lib.rs"));
 
-        // Just verify file was processed - JSON parsing results may vary
+        assert!(build_glob_set(&[]).unwrap().is_none());
+        assert!(build_glob_set(&["src/[".to_string()]).is_err());
 
         Ok(())
     }
@@ -776,7 +2799,7 @@ def test_function():
         fs::write(temp_dir.path().join("test.py"), "def test():\n    pass")?;
 
         let temp_path = temp_dir.path().to_str().unwrap();
-        let results = scan_all_languages_in_dir(temp_path);
+        let results = scan_all_languages_in_dir(temp_path, &ScanOptions::default());
 
         // Should find at least 3 languages
         assert!(results.len() >= 3);
@@ -789,6 +2812,126 @@ def test_function():
         Ok(())
     }
 
+    #[test]
+    fn test_scan_all_languages_only_scans_present_languages()
+    -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("src"))?;
+        fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}")?;
+        fs::write(temp_dir.path().join("NOTES.txt"), "# Notes")?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let languages = languages_in_dir(temp_path, &ScanOptions::default());
+        assert_eq!(
+            languages.iter().map(|c| c.name).collect::<Vec<_>>(),
+            vec!["rust"]
+        );
+
+        let results = scan_all_languages_in_dir(temp_path, &ScanOptions::default());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "Rust");
+
+        // Files the options leave out don't make their language present
+        fs::write(temp_dir.path().join("tool.py"), "def tool(): pass")?;
+        let options = ScanOptions {
+            exclude: build_glob_set(&["*.py".to_string()])?,
+            ..ScanOptions::default()
+        };
+        let languages = languages_in_dir(temp_path, &options);
+        assert_eq!(
+            languages.iter().map(|c| c.name).collect::<Vec<_>>(),
+            vec!["rust"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_reports_parse_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("good.rs"), "pub struct Good;")?;
+        fs::write(
+            temp_dir.path().join("broken.rs"),
+            "pub struct Broken {\n    field: \n\nfn orphan( {}",
+        )?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+        assert_eq!(results.len(), 2);
+
+        let failed = files_with_parse_errors(&results);
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].path.ends_with("broken.rs"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_max_depth() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("a/b"))?;
+        fs::write(temp_dir.path().join("top.rs"), "fn top() {}")?;
+        fs::write(temp_dir.path().join("a/middle.rs"), "fn middle() {}")?;
+        fs::write(temp_dir.path().join("a/b/deep.rs"), "fn deep() {}")?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let scan_to = |max_depth| {
+            let options = ScanOptions {
+                max_depth,
+                ..ScanOptions::default()
+            };
+            let mut names: Vec<String> =
+                scan_language_files_in_dir_with_options(temp_path, "rust", &options)
+                    .iter()
+                    .map(|f| f.functions.join(","))
+                    .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(scan_to(Some(1)), vec!["top"]);
+        assert_eq!(scan_to(Some(2)), vec!["middle", "top"]);
+        assert_eq!(scan_to(None), vec!["deep", "middle", "top"]);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_symlink_cycle_terminates() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new()?;
+        let outside = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("src"))?;
+        fs::write(temp_dir.path().join("src/lib.rs"), "pub struct Lib;")?;
+        fs::write(outside.path().join("shared.rs"), "pub struct Shared;")?;
+        // A link back to the scan root, and one to a directory outside it
+        symlink(temp_dir.path(), temp_dir.path().join("src/loop"))?;
+        symlink(outside.path(), temp_dir.path().join("shared"))?;
+
+        let temp_path = temp_dir.path().to_str().unwrap();
+        let results = scan_language_files_in_dir(temp_path, "rust");
+        assert_eq!(results.len(), 1);
+        assert!(results[0].path.ends_with("src/lib.rs"));
+
+        let options = ScanOptions {
+            follow_symlinks: true,
+            ..ScanOptions::default()
+        };
+        let results = scan_language_files_in_dir_with_options(temp_path, "rust", &options);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().any(|f| f.path.ends_with("shared/shared.rs")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_grammar_for() {
+        assert!(grammar_for("python").is_some());
+        assert!(grammar_for("cobol").is_none());
+    }
+
     #[test]
     fn test_legacy_functions() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
@@ -806,6 +2949,187 @@ def test_function():
         Ok(())
     }
 
+    #[test]
+    fn test_detect_primary_language() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path().to_str().unwrap();
+        let options = ScanOptions::default();
+        assert_eq!(detect_primary_language(dir, &options), None);
+
+        fs::create_dir_all(temp_dir.path().join("src"))?;
+        for file in ["src/main.rs", "src/lib.rs", "src/config.rs"] {
+            fs::write(temp_dir.path().join(file), "pub fn run() {}")?;
+        }
+        fs::write(temp_dir.path().join("build.js"), "function build() {}")?;
+        // Manifests and docs don't count, however many there are
+        for file in [
+            "Cargo.toml",
+            "README.md",
+            "a.json",
+            "b.json",
+            "c.json",
+            "d.json",
+        ] {
+            fs::write(temp_dir.path().join(file), "")?;
+        }
+        assert_eq!(detect_primary_language(dir, &options), Some("rust"));
+
+        for file in ["app.ts", "routes.ts", "view.tsx"] {
+            fs::write(temp_dir.path().join(file), "export function app() {}")?;
+        }
+        assert_eq!(detect_primary_language(dir, &options), None);
+
+        fs::write(
+            temp_dir.path().join("index.ts"),
+            "export function main() {}",
+        )?;
+        assert_eq!(detect_primary_language(dir, &options), Some("typescript"));
+
+        // Only files the scan would read count
+        let options = ScanOptions {
+            exclude: build_glob_set(&["*.ts".to_string()])?,
+            ..ScanOptions::default()
+        };
+        assert_eq!(detect_primary_language(dir, &options), Some("rust"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_single_file() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("lib.rs");
+        fs::write(&file, "pub struct Config;\npub fn run() {}")?;
+
+        assert_eq!(language_for_path(&file), Some("rust"));
+        let pattern = scan_file(&file, "rust", &ScanOptions::default()).expect("file should scan");
+        assert_eq!(pattern.structs, vec!["Config"]);
+        assert_eq!(pattern.functions, vec!["run"]);
+
+        // The caller's options apply, as in a directory scan
+        let options = ScanOptions {
+            normalize_whitespace: true,
+            base_path: Some(temp_dir.path().to_path_buf()),
+            ..ScanOptions::default()
+        };
+        let normalized = scan_file(&file, "rust", &options).expect("file should scan");
+        assert_eq!(normalized.path, "lib.rs");
+        assert_eq!(
+            normalized.content_hash,
+            Some(content_hash(&fs::read_to_string(&file)?, true))
+        );
+        assert_ne!(normalized.content_hash, pattern.content_hash);
+
+        // Same extraction as a directory walk, which stores the path relative to its root
+        let walked = scan_language_files_in_dir(temp_dir.path().to_str().unwrap(), "rust");
+        assert_eq!(walked[0].path, "lib.rs");
+        assert_eq!(
+            serde_json::to_value(FilePattern {
+                path: pattern.path.clone(),
+                ..walked[0].clone()
+            })?,
+            serde_json::to_value(&pattern)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scanned_paths_are_relative() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("src/models"))?;
+        fs::write(
+            temp_dir.path().join("src/models/user.rs"),
+            "pub struct User;",
+        )?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        assert!(root.is_absolute());
+
+        let results = scan_language_files_in_dir(root.to_str().unwrap(), "rust");
+        assert_eq!(Path::new(&results[0].path), Path::new("src/models/user.rs"));
+
+        let options = ScanOptions {
+            base_path: Some(root.clone()),
+            ..ScanOptions::default()
+        };
+        let src = root.join("src");
+        let results =
+            scan_language_files_in_dir_with_options(src.to_str().unwrap(), "rust", &options);
+        assert_eq!(Path::new(&results[0].path), Path::new("src/models/user.rs"));
+
+        let files = [src.join("models/user.rs")];
+        let results = scan_language_files(&files, "rust", &options);
+        assert_eq!(Path::new(&results[0].path), Path::new("src/models/user.rs"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_path() {
+        assert_eq!(
+            stored_path(Path::new("./src/lib.rs"), Path::new(".")),
+            "src/lib.rs"
+        );
+        assert_eq!(
+            stored_path(Path::new("src/lib.rs"), Path::new(".")),
+            "src/lib.rs"
+        );
+        assert_eq!(
+            stored_path(Path::new("/work/app/src/lib.rs"), Path::new("/work/app")),
+            "src/lib.rs"
+        );
+        // Outside the base, only the leading `./` goes
+        assert_eq!(
+            stored_path(Path::new("./lib.rs"), Path::new("/work/app")),
+            "lib.rs"
+        );
+        assert_eq!(
+            stored_path(Path::new("../shared/lib.rs"), Path::new("app")),
+            "../shared/lib.rs"
+        );
+    }
+
+    #[test]
+    fn test_scan_single_file_unsupported() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("notes.txt");
+        fs::write(&file, "fn not_code() {}")?;
+
+        assert_eq!(language_for_path(&file), None);
+        let options = ScanOptions::default();
+        assert!(scan_file(&file, "unsupported", &options).is_none());
+        assert!(scan_file(&temp_dir.path().join("missing.rs"), "rust", &options).is_none());
+        // An explicit language overrides the extension
+        assert_eq!(
+            scan_file(&file, "rust", &options).unwrap().functions,
+            vec!["not_code"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_source() {
+        let pattern = scan_source(
+            "def handler(): pass",
+            Path::new("<stdin>"),
+            "python",
+            &ScanOptions::default(),
+        )
+        .expect("python is supported");
+        assert_eq!(pattern.path, "<stdin>");
+        assert_eq!(pattern.functions, vec!["handler"]);
+        assert!(
+            scan_source(
+                "",
+                Path::new("<stdin>"),
+                "unsupported",
+                &ScanOptions::default()
+            )
+            .is_none()
+        );
+    }
+
     #[test]
     fn test_unsupported_language() {
         let results = scan_language_files_in_dir(".", "unsupported");
@@ -0,0 +1,182 @@
+use crate::pattern::{CodePattern, FilePattern};
+use crate::symbols;
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Aggregate metrics over a collection of scaffs
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScaffStats {
+    pub total_scaffs: usize,
+    /// Number of scaffs per language
+    pub languages: BTreeMap<String, usize>,
+    pub total_files: usize,
+    pub total_items: usize,
+    /// Scaff with the most items; the first one wins a tie
+    pub largest: Option<ScaffRef>,
+    /// Scaffs with the earliest and latest `created_at`; unparseable timestamps are skipped
+    pub oldest: Option<ScaffRef>,
+    pub newest: Option<ScaffRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScaffRef {
+    pub name: String,
+    pub items: usize,
+    pub created_at: String,
+}
+
+impl ScaffRef {
+    fn of(pattern: &CodePattern) -> Self {
+        ScaffRef {
+            name: pattern.name.clone(),
+            items: item_count(pattern),
+            created_at: pattern.created_at.clone(),
+        }
+    }
+}
+
+fn item_count(pattern: &CodePattern) -> usize {
+    pattern.files.iter().map(FilePattern::item_count).sum()
+}
+
+pub fn collect_stats(patterns: &[CodePattern]) -> ScaffStats {
+    let mut stats = ScaffStats {
+        total_scaffs: patterns.len(),
+        ..Default::default()
+    };
+
+    for pattern in patterns {
+        *stats.languages.entry(pattern.language.clone()).or_insert(0) += 1;
+        stats.total_files += pattern.files.len();
+        stats.total_items += item_count(pattern);
+    }
+
+    stats.largest = patterns
+        .iter()
+        .fold(None::<&CodePattern>, |largest, pattern| match largest {
+            Some(largest) if item_count(largest) >= item_count(pattern) => Some(largest),
+            _ => Some(pattern),
+        })
+        .map(ScaffRef::of);
+
+    let dated: Vec<(DateTime<FixedOffset>, &CodePattern)> = patterns
+        .iter()
+        .filter_map(|pattern| {
+            DateTime::parse_from_rfc3339(&pattern.created_at)
+                .ok()
+                .map(|created| (created, pattern))
+        })
+        .collect();
+    stats.oldest = dated
+        .iter()
+        .min_by_key(|(created, _)| *created)
+        .map(|(_, pattern)| ScaffRef::of(pattern));
+    stats.newest = dated
+        .iter()
+        .max_by_key(|(created, _)| *created)
+        .map(|(_, pattern)| ScaffRef::of(pattern));
+
+    stats
+}
+
+pub fn display_stats(stats: &ScaffStats) {
+    println!("\n{} Scaff Stats", symbols::STATS);
+    println!("{:-<50}", "");
+    println!("{} Scaffs: {}", symbols::PACKAGE, stats.total_scaffs);
+    println!("{} Files: {}", symbols::FOLDER, stats.total_files);
+    println!("{} Items: {}", symbols::ITEMS, stats.total_items);
+
+    if !stats.languages.is_empty() {
+        println!("\n{} Languages:", symbols::LANGUAGE);
+        for (language, count) in &stats.languages {
+            println!("  {}: {}", language, count);
+        }
+    }
+
+    if let Some(largest) = &stats.largest {
+        println!(
+            "\n{} Largest: {} ({} items)",
+            symbols::LARGEST,
+            largest.name,
+            largest.items
+        );
+    }
+    if let Some(oldest) = &stats.oldest {
+        println!(
+            "{} Oldest: {} ({})",
+            symbols::DATE,
+            oldest.name,
+            oldest.created_at
+        );
+    }
+    if let Some(newest) = &stats.newest {
+        println!(
+            "{} Newest: {} ({})",
+            symbols::NEW,
+            newest.name,
+            newest.created_at
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::SCHEMA_VERSION;
+
+    fn create_pattern(
+        name: &str,
+        language: &str,
+        functions: &[&str],
+        created_at: &str,
+    ) -> CodePattern {
+        CodePattern {
+            schema_version: SCHEMA_VERSION,
+            name: name.to_string(),
+            description: String::new(),
+            language: language.to_string(),
+            files: vec![FilePattern {
+                path: "src/lib.rs".to_string(),
+                extension: "rs".to_string(),
+                functions: functions.iter().map(|f| f.to_string()).collect(),
+                ..Default::default()
+            }],
+            directories: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            tags: Vec::new(),
+            created_at: created_at.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collect_stats() {
+        let patterns = vec![
+            create_pattern("api", "Rust", &["run", "stop"], "2024-03-01T00:00:00Z"),
+            create_pattern("web", "TypeScript", &["render"], "2024-01-01T00:00:00Z"),
+            create_pattern("cli", "Rust", &["a", "b", "c"], "2024-06-01T00:00:00+02:00"),
+            create_pattern("legacy", "Rust", &[], "yesterday"),
+        ];
+
+        let stats = collect_stats(&patterns);
+        assert_eq!(stats.total_scaffs, 4);
+        assert_eq!(stats.total_files, 4);
+        assert_eq!(stats.total_items, 6);
+        assert_eq!(
+            stats.languages,
+            BTreeMap::from([("Rust".to_string(), 3), ("TypeScript".to_string(), 1)])
+        );
+        assert_eq!(stats.largest.unwrap().name, "cli");
+        assert_eq!(stats.oldest.unwrap().name, "web");
+        assert_eq!(stats.newest.unwrap().name, "cli");
+    }
+
+    #[test]
+    fn test_collect_stats_empty() {
+        let stats = collect_stats(&[]);
+        assert_eq!(stats.total_scaffs, 0);
+        assert!(stats.largest.is_none() && stats.oldest.is_none() && stats.newest.is_none());
+    }
+}
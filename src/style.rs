@@ -0,0 +1,106 @@
+use owo_colors::{OwoColorize, Style};
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Color styled text from now on
+pub fn set_color(color: bool) {
+    COLOR.store(color, Ordering::Relaxed);
+}
+
+/// Whether output should be plain, with ASCII symbols and no color: asked for with a
+/// flag (--no-emoji for symbols, --no-color for color), `NO_COLOR` set to anything
+/// non-empty, or stdout not a terminal (CI logs, pipes, redirects)
+pub fn prefers_plain(asked: bool) -> bool {
+    plain_for(
+        asked,
+        std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+        std::io::stdout().is_terminal(),
+    )
+}
+
+// `prefers_plain` given whether `NO_COLOR` is set and stdout is a terminal
+fn plain_for(asked: bool, no_color: bool, terminal: bool) -> bool {
+    asked || no_color || !terminal
+}
+
+/// Text displayed in a style while color is on, and as is otherwise
+#[derive(Debug, Clone, Copy)]
+pub struct Styled<T> {
+    text: T,
+    style: Style,
+}
+
+impl<T: fmt::Display> Styled<T> {
+    fn render(&self, color: bool) -> String {
+        if color {
+            self.text.style(self.style).to_string()
+        } else {
+            self.text.to_string()
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(COLOR.load(Ordering::Relaxed)))
+    }
+}
+
+/// Missing or failing
+pub fn red<T>(text: T) -> Styled<T> {
+    Styled {
+        text,
+        style: Style::new().red(),
+    }
+}
+
+/// Extra or changed, worth a look but not failing
+pub fn yellow<T>(text: T) -> Styled<T> {
+    Styled {
+        text,
+        style: Style::new().yellow(),
+    }
+}
+
+/// Matching or passing
+pub fn green<T>(text: T) -> Styled<T> {
+    Styled {
+        text,
+        style: Style::new().green(),
+    }
+}
+
+/// Headings
+pub fn bold<T>(text: T) -> Styled<T> {
+    Styled {
+        text,
+        style: Style::new().bold(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_styled_render() {
+        assert_eq!(green("all").render(false), "all");
+
+        let colored = red("run").render(true);
+        assert!(colored.contains("\x1b["));
+        assert!(colored.contains("run"));
+    }
+
+    #[test]
+    fn test_plain_output() {
+        // In a terminal, output is plain only when asked for or NO_COLOR is set
+        assert!(!plain_for(false, false, true));
+        assert!(plain_for(true, false, true));
+        assert!(plain_for(false, true, true));
+        // Outside one, always
+        assert!(plain_for(false, false, false));
+    }
+}
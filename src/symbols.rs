@@ -0,0 +1,100 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Print the ASCII form of every symbol from now on
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// A marker prefixed to user-facing output, displayed as emoji or as its ASCII form
+#[derive(Debug, Clone, Copy)]
+pub struct Symbol {
+    emoji: &'static str,
+    ascii: &'static str,
+}
+
+impl Symbol {
+    fn as_str(self, ascii: bool) -> &'static str {
+        if ascii { self.ascii } else { self.emoji }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str(ASCII.load(Ordering::Relaxed)))
+    }
+}
+
+const fn symbol(emoji: &'static str, ascii: &'static str) -> Symbol {
+    Symbol { emoji, ascii }
+}
+
+// Emoji that render narrow in most terminals carry a trailing space to keep text aligned
+
+pub const OK: Symbol = symbol("✅", "[OK]");
+pub const FAIL: Symbol = symbol("❌", "[FAIL]");
+pub const WARN: Symbol = symbol("⚠️ ", "[WARN]");
+pub const TIP: Symbol = symbol("💡", "[TIP]");
+pub const INFO: Symbol = symbol("ℹ️", "[INFO]");
+pub const SEARCH: Symbol = symbol("🔍", "[*]");
+pub const SUCCESS: Symbol = symbol("🎉", "[OK]");
+#[cfg(feature = "watch")]
+pub const BYE: Symbol = symbol("👋", "[BYE]");
+#[cfg(feature = "watch")]
+pub const WATCH: Symbol = symbol("👀", "[WATCH]");
+pub const FIX: Symbol = symbol("🔧", "[FIX]");
+pub const RETRY: Symbol = symbol("🔁", "[RETRY]");
+pub const SAVE: Symbol = symbol("💾", "[SAVE]");
+pub const CLEAN: Symbol = symbol("🧹", "[CLEAN]");
+pub const BUILD: Symbol = symbol("🏗️", "[GEN]");
+pub const UPGRADE: Symbol = symbol("⬆️", "[UP]");
+pub const DOCTOR: Symbol = symbol("🩺", "[DOCTOR]");
+pub const PASSED: Symbol = symbol("✓", "[OK]");
+pub const FAILED: Symbol = symbol("✗", "[FAIL]");
+pub const NOTE: Symbol = symbol("📝", "[NOTE]");
+pub const LIST_ITEM: Symbol = symbol("📋", "-");
+pub const BULLET: Symbol = symbol("•", "-");
+
+pub const FOLDER: Symbol = symbol("📁", "[DIR]");
+pub const OPEN_FOLDER: Symbol = symbol("📂", "[DIR]");
+pub const FILE: Symbol = symbol("📄", "[FILE]");
+pub const PACKAGE: Symbol = symbol("📦", "[PKG]");
+pub const CONFIG: Symbol = symbol("⚙️", "[CFG]");
+pub const TEMPLATES: Symbol = symbol("🎨", "[TPL]");
+pub const LOG: Symbol = symbol("🪵", "[LOG]");
+pub const TREE: Symbol = symbol("🌳", "[TS]");
+
+pub const STATS: Symbol = symbol("📊", "[STATS]");
+pub const SCORE: Symbol = symbol("📈", "[SCORE]");
+pub const LANGUAGE: Symbol = symbol("🔤", "[LANG]");
+pub const COUNT: Symbol = symbol("🔢", "[#]");
+pub const ITEMS: Symbol = symbol("🧩", "[ITEMS]");
+pub const LARGEST: Symbol = symbol("🏆", "[TOP]");
+pub const DATE: Symbol = symbol("📅", "[DATE]");
+pub const NEW: Symbol = symbol("🆕", "[NEW]");
+pub const TAG: Symbol = symbol("🏷️ ", "[TAG]");
+pub const INCLUDE: Symbol = symbol("🔎", "[INCL]");
+pub const EXCLUDE: Symbol = symbol("🚫", "[EXCL]");
+
+pub const DIFF: Symbol = symbol("🔀", "[DIFF]");
+pub const ADDED: Symbol = symbol("➕", "[+]");
+pub const REMOVED: Symbol = symbol("➖", "[-]");
+pub const CHANGED: Symbol = symbol("🔄", "[~]");
+pub const HASH: Symbol = symbol("🧬", "[HASH]");
+pub const SIGNATURE: Symbol = symbol("✏️ ", "[SIG]");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checked without `set_ascii`, which would race other tests printing symbols
+    #[test]
+    fn test_symbol_forms() {
+        assert_eq!(OK.as_str(false), "✅");
+        assert_eq!(WARN.as_str(false), "⚠️ ");
+        assert_eq!(OK.as_str(true), "[OK]");
+        assert_eq!(WARN.as_str(true), "[WARN]");
+    }
+}
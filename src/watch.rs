@@ -0,0 +1,172 @@
+use crate::scanner::SUPPORTED_LANGUAGES;
+use crate::symbols;
+use crate::validator::{ArchitectureValidator, ValidationResult};
+use log::{debug, info, warn};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+/// How long the tree must stay quiet after a change before re-validating, so an
+/// editor's burst of save events triggers a single run
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// How often the loop wakes up to check for Ctrl-C while idle
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Directories whose changes never affect validation
+const IGNORED_DIRS: &[&str] = &["target", ".git", "node_modules", "dist", ".scaff"];
+
+/// Validate `base_dir` against the scaff, then again after every relevant change to
+/// the directory or to the validator's scaffs directory, until interrupted with Ctrl-C. Each
+/// result is shown with `display`.
+pub fn watch_and_validate(
+    validator: &ArchitectureValidator,
+    scaff_name: &str,
+    base_dir: &Path,
+    display: &dyn Fn(&ValidationResult),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))?;
+
+    let watched_dir = fs::canonicalize(base_dir)?;
+    let scaffs_dir = validator.scaff_directory().path().to_path_buf();
+    let scaffs_dir = fs::canonicalize(&scaffs_dir).unwrap_or(scaffs_dir);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&watched_dir, RecursiveMode::Recursive)?;
+    if scaffs_dir.is_dir() && !scaffs_dir.starts_with(&watched_dir) {
+        watcher.watch(&scaffs_dir, RecursiveMode::NonRecursive)?;
+    }
+    info!(
+        "Watching {} and {}",
+        watched_dir.display(),
+        scaffs_dir.display()
+    );
+
+    run_validation(validator, scaff_name, base_dir, display);
+
+    while running.load(Ordering::SeqCst) {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) if is_relevant_event(&event, &scaffs_dir) => {
+                debug!("Change detected: {:?}", event.paths);
+                // Wait for the burst to settle; the events in it don't matter
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+                run_validation(validator, scaff_name, base_dir, display);
+            }
+            Ok(Ok(_)) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Ok(Err(e)) => warn!("Watch error: {}", e),
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    println!("\n{} Stopped watching", symbols::BYE);
+    Ok(())
+}
+
+fn run_validation(
+    validator: &ArchitectureValidator,
+    scaff_name: &str,
+    base_dir: &Path,
+    display: &dyn Fn(&ValidationResult),
+) {
+    // Clear the screen and move the cursor home so each run replaces the last
+    print!("\x1B[2J\x1B[1;1H");
+    println!(
+        "{} Watching {} against scaff '{}' (Ctrl-C to stop)",
+        symbols::WATCH,
+        base_dir.display(),
+        scaff_name
+    );
+
+    match validator.validate_against_scaff(scaff_name, base_dir) {
+        Ok(result) => display(&result),
+        Err(e) => println!("{} Validation failed: {}", symbols::FAIL, e),
+    }
+}
+
+fn is_relevant_event(event: &Event, scaffs_dir: &Path) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    // Directories appearing or disappearing matter for required directories
+    let structural = matches!(event.kind, EventKind::Create(_) | EventKind::Remove(_));
+
+    event
+        .paths
+        .iter()
+        .any(|path| is_relevant_path(path, scaffs_dir, structural))
+}
+
+// Scaff files, and source files of any supported language outside build output
+fn is_relevant_path(path: &Path, scaffs_dir: &Path, structural: bool) -> bool {
+    if path.starts_with(scaffs_dir) {
+        return path.extension().and_then(|s| s.to_str()) == Some("json");
+    }
+
+    if path
+        .components()
+        .any(|c| IGNORED_DIRS.contains(&c.as_os_str().to_string_lossy().as_ref()))
+    {
+        return false;
+    }
+
+    match path.extension().and_then(|s| s.to_str()) {
+        Some(extension) => SUPPORTED_LANGUAGES
+            .iter()
+            .any(|config| config.extensions.contains(&extension)),
+        None => structural,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_relevant_path() {
+        let scaffs_dir = Path::new("/work/app/scaffs");
+
+        assert!(is_relevant_path(
+            Path::new("/work/app/src/main.rs"),
+            scaffs_dir,
+            false
+        ));
+        assert!(is_relevant_path(
+            Path::new("/work/app/scaffs/api.json"),
+            scaffs_dir,
+            false
+        ));
+        assert!(!is_relevant_path(
+            Path::new("/work/app/scaffs/api.json.swp"),
+            scaffs_dir,
+            false
+        ));
+        assert!(!is_relevant_path(
+            Path::new("/work/app/target/debug/build.rs"),
+            scaffs_dir,
+            false
+        ));
+        assert!(!is_relevant_path(
+            Path::new("/work/app/notes.txt"),
+            scaffs_dir,
+            false
+        ));
+
+        // Extensionless paths only count when something was created or removed
+        assert!(is_relevant_path(
+            Path::new("/work/app/src/services"),
+            scaffs_dir,
+            true
+        ));
+        assert!(!is_relevant_path(
+            Path::new("/work/app/src/services"),
+            scaffs_dir,
+            false
+        ));
+    }
+}
@@ -42,6 +42,1717 @@ fn test_scan_rust() {
         .stdout(predicate::str::contains("test.rs"));
 }
 
+#[test]
+fn test_scan_stream_json() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a.rs"), "fn a() {}").unwrap();
+    fs::write(temp_dir.path().join("b.rs"), "fn b() {}").unwrap();
+
+    let output = scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("rust")
+        .arg("--stream-json")
+        .env("RUST_LOG", "off")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let files: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(files.len(), 2);
+}
+
+#[test]
+fn test_scan_truncates_long_item_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let long_name = "a".repeat(200);
+    fs::write(
+        temp_dir.path().join("long.js"),
+        format!("function {}() {{}}", long_name),
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("javascript")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{}...", "a".repeat(77))))
+        .stdout(predicate::str::contains(long_name).not());
+}
+
+#[test]
+fn test_scan_with_path() {
+    let temp_dir = TempDir::new().unwrap();
+    let work_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("scanned.rs"), "fn main() {}").unwrap();
+    fs::create_dir(temp_dir.path().join("scaffs")).unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("rust")
+        .arg("--path")
+        .arg(temp_dir.path())
+        .current_dir(work_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("scanned.rs"));
+
+    // The scan cache belongs to the scanned directory's project, not the current one
+    assert!(temp_dir.path().join(".scaff/cache.json").exists());
+    assert!(!work_dir.path().join(".scaff").exists());
+}
+
+#[test]
+fn test_scan_in_subdirectory_keeps_project_root() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path();
+    let src = repo.join("src");
+    fs::create_dir(repo.join(".git")).unwrap();
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("lib.rs"), "pub fn run() {}").unwrap();
+
+    // Outside a project root there's nothing to cache into, and scanning mustn't
+    // create a `.scaff/` that would make `src` a root of its own
+    scaff_cmd()
+        .args(["scan", "--language", "rust"])
+        .current_dir(&src)
+        .assert()
+        .success();
+    assert!(!src.join(".scaff").exists());
+
+    scaff_cmd()
+        .args(["save", "demo", "--language", "rust"])
+        .current_dir(repo)
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .args(["scan", "--language", "rust"])
+        .current_dir(&src)
+        .assert()
+        .success();
+    assert!(repo.join(".scaff/cache.json").exists());
+    assert!(!src.join(".scaff").exists());
+
+    // Found in the repo's scaffs/; `src` on its own deviates from it, which is fine
+    scaff_cmd()
+        .args(["validate", "demo"])
+        .current_dir(&src)
+        .assert()
+        .stdout(predicate::str::contains("Architecture Validation Results"))
+        .stdout(predicate::str::contains("not found").not());
+}
+
+#[test]
+fn test_scan_missing_path_fails() {
+    let temp_dir = TempDir::new().unwrap();
+
+    scaff_cmd()
+        .args(["scan", "--language", "rust", "--path", "does-not-exist"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Directory 'does-not-exist' does not exist",
+        ))
+        .stdout(predicate::str::contains("No supported files found").not());
+}
+
+#[test]
+fn test_save_and_validate_with_path() {
+    let project_dir = TempDir::new().unwrap();
+    let work_dir = TempDir::new().unwrap();
+    fs::write(project_dir.path().join("lib.rs"), "fn helper() {}").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("path_pattern")
+        .arg("--language")
+        .arg("rust")
+        .arg("--path")
+        .arg(project_dir.path())
+        .current_dir(work_dir.path())
+        .assert()
+        .success();
+    let saved = fs::read_to_string(work_dir.path().join("scaffs/path_pattern.json")).unwrap();
+    let pattern: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    // Stored relative to the scanned directory, not as the absolute path scanned
+    assert_eq!(pattern["files"][0]["path"], "lib.rs");
+
+    // So it validates a copy of the project anywhere else
+    let copy_dir = TempDir::new().unwrap();
+    fs::write(copy_dir.path().join("lib.rs"), "fn helper() {}").unwrap();
+    scaff_cmd()
+        .arg("validate")
+        .arg("path_pattern")
+        .arg("--path")
+        .arg(copy_dir.path())
+        .current_dir(work_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Architecture is VALID"));
+
+    scaff_cmd()
+        .arg("validate")
+        .arg("path_pattern")
+        .arg("--path")
+        .arg(project_dir.path())
+        .current_dir(work_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Architecture is VALID"));
+}
+
+#[test]
+fn test_display_name_resolves_for_generate_and_validate() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Named;").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("My Named Pattern")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    assert!(
+        temp_dir
+            .path()
+            .join("scaffs/my_named_pattern.json")
+            .exists()
+    );
+
+    for name in ["My Named Pattern", "my_named_pattern"] {
+        scaff_cmd()
+            .arg("validate")
+            .arg(name)
+            .current_dir(temp_dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Architecture is VALID"));
+    }
+
+    let output_dir = temp_dir.path().join("generated");
+    scaff_cmd()
+        .arg("generate")
+        .arg("My Named Pattern")
+        .arg("--output")
+        .arg(&output_dir)
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    assert!(output_dir.join("lib.rs").exists());
+}
+
+#[test]
+fn test_validate_generated_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(
+        temp_dir.path().join("src/models.rs"),
+        "struct User;\nenum Role { Admin }\nfn load_users() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("models")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("models")
+        .arg("--output")
+        .arg("generated")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .arg("validate")
+        .arg("models")
+        .arg("--path")
+        .arg("generated")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Architecture is VALID"));
+
+    scaff_cmd()
+        .arg("validate")
+        .arg("models")
+        .arg("--path")
+        .arg("does-not-exist")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Directory 'does-not-exist' does not exist",
+        ));
+}
+
+#[test]
+fn test_validate_missing_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let services = temp_dir.path().join("src/services");
+    fs::create_dir_all(&services).unwrap();
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(services.join("user.rs"), "struct UserService;").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("layered")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    fs::remove_dir_all(&services).unwrap();
+    scaff_cmd()
+        .arg("validate")
+        .arg("layered")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Missing Directories (1)"))
+        .stdout(predicate::str::contains("src/services/"));
+
+    // An empty directory is enough to satisfy the structure, though its file is
+    // still missing
+    fs::create_dir_all(&services).unwrap();
+    scaff_cmd()
+        .arg("validate")
+        .arg("layered")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Missing Directories").not());
+}
+
+#[test]
+fn test_validate_honors_saved_exclude() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("tests")).unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Core;").unwrap();
+    fs::write(temp_dir.path().join("tests/core.rs"), "fn it_works() {}").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("no_tests")
+        .arg("--language")
+        .arg("rust")
+        .arg("--exclude")
+        .arg("tests/**")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let saved = fs::read_to_string(temp_dir.path().join("scaffs/no_tests.json")).unwrap();
+    let pattern: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    assert_eq!(pattern["exclude"], serde_json::json!(["tests/**"]));
+    assert_eq!(pattern["files"].as_array().unwrap().len(), 1);
+
+    fs::write(
+        temp_dir.path().join("tests/more.rs"),
+        "fn it_also_works() {}",
+    )
+    .unwrap();
+    scaff_cmd()
+        .arg("validate")
+        .arg("no_tests")
+        .arg("--strict")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Architecture is VALID"))
+        .stdout(predicate::str::contains("Extra Files").not());
+}
+
+#[test]
+fn test_validate_fix_creates_missing_file() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(
+        temp_dir.path().join("src/models.rs"),
+        "pub struct User;\npub fn load_user() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .args(["save", "app", "--language", "rust"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    fs::remove_file(temp_dir.path().join("src/models.rs")).unwrap();
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() { run(); }").unwrap();
+
+    scaff_cmd()
+        .args(["validate", "app", "--fix"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created 1 missing file(s)"))
+        .stdout(predicate::str::contains("Missing Files").not());
+
+    let models = fs::read_to_string(temp_dir.path().join("src/models.rs")).unwrap();
+    assert!(models.contains("pub struct User"));
+    assert!(models.contains("pub fn load_user"));
+    // Existing files and the manifest are left alone
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("src/main.rs")).unwrap(),
+        "fn main() { run(); }"
+    );
+    assert!(!temp_dir.path().join("Cargo.toml").exists());
+}
+
+#[test]
+fn test_generate_target_language() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(
+        temp_dir.path().join("src/models.rs"),
+        "pub struct User;\npub fn load_user() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("models")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("models")
+        .arg("--target-language")
+        .arg("js")
+        .arg("--output")
+        .arg("out")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully generated"));
+
+    let content = fs::read_to_string(temp_dir.path().join("out/src/models.js")).unwrap();
+    assert!(content.contains("class User"));
+    assert!(content.contains("function load_user"));
+    assert!(temp_dir.path().join("out/package.json").exists());
+    assert!(!temp_dir.path().join("out/Cargo.toml").exists());
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("models")
+        .arg("--target-language")
+        .arg("go")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "possible values: rust, javascript, typescript, python",
+        ));
+}
+
+#[test]
+fn test_generate_python() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("service.py"),
+        "class Service:\n    pass\n\ndef start():\n    pass\n",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("service")
+        .arg("--language")
+        .arg("python")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("service")
+        .arg("--output")
+        .arg("out")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully generated"));
+
+    let content = fs::read_to_string(temp_dir.path().join("out/service.py")).unwrap();
+    assert!(content.contains("class Service:"));
+    assert!(content.contains("def start():"));
+    assert!(temp_dir.path().join("out/pyproject.toml").exists());
+}
+
+#[test]
+fn test_parse_errors_reported_and_strict_parse() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("good.rs"), "pub struct Good;").unwrap();
+    fs::write(
+        temp_dir.path().join("broken.rs"),
+        "pub struct Broken {\n    field:\n",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Files with parse errors (1)"))
+        .stdout(predicate::str::contains("broken.rs"));
+
+    scaff_cmd()
+        .arg("save")
+        .arg("strict")
+        .arg("--language")
+        .arg("rust")
+        .arg("--strict-parse")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Files with parse errors (1)"))
+        .stdout(predicate::str::contains("Successfully saved").not());
+    assert!(!temp_dir.path().join("scaffs/strict.json").exists());
+
+    scaff_cmd()
+        .arg("save")
+        .arg("lenient")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully saved"));
+}
+
+#[test]
+fn test_scan_and_save_depth() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("app/core")).unwrap();
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {}").unwrap();
+    fs::write(temp_dir.path().join("app/mod.rs"), "pub struct App;").unwrap();
+    fs::write(
+        temp_dir.path().join("app/core/engine.rs"),
+        "pub struct Engine;",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("rust")
+        .arg("--depth")
+        .arg("2")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("App"))
+        .stdout(predicate::str::contains("Engine").not());
+
+    scaff_cmd()
+        .arg("save")
+        .arg("shallow")
+        .arg("--language")
+        .arg("rust")
+        .arg("--depth")
+        .arg("2")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let saved = fs::read_to_string(temp_dir.path().join("scaffs/shallow.json")).unwrap();
+    assert!(saved.contains("app/mod.rs"));
+    assert!(!saved.contains("engine.rs"));
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--depth")
+        .arg("0")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_save_interactive_without_terminal_saves_all_files() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a.rs"), "pub struct A;").unwrap();
+    fs::write(temp_dir.path().join("b.rs"), "pub struct B;").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("picked")
+        .arg("--language")
+        .arg("rust")
+        .arg("--interactive")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("saving all 2 scanned files"))
+        .stdout(predicate::str::contains("Successfully saved"));
+}
+
+#[test]
+fn test_save_with_description_and_tags() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "pub struct Api;").unwrap();
+
+    for (name, tag) in [("api", "backend"), ("ui", "frontend")] {
+        scaff_cmd()
+            .arg("save")
+            .arg(name)
+            .arg("--language")
+            .arg("rust")
+            .arg("--description")
+            .arg(format!("The {} layer", name))
+            .arg("--tag")
+            .arg(tag)
+            .arg("--tag")
+            .arg("team-a")
+            .current_dir(temp_dir.path())
+            .assert()
+            .success();
+    }
+
+    let saved = fs::read_to_string(temp_dir.path().join("scaffs/api.json")).unwrap();
+    let pattern: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    assert_eq!(pattern["description"], "The api layer");
+    assert_eq!(pattern["tags"], serde_json::json!(["backend", "team-a"]));
+
+    scaff_cmd()
+        .arg("list")
+        .arg("--tag")
+        .arg("backend")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- api"))
+        .stdout(predicate::str::contains("Tags: backend, team-a"))
+        .stdout(predicate::str::contains("- ui").not());
+
+    scaff_cmd()
+        .arg("list")
+        .arg("--tag")
+        .arg("team-a")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- api"))
+        .stdout(predicate::str::contains("- ui"));
+
+    scaff_cmd()
+        .arg("list")
+        .arg("--tag")
+        .arg("mobile")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No scaffs match the given filters",
+        ));
+}
+
+#[test]
+fn test_list_sort_and_language() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "pub struct Api;").unwrap();
+    fs::write(temp_dir.path().join("app.js"), "class App {}").unwrap();
+
+    for (name, language) in [("zeta", "rust"), ("alpha", "javascript")] {
+        scaff_cmd()
+            .arg("save")
+            .arg(name)
+            .arg("--language")
+            .arg(language)
+            .current_dir(temp_dir.path())
+            .assert()
+            .success();
+    }
+
+    let output = scaff_cmd()
+        .arg("list")
+        .arg("--sort")
+        .arg("name")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.find("- alpha").unwrap() < stdout.find("- zeta").unwrap());
+
+    scaff_cmd()
+        .arg("list")
+        .arg("--language")
+        .arg("RUST")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- zeta"))
+        .stdout(predicate::str::contains("- alpha").not());
+}
+
+#[test]
+fn test_validate_conformance_score() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        "pub struct A;\npub fn b() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("conformance")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .arg("validate")
+        .arg("conformance")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Conformance: 100%"));
+
+    // Drop one of the file's two items: (1 file + 1 item) / (1 file + 2 items)
+    fs::write(temp_dir.path().join("lib.rs"), "pub struct A;").unwrap();
+    let output = scaff_cmd()
+        .arg("validate")
+        .arg("conformance")
+        .arg("--format")
+        .arg("json")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["is_valid"], false);
+    let score = result["conformance_score"].as_f64().unwrap();
+    assert!((score - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(result["missing_items"][0]["item_name"], "b");
+}
+
+#[test]
+fn test_validate_fuzzy_rename() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "pub fn get_name() {}").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("names")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    fs::write(temp_dir.path().join("lib.rs"), "pub fn get_names() {}").unwrap();
+    scaff_cmd()
+        .arg("validate")
+        .arg("names")
+        .arg("--fuzzy")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "function 'get_name' -> 'get_names'",
+        ))
+        .stdout(predicate::str::contains("Missing Items").not());
+
+    scaff_cmd()
+        .arg("validate")
+        .arg("names")
+        .arg("--fuzzy")
+        .arg("--fuzzy-threshold")
+        .arg("1.5")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_validate_optional_items() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        "pub fn handle() {}\npub fn health_check() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("service")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    // Mark health_check optional by hand, as `save --interactive` would
+    let scaff_path = temp_dir.path().join("scaffs/service.json");
+    let mut pattern: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&scaff_path).unwrap()).unwrap();
+    pattern["files"][0]["optional"] =
+        serde_json::json!([{ "item_type": "function", "name": "health_check" }]);
+    fs::write(&scaff_path, pattern.to_string()).unwrap();
+
+    fs::write(temp_dir.path().join("lib.rs"), "pub fn handle() {}").unwrap();
+    scaff_cmd()
+        .arg("validate")
+        .arg("service")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Architecture is VALID"))
+        .stdout(predicate::str::contains(
+            "Consider adding optional function 'health_check' to lib.rs",
+        ));
+}
+
+#[test]
+fn test_project_root_found_from_nested_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "pub struct Root;").unwrap();
+    let nested = temp_dir.path().join("app/src");
+    fs::create_dir_all(&nested).unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("root_scaff")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    // Two directories down, the scaffs/ marker at the top is still found
+    scaff_cmd()
+        .arg("list")
+        .current_dir(&nested)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- root_scaff"));
+
+    // Saving from the nested directory writes to the root's scaffs/ too
+    fs::write(nested.join("main.rs"), "fn main() {}").unwrap();
+    scaff_cmd()
+        .arg("save")
+        .arg("nested_scaff")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(&nested)
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("scaffs/nested_scaff.json").exists());
+    assert!(!nested.join("scaffs").exists());
+
+    let root = temp_dir.path().canonicalize().unwrap();
+    scaff_cmd()
+        .arg("config")
+        .arg("path")
+        .current_dir(&nested)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Project root: {}",
+            root.display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "Scaffs directory: {}",
+            root.join("scaffs").display()
+        )));
+}
+
+#[test]
+fn test_stats_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let scaffs_dir = temp_dir.path().join("scaffs");
+    fs::create_dir_all(&scaffs_dir).unwrap();
+    for (name, language, created_at) in [
+        ("api", "Rust", "2024-01-01T00:00:00Z"),
+        ("web", "TypeScript", "2024-02-01T00:00:00Z"),
+    ] {
+        fs::write(
+            scaffs_dir.join(format!("{}.json", name)),
+            format!(
+                r#"{{
+  "name": "{}",
+  "description": "",
+  "language": "{}",
+  "files": [{{ "path": "src/main.rs", "extension": "rs", "classes": [], "functions": ["main"], "structs": [], "implementations": [] }}],
+  "created_at": "{}"
+}}"#,
+                name, language, created_at
+            ),
+        )
+        .unwrap();
+    }
+
+    let assert = scaff_cmd()
+        .args(["stats", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let stats: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(stats["total_scaffs"], 2);
+    assert_eq!(stats["total_items"], 2);
+    assert_eq!(stats["languages"]["TypeScript"], 1);
+    assert_eq!(stats["oldest"]["name"], "api");
+    assert_eq!(stats["newest"]["name"], "web");
+
+    scaff_cmd()
+        .arg("stats")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[PKG] Scaffs: 2"));
+}
+
+#[test]
+fn test_scan_cache_and_clear() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "pub fn run() {}").unwrap();
+    fs::create_dir(temp_dir.path().join("scaffs")).unwrap();
+    let cache_file = temp_dir.path().join(".scaff/cache.json");
+
+    scaff_cmd()
+        .args(["scan", "--language", "rust", "--no-cache"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    assert!(!cache_file.exists());
+
+    for _ in 0..2 {
+        scaff_cmd()
+            .args(["scan", "--language", "rust"])
+            .current_dir(temp_dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("run"));
+    }
+    assert!(cache_file.exists());
+
+    scaff_cmd()
+        .args(["cache", "clear"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cleared scan cache"));
+    assert!(!cache_file.exists());
+}
+
+#[test]
+fn test_verbose_flag_sets_log_level() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "pub fn run() {}").unwrap();
+
+    scaff_cmd()
+        .args(["-vv", "scan", "--language", "rust", "--no-cache"])
+        .env_remove("RUST_LOG")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("DEBUG"))
+        .stderr(predicate::str::contains("Found rust file"));
+
+    scaff_cmd()
+        .args(["scan", "--language", "rust", "--no-cache"])
+        .env_remove("RUST_LOG")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Found rust file").not());
+
+    // RUST_LOG overrides the flag
+    scaff_cmd()
+        .args(["scan", "-vv", "--language", "rust", "--no-cache"])
+        .env("RUST_LOG", "error")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Found rust file").not());
+}
+
+#[test]
+// Piped output uses the ASCII form of every symbol (the --no-emoji decision itself is
+// unit tested in symbols.rs), so any non-ASCII here was printed around `symbols`
+fn test_piped_output_is_ascii() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(
+        temp_dir.path().join("src/models.rs"),
+        "pub struct User;\npub fn load_user() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .args(["save", "app", "--language", "rust"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    fs::remove_file(temp_dir.path().join("src/models.rs")).unwrap();
+    fs::write(temp_dir.path().join("src/extra.rs"), "pub fn extra() {}").unwrap();
+
+    // Validation fails on the removed file, and its report should be ASCII too
+    for (args, succeeds) in [
+        (vec!["validate", "app"], false),
+        (vec!["scan", "--language", "rust"], true),
+        (vec!["show", "app"], true),
+        (vec!["stats"], true),
+    ] {
+        let output = scaff_cmd()
+            .args(&args)
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        assert_eq!(output.status.success(), succeeds, "{:?}", args);
+        let stdout = &output.stdout;
+        assert!(stdout.is_ascii(), "non-ASCII output from {:?}", args);
+    }
+}
+
+// Output that isn't a terminal is never colored; how NO_COLOR and --no-color turn
+// color off in one is covered by the style module's unit tests
+#[test]
+fn test_piped_output_has_no_escape_codes() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        "pub fn kept() {}\npub fn gone() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .args(["save", "colors", "--language", "rust"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        "pub fn kept() {}\npub fn added() {}",
+    )
+    .unwrap();
+
+    for args in [
+        vec!["validate", "colors"],
+        vec!["scan", "--language", "rust"],
+        vec!["scan", "--language", "all"],
+    ] {
+        // The validation fails, which is fine here
+        let output = scaff_cmd()
+            .args(&args)
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        assert!(!stdout.contains('\x1b'), "escape codes from {:?}", args);
+    }
+}
+
+#[test]
+fn test_template_list_and_show_builtins() {
+    let temp_dir = TempDir::new().unwrap();
+
+    scaff_cmd()
+        .args(["template", "list"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("default_rust_file (built-in)"))
+        .stdout(predicate::str::contains("default_js_file (built-in)"))
+        .stdout(predicate::str::contains("default_cargo_toml (built-in)"))
+        .stdout(predicate::str::contains("default_package_json (built-in)"));
+
+    scaff_cmd()
+        .args(["template", "show", "default_rust_file"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pub struct {{name}}"));
+
+    fs::create_dir_all(temp_dir.path().join("templates")).unwrap();
+    fs::write(
+        temp_dir.path().join("templates/rust_file.hbs"),
+        "// custom {{pattern_name}}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .args(["template", "list"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust_file (templates"));
+    scaff_cmd()
+        .args(["template", "show", "rust_file"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout("// custom {{pattern_name}}");
+}
+
+#[test]
+fn test_generate_with_vars() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("src/lib.rs"), "pub fn run() {}").unwrap();
+    fs::create_dir_all(temp_dir.path().join("templates")).unwrap();
+    fs::write(
+        temp_dir.path().join("templates/rust_file.hbs"),
+        "// Author: {{vars.author}}\n",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .args(["save", "app", "--language", "rust"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .args([
+            "generate",
+            "app",
+            "--output",
+            "out",
+            "--var",
+            "author=Jane Doe",
+        ])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("out/src/lib.rs")).unwrap(),
+        "// Author: Jane Doe\n"
+    );
+
+    scaff_cmd()
+        .args(["generate", "app", "--var", "=oops"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected KEY=VALUE"));
+}
+
+#[test]
+fn test_scan_format_json() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(
+        temp_dir.path().join("src/lib.rs"),
+        "pub struct Config;\npub fn run() {}",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("app.js"), "function start() {}").unwrap();
+
+    let assert = scaff_cmd()
+        .args(["scan", "--language", "rust", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    let files: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let files = files.as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["structs"], serde_json::json!(["Config"]));
+    assert_eq!(files[0]["functions"], serde_json::json!(["run"]));
+
+    let assert = scaff_cmd()
+        .args(["scan", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    let results: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let languages: Vec<&str> = results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result[0].as_str().unwrap())
+        .collect();
+    assert!(languages.contains(&"Rust") && languages.contains(&"JavaScript"));
+}
+
+#[test]
+fn test_doctor_reports_problems() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("scaffs")).unwrap();
+    fs::write(temp_dir.path().join("scaffs/broken.json"), "{ not json").unwrap();
+    fs::create_dir_all(temp_dir.path().join(".scaff")).unwrap();
+    fs::write(temp_dir.path().join(".scaff/config.json"), "{ not json").unwrap();
+
+    scaff_cmd()
+        .arg("doctor")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "broken.json: failed to parse pattern",
+        ))
+        .stdout(predicate::str::contains("config.json is invalid"))
+        .stdout(predicate::str::contains("2 problem(s) found"));
+
+    fs::remove_file(temp_dir.path().join("scaffs/broken.json")).unwrap();
+    fs::write(
+        temp_dir.path().join(".scaff/config.json"),
+        r#"{ "default_scaff": "gone" }"#,
+    )
+    .unwrap();
+    scaff_cmd()
+        .arg("doctor")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Default scaff 'gone' doesn't exist",
+        ))
+        .stdout(predicate::str::contains("1 problem(s) found"));
+
+    fs::remove_file(temp_dir.path().join(".scaff/config.json")).unwrap();
+    scaff_cmd()
+        .arg("doctor")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_update_keeps_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("src/lib.rs"), "pub struct Config;").unwrap();
+    fs::write(temp_dir.path().join("src/old.rs"), "pub fn legacy() {}").unwrap();
+
+    scaff_cmd()
+        .args([
+            "save",
+            "api",
+            "--language",
+            "rust",
+            "--description",
+            "Our API",
+        ])
+        .args(["--tag", "backend"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(temp_dir.path().join("scaffs/api.json")).unwrap())
+            .unwrap();
+
+    fs::remove_file(temp_dir.path().join("src/old.rs")).unwrap();
+    fs::write(temp_dir.path().join("src/new.rs"), "pub fn fresh() {}").unwrap();
+    fs::write(
+        temp_dir.path().join("src/lib.rs"),
+        "pub struct Config;\npub struct Extra;",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .args(["update", "api"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[+] src/new.rs"))
+        .stdout(predicate::str::contains("[-] src/old.rs"))
+        .stdout(predicate::str::contains(
+            "[~] src/lib.rs (1 item(s) added, 0 removed)",
+        ))
+        .stdout(predicate::str::contains(
+            "1 file(s) added, 1 removed, 1 changed",
+        ));
+
+    let updated: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(temp_dir.path().join("scaffs/api.json")).unwrap())
+            .unwrap();
+    assert_eq!(updated["description"], "Our API");
+    assert_eq!(updated["tags"], serde_json::json!(["backend"]));
+    assert_eq!(updated["created_at"], saved["created_at"]);
+    assert!(saved["updated_at"].is_null());
+    assert!(updated["updated_at"].is_string());
+    assert_eq!(updated["files"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_update_missing_scaff() {
+    let temp_dir = TempDir::new().unwrap();
+
+    scaff_cmd()
+        .args(["update", "missing"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Scaff 'missing' not found"));
+}
+
+#[test]
+fn test_scan_auto_detects_language() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Detected;").unwrap();
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {}").unwrap();
+    fs::write(temp_dir.path().join("app.js"), "function skipped() {}").unwrap();
+
+    scaff_cmd()
+        .args(["scan", "--auto"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Detected primary language: rust"))
+        .stdout(predicate::str::contains("lib.rs"))
+        .stdout(predicate::str::contains("skipped").not());
+
+    scaff_cmd()
+        .args(["save", "app", "--auto"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully saved pattern 'app'"));
+    let saved = fs::read_to_string(temp_dir.path().join("scaffs/app.json")).unwrap();
+    assert!(saved.contains("\"language\": \"Rust\""));
+
+    // Detection only counts files the filters keep, leaving a tie
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Detected;").unwrap();
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {}").unwrap();
+    fs::write(temp_dir.path().join("app.js"), "function kept() {}").unwrap();
+    scaff_cmd()
+        .args(["scan", "--auto", "--exclude", "main.rs"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No single primary language"));
+
+    scaff_cmd()
+        .args(["save", "both", "--auto", "--exclude", "main.rs"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("saving all languages"))
+        .stdout(predicate::str::contains(
+            "Successfully saved pattern 'both'",
+        ));
+    let saved = fs::read_to_string(temp_dir.path().join("scaffs/both.json")).unwrap();
+    assert!(saved.contains("\"language\": \"Rust/JavaScript\""));
+}
+
+#[test]
+fn test_scan_single_file() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Single;").unwrap();
+    fs::write(temp_dir.path().join("other.rs"), "struct Skipped;").unwrap();
+    fs::write(temp_dir.path().join("notes.txt"), "plain text").unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--file")
+        .arg("lib.rs")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Scan Results (Rust)"))
+        .stdout(predicate::str::contains("Single"))
+        .stdout(predicate::str::contains("Skipped").not());
+
+    scaff_cmd()
+        .args(["scan", "--file", "lib.rs", "--exclude", "*.rs"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "lib.rs is ignored or excluded by --include, --exclude or --max-file-size",
+        ));
+
+    scaff_cmd()
+        .args(["scan", "--file", "lib.rs", "--max-file-size", "4"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure();
+
+    // .scaffignore applies as it would to a scan of the directory
+    fs::write(temp_dir.path().join(".scaffignore"), "lib.rs\n").unwrap();
+    scaff_cmd()
+        .args(["scan", "--file", "lib.rs"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure();
+    scaff_cmd()
+        .args(["scan", "--file", "lib.rs", "--no-ignore"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    fs::remove_file(temp_dir.path().join(".scaffignore")).unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--file")
+        .arg("notes.txt")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No supported language for notes.txt, pass --language",
+        ));
+}
+
+#[test]
+fn test_scan_stdin() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = scaff_cmd()
+        .arg("scan")
+        .arg("--stdin")
+        .arg("--language")
+        .arg("js")
+        .arg("--format")
+        .arg("json")
+        .write_stdin("function piped() {}")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let file: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(file["path"], "<stdin>");
+    assert_eq!(file["functions"], serde_json::json!(["piped"]));
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--stdin")
+        .write_stdin("function piped() {}")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--stdin needs --language"));
+}
+
+#[test]
+fn test_save_with_base_path() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src/api")).unwrap();
+    fs::write(temp_dir.path().join("src/api/routes.rs"), "fn routes() {}").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("based")
+        .arg("--language")
+        .arg("rust")
+        .arg("--path")
+        .arg("./src")
+        .arg("--base-path")
+        .arg(".")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let saved = fs::read_to_string(temp_dir.path().join("scaffs/based.json")).unwrap();
+    let pattern: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    assert_eq!(pattern["files"][0]["path"], "src/api/routes.rs");
+}
+
+#[test]
+fn test_save_from_dir() {
+    let reference = TempDir::new().unwrap();
+    fs::create_dir_all(reference.path().join("src/models")).unwrap();
+    fs::write(
+        reference.path().join("src/models/user.rs"),
+        "pub struct User;",
+    )
+    .unwrap();
+    fs::write(reference.path().join("src/main.rs"), "fn main() {}").unwrap();
+    let workspace = TempDir::new().unwrap();
+
+    scaff_cmd()
+        .args(["save", "golden", "--language", "rust", "--from-dir"])
+        .arg(reference.path())
+        .current_dir(workspace.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Successfully saved pattern 'golden'",
+        ));
+
+    // Saved in the current directory, with paths relative to the scanned one
+    let saved = fs::read_to_string(workspace.path().join("scaffs/golden.json")).unwrap();
+    let pattern: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    let mut paths: Vec<&str> = pattern["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| file["path"].as_str().unwrap())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, vec!["src/main.rs", "src/models/user.rs"]);
+    assert!(!reference.path().join("scaffs").exists());
+
+    scaff_cmd()
+        .args(["validate", "golden", "--path"])
+        .arg(reference.path())
+        .current_dir(workspace.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Architecture is VALID"));
+
+    scaff_cmd()
+        .args(["save", "nothing", "--from-dir", "missing"])
+        .current_dir(workspace.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Directory 'missing' does not exist",
+        ));
+}
+
+#[test]
+fn test_validate_multiple_scaffs() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("api.rs"), "pub fn routes() {}").unwrap();
+    fs::write(temp_dir.path().join("models.rs"), "pub struct User;").unwrap();
+
+    for (name, include) in [("service", "api.rs"), ("shared", "models.rs")] {
+        scaff_cmd()
+            .args(["save", name, "--language", "rust", "--include", include])
+            .current_dir(temp_dir.path())
+            .assert()
+            .success();
+    }
+
+    scaff_cmd()
+        .args(["validate", "service", "shared"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Validation Summary (2 scaffs)"))
+        .stdout(predicate::str::contains("All 2 scaffs passed"));
+
+    fs::remove_file(temp_dir.path().join("models.rs")).unwrap();
+    scaff_cmd()
+        .args(["validate", "service", "shared"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[OK] service (100% conformance)"))
+        .stdout(predicate::str::contains("[FAIL] shared"))
+        .stdout(predicate::str::contains("1 of 2 scaffs failed"));
+
+    let output = scaff_cmd()
+        .args(["validate", "--all", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let results: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(
+        results
+            .iter()
+            .any(|result| result["scaff_name"] == "shared" && result["is_valid"] == false)
+    );
+
+    // A single failing scaff fails the run too
+    scaff_cmd()
+        .args(["validate", "shared"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure();
+
+    // Scaffs that couldn't be validated are reported rather than dropped
+    fs::write(temp_dir.path().join("models.rs"), "pub struct User;").unwrap();
+    let output = scaff_cmd()
+        .args(["validate", "shared", "gone", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let results: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["is_valid"], true);
+    assert_eq!(results[1]["scaff_name"], "gone");
+    assert!(
+        results[1]["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("not found"))
+    );
+}
+
+#[test]
+fn test_scan_only_files() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Listed;").unwrap();
+    fs::write(temp_dir.path().join("app.js"), "function skipped() {}").unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("rust")
+        .arg("--only-files")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs"))
+        .stdout(predicate::str::contains("app.js").not())
+        .stdout(predicate::str::contains("Listed").not());
+}
+
+#[test]
+fn test_scan_include_glob() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("main.rs"), "fn top_level() {}").unwrap();
+    fs::write(temp_dir.path().join("src/lib.rs"), "fn nested() {}").unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("rust")
+        .arg("--include")
+        .arg("src/**")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nested"))
+        .stdout(predicate::str::contains("top_level").not());
+}
+
+#[test]
+fn test_save_invalid_glob_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+    scaff_cmd()
+        .args([
+            "save",
+            "broken",
+            "--language",
+            "rust",
+            "--include",
+            "src/[a",
+        ])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid glob"));
+    assert!(!temp_dir.path().join("scaffs/broken.json").exists());
+}
+
+#[test]
+fn test_generate_invalid_only_glob_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {}").unwrap();
+    scaff_cmd()
+        .args(["save", "app", "--language", "rust"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    scaff_cmd()
+        .args(["generate", "app", "--output", "out", "--only", "["])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid glob"));
+    assert!(!temp_dir.path().join("out").exists());
+}
+
+#[test]
+fn test_scan_no_ignore() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("node_modules")).unwrap();
+    fs::write(
+        temp_dir.path().join("node_modules/foo.js"),
+        "function foo() {}",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("app.js"), "function app() {}").unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("javascript")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("app.js"))
+        .stdout(predicate::str::contains("foo.js").not());
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("javascript")
+        .arg("--no-ignore")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("node_modules/foo.js"));
+}
+
+#[test]
+fn test_schema() {
+    let output = scaff_cmd().arg("schema").output().unwrap();
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["title"], "CodePattern");
+    let required = schema["required"].as_array().unwrap();
+    assert!(required.contains(&serde_json::json!("language")));
+    assert!(schema["$defs"]["FilePattern"].is_object());
+}
+
+#[test]
+fn test_info() {
+    let temp_dir = TempDir::new().unwrap();
+    let scaffs_dir = temp_dir.path().canonicalize().unwrap().join("scaffs");
+
+    let assert = scaff_cmd()
+        .arg("info")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(scaffs_dir.display().to_string()))
+        .stdout(predicate::str::contains("Supported languages (14)"));
+
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+    for language in [
+        "Rust",
+        "JavaScript",
+        "TypeScript",
+        "Python",
+        "Java",
+        "Go",
+        "JSON",
+        "HTML",
+        "CSS",
+        "TOML",
+        "YAML",
+        "Markdown",
+    ] {
+        assert!(stdout.contains(language), "missing {}", language);
+    }
+}
+
 #[test]
 fn test_list_empty() {
     let temp_dir = TempDir::new().unwrap();
@@ -51,7 +1762,710 @@ fn test_list_empty() {
         .current_dir(temp_dir.path())
         .assert()
         .success()
-        .stdout(predicate::str::contains("No scaffs found"));
+        .stdout(predicate::str::contains("No scaffs found"));
+}
+
+#[test]
+fn test_list_detailed() {
+    let temp_dir = TempDir::new().unwrap();
+    let scaffs_dir = temp_dir.path().join("scaffs");
+    fs::create_dir_all(&scaffs_dir).unwrap();
+    fs::write(
+        scaffs_dir.join("stats.json"),
+        r#"{
+  "name": "stats",
+  "description": "Pattern for stats",
+  "language": "Rust",
+  "files": [
+    {
+      "path": "src/main.rs",
+      "extension": "rs",
+      "classes": [],
+      "functions": ["main", "run", "helper"],
+      "structs": ["Config", "App"],
+      "implementations": ["App"]
+    }
+  ],
+  "created_at": "2024-01-01T00:00:00Z"
+}"#,
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("list")
+        .arg("--detailed")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Items: 6"))
+        .stdout(predicate::str::contains("Structs: 2"))
+        .stdout(predicate::str::contains("Functions: 3"));
+}
+
+#[test]
+fn test_generate_output_ext() {
+    let temp_dir = TempDir::new().unwrap();
+    let scaffs_dir = temp_dir.path().join("scaffs");
+    fs::create_dir_all(&scaffs_dir).unwrap();
+    fs::write(
+        scaffs_dir.join("docs.json"),
+        r#"{
+  "name": "docs",
+  "description": "Pattern for docs",
+  "language": "Rust",
+  "files": [
+    {
+      "path": "src/lib.rs",
+      "extension": "rs",
+      "classes": [],
+      "functions": ["run"],
+      "structs": [],
+      "implementations": []
+    }
+  ],
+  "created_at": "2024-01-01T00:00:00Z"
+}"#,
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("docs")
+        .arg("--output-ext")
+        .arg("rs=txt")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let generated = temp_dir.path().join("generated/src");
+    assert!(generated.join("lib.txt").exists());
+    assert!(!generated.join("lib.rs").exists());
+}
+
+#[test]
+fn test_generate_dry_run() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Planned;").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("planned")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let output_dir = temp_dir.path().join("generated");
+    scaff_cmd()
+        .arg("generate")
+        .arg("planned")
+        .arg("--output")
+        .arg(&output_dir)
+        .arg("--dry-run")
+        .arg("--print")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs (new)"))
+        .stdout(predicate::str::contains("pub struct Planned"))
+        .stdout(predicate::str::contains(
+            "would write 2 new and overwrite 0",
+        ));
+
+    assert!(!output_dir.exists());
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("planned")
+        .arg("--print")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_generate_list_files() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("src/services")).unwrap();
+    fs::write(temp_dir.path().join("src/lib.rs"), "pub struct App;").unwrap();
+    fs::write(
+        temp_dir.path().join("src/services/user.rs"),
+        "pub fn find() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("listed")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let saved: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(temp_dir.path().join("scaffs/listed.json")).unwrap(),
+    )
+    .unwrap();
+    let output_dir = temp_dir.path().join("out");
+    let mut expected: Vec<String> = saved["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| {
+            output_dir
+                .join(file["path"].as_str().unwrap())
+                .display()
+                .to_string()
+        })
+        .collect();
+    // The module declaring services/user.rs comes along too
+    expected.push(output_dir.join("src/services/mod.rs").display().to_string());
+    expected.push(output_dir.join("Cargo.toml").display().to_string());
+
+    let output = scaff_cmd()
+        .arg("generate")
+        .arg("listed")
+        .arg("--output")
+        .arg(&output_dir)
+        .arg("--list-files")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let listed: Vec<String> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(listed, expected);
+    assert!(!output_dir.exists());
+
+    // A crate root that already declares the module isn't listed
+    fs::create_dir_all(output_dir.join("src")).unwrap();
+    fs::write(output_dir.join("src/lib.rs"), "pub mod services;\n").unwrap();
+    scaff_cmd()
+        .arg("generate")
+        .arg("listed")
+        .arg("--list-files")
+        .arg("--only")
+        .arg("src/services/**")
+        .arg("--output")
+        .arg(&output_dir)
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("user.rs"))
+        .stdout(predicate::str::contains("lib.rs").not());
+
+    // Files generation would skip as existing are only listed with --force
+    fs::create_dir_all(output_dir.join("src/services")).unwrap();
+    fs::write(output_dir.join("src/services/user.rs"), "// mine").unwrap();
+    for (force, listed) in [(false, false), (true, true)] {
+        let mut cmd = scaff_cmd();
+        cmd.arg("generate")
+            .arg("listed")
+            .arg("--list-files")
+            .arg("--only")
+            .arg("src/services/**")
+            .arg("--output")
+            .arg(&output_dir)
+            .current_dir(temp_dir.path());
+        if force {
+            cmd.arg("--force");
+        }
+        let output = cmd.output().unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout.contains("user.rs"), listed);
+    }
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("missing")
+        .arg("--list-files")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_generate_skips_existing_unless_forced() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Kept;").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("kept")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let output_dir = temp_dir.path().join("generated");
+    let generate = || {
+        let mut cmd = scaff_cmd();
+        cmd.arg("generate")
+            .arg("kept")
+            .arg("--output")
+            .arg(&output_dir)
+            .current_dir(temp_dir.path());
+        cmd
+    };
+
+    generate()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "(2 new, 0 overwritten, 0 skipped)",
+        ));
+
+    let generated = output_dir.join("lib.rs");
+    fs::write(&generated, "// edited by hand").unwrap();
+
+    generate()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "(0 new, 0 overwritten, 2 skipped)",
+        ))
+        .stdout(predicate::str::contains("--force"));
+    assert_eq!(fs::read_to_string(&generated).unwrap(), "// edited by hand");
+
+    // The project's own manifest survives --force
+    let manifest = output_dir.join("Cargo.toml");
+    fs::write(&manifest, "[dependencies]\nserde = \"1\"\n").unwrap();
+    generate()
+        .arg("--force")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "(0 new, 1 overwritten, 1 skipped)",
+        ))
+        .stdout(predicate::str::contains("--force").not());
+    assert!(
+        fs::read_to_string(&generated)
+            .unwrap()
+            .contains("pub struct Kept")
+    );
+    assert_eq!(
+        fs::read_to_string(&manifest).unwrap(),
+        "[dependencies]\nserde = \"1\"\n"
+    );
+}
+
+#[test]
+fn test_generate_diff() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Drifted;").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("drifted")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let output_dir = temp_dir.path().join("generated");
+    scaff_cmd()
+        .arg("generate")
+        .arg("drifted")
+        .arg("--output")
+        .arg(&output_dir)
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let generated = output_dir.join("lib.rs");
+    fs::write(&generated, "pub struct Renamed;\n").unwrap();
+
+    scaff_cmd()
+        .arg("generate")
+        .arg("drifted")
+        .arg("--output")
+        .arg(&output_dir)
+        .arg("--diff")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(on disk)"))
+        .stdout(predicate::str::contains("-pub struct Renamed;"))
+        .stdout(predicate::str::contains("+pub struct Drifted {"))
+        .stdout(predicate::str::contains("1 existing file(s) differ"));
+    assert_eq!(
+        fs::read_to_string(&generated).unwrap(),
+        "pub struct Renamed;\n"
+    );
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        "struct Shared;\nfn run() {}",
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("Shared Pattern")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let saved = temp_dir.path().join("scaffs/shared_pattern.json");
+    let original: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&saved).unwrap()).unwrap();
+
+    for export_file in ["shared.json", "shared.scaff"] {
+        scaff_cmd()
+            .arg("export")
+            .arg("Shared Pattern")
+            .arg("--output")
+            .arg(export_file)
+            .current_dir(temp_dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Exported scaff"));
+
+        fs::remove_file(&saved).unwrap();
+
+        scaff_cmd()
+            .arg("import")
+            .arg(export_file)
+            .current_dir(temp_dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Imported scaff 'Shared Pattern'"));
+
+        let imported: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&saved).unwrap()).unwrap();
+        assert_eq!(imported, original);
+
+        scaff_cmd()
+            .arg("import")
+            .arg(export_file)
+            .current_dir(temp_dir.path())
+            .assert()
+            .failure()
+            .code(1)
+            .stdout(predicate::str::contains("already exists"));
+    }
+}
+
+#[test]
+fn test_export_import_failures() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("broken.json"), "{ not json").unwrap();
+
+    scaff_cmd()
+        .args(["export", "missing"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Failed to export scaff"));
+    assert!(!temp_dir.path().join("missing.json").exists());
+
+    for file in ["broken.json", "absent.json"] {
+        scaff_cmd()
+            .args(["import", file])
+            .current_dir(temp_dir.path())
+            .assert()
+            .failure()
+            .code(1)
+            .stdout(predicate::str::contains("Failed to import scaff"));
+    }
+}
+
+#[test]
+fn test_diff_scaffs() {
+    let temp_dir = TempDir::new().unwrap();
+    let lib = temp_dir.path().join("lib.rs");
+
+    for (name, source) in [
+        ("before", "fn run() {}\nfn stop() {}"),
+        ("after", "fn run() {}\nfn pause() {}"),
+    ] {
+        fs::write(&lib, source).unwrap();
+        scaff_cmd()
+            .arg("save")
+            .arg(name)
+            .arg("--language")
+            .arg("rust")
+            .current_dir(temp_dir.path())
+            .assert()
+            .success();
+    }
+
+    scaff_cmd()
+        .arg("diff")
+        .arg("before")
+        .arg("after")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[FILE] lib.rs"))
+        .stdout(predicate::str::contains("- function: stop"))
+        .stdout(predicate::str::contains("+ function: pause"))
+        .stdout(predicate::str::contains("function: run").not());
+
+    let output = scaff_cmd()
+        .arg("diff")
+        .arg("before")
+        .arg("after")
+        .arg("--format")
+        .arg("json")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    let diff: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diff["changed_files"][0]["removed"][0]["name"], "stop");
+    assert_eq!(diff["changed_files"][0]["added"][0]["name"], "pause");
+    assert_eq!(diff["added_files"], serde_json::json!([]));
+
+    scaff_cmd()
+        .args(["diff", "before", "missing"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Scaff 'missing' not found"));
+}
+
+#[test]
+fn test_show() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "struct Shown;\nfn run() {}").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("shown")
+        .arg("--language")
+        .arg("rust")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    // The only saved scaff is used when the name is omitted
+    scaff_cmd()
+        .arg("show")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Pattern: shown"))
+        .stdout(predicate::str::contains("Structs: Shown"));
+
+    let output = scaff_cmd()
+        .arg("show")
+        .arg("shown")
+        .arg("--json")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    let pattern: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(pattern["name"], "shown");
+    assert_eq!(pattern["files"][0]["structs"][0], "Shown");
+
+    scaff_cmd()
+        .args(["show", "shown", "--format", "mermaid"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("classDiagram\n"))
+        .stdout(predicate::str::contains("class lib_rs[\"lib.rs\"]"))
+        .stdout(predicate::str::contains("lib_rs : struct Shown"));
+
+    scaff_cmd()
+        .arg("show")
+        .arg("missing")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Scaff 'missing' not found"))
+        .stdout(predicate::str::contains("scaff list"));
+}
+
+#[test]
+fn test_generate_missing_scaff_fails() {
+    let temp_dir = TempDir::new().unwrap();
+
+    scaff_cmd()
+        .args(["generate", "missing", "--output", "out"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Failed to generate code"))
+        .stdout(predicate::str::contains(
+            "Make sure the scaff 'missing' exists",
+        ));
+    assert!(!temp_dir.path().join("out").exists());
+}
+
+#[test]
+fn test_migrate() {
+    let temp_dir = TempDir::new().unwrap();
+    let scaffs_dir = temp_dir.path().join("scaffs");
+    fs::create_dir_all(&scaffs_dir).unwrap();
+    fs::write(
+        scaffs_dir.join("old.json"),
+        r#"{
+  "name": "old",
+  "description": "Saved before schema versions",
+  "language": "Rust",
+  "files": [
+    {
+      "path": "src/lib.rs",
+      "extension": "rs",
+      "classes": [],
+      "functions": ["run"],
+      "structs": [],
+      "implementations": []
+    }
+  ],
+  "created_at": "2024-01-01T00:00:00Z"
+}"#,
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("migrate")
+        .arg("--all")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Migrated 'old' from schema version 1 to 3",
+        ));
+
+    let migrated: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(scaffs_dir.join("old.json")).unwrap()).unwrap();
+    assert_eq!(migrated["schema_version"], 3);
+    assert_eq!(migrated["unqualified_modules"], true);
+    assert_eq!(migrated["files"][0]["functions"][0], "run");
+    assert_eq!(migrated["files"][0]["enums"], serde_json::json!([]));
+
+    scaff_cmd()
+        .arg("migrate")
+        .arg("old")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already use schema version 3"));
+}
+
+#[test]
+fn test_scaff_dir_env_and_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let env_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "fn helper() {}").unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("env_pattern")
+        .arg("--language")
+        .arg("rust")
+        .env("SCAFF_DIR", env_dir.path())
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    assert!(env_dir.path().join("env_pattern.json").exists());
+    assert!(!temp_dir.path().join("scaffs").exists());
+
+    scaff_cmd()
+        .arg("list")
+        .env("SCAFF_DIR", env_dir.path())
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("env_pattern"));
+
+    fs::create_dir_all(temp_dir.path().join(".scaff")).unwrap();
+    fs::write(
+        temp_dir.path().join(".scaff/config.json"),
+        r#"{ "scaff_dir": "architecture" }"#,
+    )
+    .unwrap();
+
+    scaff_cmd()
+        .arg("save")
+        .arg("config_pattern")
+        .arg("--language")
+        .arg("rust")
+        .env_remove("SCAFF_DIR")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    assert!(
+        temp_dir
+            .path()
+            .join("architecture/config_pattern.json")
+            .exists()
+    );
+
+    // The environment variable takes precedence over the config
+    scaff_cmd()
+        .arg("list")
+        .env("SCAFF_DIR", env_dir.path())
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("config_pattern").not());
+}
+
+#[test]
+fn test_scaffs_dir_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    let flag_dir = TempDir::new().unwrap();
+    let env_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "fn helper() {}").unwrap();
+
+    scaff_cmd()
+        .args(["save", "flag_pattern", "--language", "rust", "--scaffs-dir"])
+        .arg(flag_dir.path())
+        .env("SCAFF_DIR", env_dir.path())
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    // The flag wins over SCAFF_DIR, and ./scaffs is never touched
+    assert!(flag_dir.path().join("flag_pattern.json").exists());
+    assert!(!env_dir.path().join("flag_pattern.json").exists());
+    assert!(!temp_dir.path().join("scaffs").exists());
+
+    scaff_cmd()
+        .arg("--scaffs-dir")
+        .arg(flag_dir.path())
+        .arg("list")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("flag_pattern"));
+
+    scaff_cmd()
+        .args(["validate", "flag_pattern", "--scaffs-dir"])
+        .arg(flag_dir.path())
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Architecture is VALID"));
+
+    scaff_cmd()
+        .arg("list")
+        .env_remove("SCAFF_DIR")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("flag_pattern").not());
 }
 
 #[test]
@@ -68,3 +2482,118 @@ fn test_save_pattern() {
         .assert()
         .success();
 }
+
+#[cfg(feature = "git-tests")]
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[cfg(feature = "git-tests")]
+#[test]
+fn test_scan_changed_since() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("unchanged.rs"), "fn stable() {}").unwrap();
+    fs::write(dir.join("changed.rs"), "fn before() {}").unwrap();
+
+    git(dir, &["init", "-q"]);
+    git(dir, &["add", "."]);
+    git(
+        dir,
+        &[
+            "-c",
+            "user.name=scaff",
+            "-c",
+            "user.email=scaff@example.com",
+            "commit",
+            "-qm",
+            "initial",
+        ],
+    );
+
+    fs::write(dir.join("changed.rs"), "fn after() {}").unwrap();
+
+    scaff_cmd()
+        .arg("scan")
+        .arg("--language")
+        .arg("rust")
+        .arg("--changed-since")
+        .arg("HEAD")
+        .current_dir(dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("changed.rs"))
+        .stdout(predicate::str::contains("unchanged.rs").not());
+}
+
+#[cfg(feature = "git-tests")]
+#[test]
+fn test_validate_changed_since() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("unchanged.rs"), "fn stable() {}\nfn helper() {}").unwrap();
+    fs::write(dir.join("changed.rs"), "fn before() {}").unwrap();
+    scaff_cmd()
+        .args(["save", "layout", "--language", "rust"])
+        .current_dir(dir)
+        .assert()
+        .success();
+
+    // Deviates from the scaff, but isn't part of the change
+    fs::write(dir.join("unchanged.rs"), "fn stable() {}").unwrap();
+    git(dir, &["init", "-q"]);
+    git(dir, &["add", "."]);
+    git(
+        dir,
+        &[
+            "-c",
+            "user.name=scaff",
+            "-c",
+            "user.email=scaff@example.com",
+            "commit",
+            "-qm",
+            "initial",
+        ],
+    );
+
+    fs::write(dir.join("changed.rs"), "fn after() {}").unwrap();
+
+    scaff_cmd()
+        .args(["validate", "layout", "--since", "HEAD"])
+        .current_dir(dir)
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "Limiting validation to 1 files changed since HEAD",
+        ))
+        .stdout(predicate::str::contains("before"))
+        .stdout(predicate::str::contains("helper").not());
+
+    scaff_cmd()
+        .args(["validate", "layout"])
+        .current_dir(dir)
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("helper"));
+}
+
+#[test]
+fn test_scan_changed_since_outside_git_repo() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("lib.rs"), "fn run() {}").unwrap();
+
+    scaff_cmd()
+        .args(["scan", "--language", "rust", "--since", "main"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("isn't in a git repository"))
+        .stdout(predicate::str::contains("lib.rs"));
+}